@@ -3,14 +3,17 @@ use clap::Parser;
 use colored::Colorize;
 use log::{debug, info};
 use roblox_install::RobloxStudio;
-use std::{fs, path::PathBuf, process};
+use serde_json::json;
+use std::{fs, path::PathBuf, process, time::Instant};
 
+use super::WatcherArgs;
 use crate::{
 	argon_info,
 	config::Config,
+	constants::BUILD_DEBOUNCE_TIME,
 	core::Core,
 	ext::PathExt,
-	integration,
+	integration, logger,
 	program::{Program, ProgramName},
 	project::{self, Project},
 	sessions,
@@ -51,6 +54,13 @@ pub struct Build {
 	#[arg(short, long)]
 	ts: bool,
 
+	/// Project profile to apply
+	#[arg(long)]
+	profile: Option<String>,
+
+	#[command(flatten)]
+	watcher: WatcherArgs,
+
 	/// Run Argon asynchronously
 	#[arg(short = 'A', long = "async")]
 	run_async: bool,
@@ -65,6 +75,13 @@ impl Build {
 		let project_path = project::resolve(self.project.clone().unwrap_or_default())?;
 
 		Config::load_workspace(project_path.get_parent());
+
+		if let Some(profile) = &self.profile {
+			Config::new_mut().profile = profile.to_owned();
+		}
+
+		self.watcher.apply(&mut Config::new_mut());
+
 		let config = Config::new();
 
 		if self.watch && !self.argon_spawn && (self.run_async || config.run_async) {
@@ -85,6 +102,7 @@ impl Build {
 		}
 
 		let project = Project::load(&project_path)?;
+		project.verify_profile()?;
 
 		let mut xml = self.xml || config.build_xml;
 		let path = if self.plugin {
@@ -178,10 +196,13 @@ impl Build {
 			path.to_string().bold()
 		);
 
+		logger::emit("build", json!({ "project": project_path, "output": path }));
+
 		if let Some(path) = &sourcemap_path {
 			core.sourcemap(Some(path.clone()), false)?;
 
 			argon_info!("Generated sourcemap at: {}", path.to_string().bold());
+			logger::emit("sourcemap", json!({ "project": project_path, "output": path }));
 		}
 
 		if self.watch {
@@ -197,19 +218,48 @@ impl Build {
 					.spawn()?;
 			}
 
-			sessions::add(self.session, None, None, process::id(), config.run_async)?;
+			sessions::add(self.session, None, None, None, process::id(), config.run_async)?;
 
 			argon_info!("Watching for changes..");
 
 			let queue = core.queue();
 			queue.subscribe_internal().unwrap();
 
+			// Output and sourcemap may be written inside synced directories
+			let ignored: Vec<PathBuf> = [Some(path.clone()), sourcemap_path.clone()]
+				.into_iter()
+				.flatten()
+				.collect();
+
 			loop {
-				let _message = queue.get(0).unwrap();
+				// Merge bursts of changes into a single rebuild
+				let changes = core.wait_for_changes(BUILD_DEBOUNCE_TIME)?;
+
+				if !core.affects_build(&changes, &ignored) {
+					debug!("Skipping rebuild: changes do not affect the output");
+					continue;
+				}
 
 				info!("Rebuilding project..");
+
+				let start = Instant::now();
 				core.build(&path, xml)?;
 
+				argon_info!(
+					"Rebuilt project in {} ({} changes)",
+					format!("{:.2?}", start.elapsed()).bold(),
+					changes.total()
+				);
+
+				logger::emit(
+					"rebuild",
+					json!({
+						"output": path,
+						"changes": changes.total(),
+						"time": start.elapsed().as_secs_f64(),
+					}),
+				);
+
 				if let Some(path) = &sourcemap_path {
 					info!("Regenerating sourcemap..");
 					core.sourcemap(Some(path.clone()), false)?;
@@ -272,6 +322,13 @@ impl Build {
 			args.push(String::from("--ts"))
 		}
 
+		if let Some(profile) = self.profile {
+			args.push(String::from("--profile"));
+			args.push(profile)
+		}
+
+		args.extend(self.watcher.args());
+
 		Program::new(ProgramName::Argon).args(args).spawn()?;
 
 		Ok(())
@@ -0,0 +1,173 @@
+use anyhow::{bail, Result};
+use clap::Parser;
+use colored::Colorize;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+use crate::{
+	argon_info,
+	config::Config,
+	core::{diff, meta::Meta, Core},
+	ext::PathExt,
+	logger,
+	project::{self, Project},
+	util,
+};
+
+use super::syncback;
+
+/// Show differences between the project and Roblox place or model file
+#[derive(Parser)]
+pub struct Diff {
+	/// Project path
+	#[arg()]
+	project: Option<PathBuf>,
+
+	/// Input path
+	#[arg(short, long)]
+	input: Option<PathBuf>,
+
+	/// Whether to read default input in XML format (.rbxlx or .rbxmx)
+	#[arg(short, long)]
+	xml: bool,
+
+	/// Classes to hide, in addition to project syncback filter
+	#[arg(long, value_delimiter = ',')]
+	ignore_classes: Vec<String>,
+
+	/// Properties to hide, in addition to project syncback filter
+	#[arg(long, value_delimiter = ',')]
+	ignore_properties: Vec<String>,
+
+	/// Project profile to apply
+	#[arg(long)]
+	profile: Option<String>,
+}
+
+impl Diff {
+	pub fn main(self) -> Result<()> {
+		let project_path = project::resolve(self.project.clone().unwrap_or_default())?;
+
+		Config::load_workspace(project_path.get_parent());
+
+		if let Some(profile) = &self.profile {
+			Config::new_mut().profile = profile.to_owned();
+		}
+
+		if !project_path.exists() {
+			bail!(
+				"No project files found in {}",
+				project_path.get_parent().to_string().bold()
+			);
+		}
+
+		let project = Project::load(&project_path)?;
+		project.verify_profile()?;
+
+		let path = if let Some(path) = self.input.clone() {
+			path
+		} else {
+			self.get_default_file(&project)
+		}
+		.resolve()?;
+
+		if !path.exists() {
+			bail!("Input file {} does not exist", path.to_string().bold());
+		}
+
+		let ext = path.get_ext();
+
+		if ext.starts_with("rbxm") && project.is_place() {
+			bail!("Cannot compare model with place project");
+		} else if ext.starts_with("rbxl") && !project.is_place() {
+			bail!("Cannot compare place with plugin or model project");
+		}
+
+		let mut filter = Meta::from_project(&project).context.syncback_filter().clone();
+
+		filter.ignore_classes.extend(self.ignore_classes);
+		filter.ignore_properties.extend(self.ignore_properties);
+
+		let dom = syncback::read_file(&path)?;
+		let core = Core::new(project, false)?;
+
+		let changes = core.diff(&dom)?;
+		let report = diff::report(&changes, &core.tree(), &filter);
+
+		if util::env_output_json() {
+			logger::emit(
+				"diff",
+				json!({
+					"project": project_path,
+					"input": path,
+					"added": report.added,
+					"removed": report.removed,
+					"modified": report.modified,
+				}),
+			);
+
+			return Ok(());
+		}
+
+		if report.is_empty() {
+			argon_info!("Project is the same as {}", path.to_string().bold());
+			return Ok(());
+		}
+
+		for entry in &report.added {
+			println!("{} {} ({})", "+".green().bold(), entry.path.green(), entry.class);
+		}
+
+		for entry in &report.removed {
+			println!("{} {} ({})", "-".red().bold(), entry.path.red(), entry.class);
+		}
+
+		for entry in &report.modified {
+			println!("{} {} ({})", "~".blue().bold(), entry.path.blue(), entry.class);
+
+			for property in &entry.properties {
+				println!(
+					"    {}: {} -> {}",
+					property.name.bold(),
+					format_value(&property.old).red(),
+					format_value(&property.new).green()
+				);
+			}
+		}
+
+		println!();
+
+		argon_info!(
+			"Project differs from {}: {}, {} and {}",
+			path.to_string().bold(),
+			format!("{} added", report.added.len()).bold().green(),
+			format!("{} removed", report.removed.len()).bold().red(),
+			format!("{} modified", report.modified.len()).bold().blue(),
+		);
+
+		Ok(())
+	}
+
+	fn get_default_file(&self, project: &Project) -> PathBuf {
+		let ext = if project.is_place() {
+			if self.xml {
+				"rbxlx"
+			} else {
+				"rbxl"
+			}
+		} else if self.xml {
+			"rbxmx"
+		} else {
+			"rbxm"
+		};
+
+		PathBuf::from(format!("{}.{}", project.name, ext))
+	}
+}
+
+fn format_value(value: &Option<Value>) -> String {
+	match value {
+		Some(value) => value.to_string(),
+		None => String::from("none"),
+	}
+}
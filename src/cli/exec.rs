@@ -1,6 +1,9 @@
 use anyhow::Result;
 use clap::Parser;
-use reqwest::{blocking::Client, header::CONTENT_TYPE};
+use reqwest::{
+	blocking::Client,
+	header::{AUTHORIZATION, CONTENT_TYPE},
+};
 use serde::Serialize;
 use std::{fs, path::MAIN_SEPARATOR};
 
@@ -65,13 +68,16 @@ impl Exec {
 					},
 				})?;
 
-				let response = Client::default()
+				let mut request = Client::default()
 					.post(url)
 					.header(CONTENT_TYPE, "application/msgpack")
-					.body(body)
-					.send();
+					.body(body);
 
-				match response {
+				if let Some(auth) = session.get_auth_header() {
+					request = request.header(AUTHORIZATION, auth);
+				}
+
+				match request.send().and_then(|response| response.error_for_status()) {
 					Ok(_) => argon_info!("Code executed successfully!"),
 					Err(err) => argon_error!("Code execution failed: {}", err),
 				}
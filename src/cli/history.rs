@@ -0,0 +1,339 @@
+use anyhow::{bail, Context, Result};
+use chrono::{Local, NaiveDateTime, NaiveTime, TimeZone};
+use clap::{Parser, ValueEnum};
+use colored::Colorize;
+use serde_json::json;
+use std::{fs, path::PathBuf};
+
+use crate::{
+	argon_info, argon_warn,
+	config::Config,
+	ext::PathExt,
+	history::{self, Change, DiffLine, Entry, History as LocalHistory, Record},
+	logger, project, util,
+	vfs::Vfs,
+};
+
+/// Number of unchanged lines shown around every change
+const DIFF_CONTEXT: usize = 2;
+
+/// List, compare and restore files modified by two-way sync
+#[derive(Parser)]
+pub struct History {
+	/// Whether to `list`, `diff` or `restore` history entries
+	#[arg(hide_possible_values = true)]
+	mode: Option<HistoryMode>,
+
+	/// Entry ID or time (`HH:MM[:SS]` or `YYYY-MM-DD HH:MM[:SS]`) to go back to
+	#[arg()]
+	entry: Option<String>,
+
+	/// Only include files of this instance and its descendants (like `ReplicatedStorage/Shared`)
+	#[arg(short, long)]
+	instance: Option<String>,
+
+	/// Project path
+	#[arg(short, long)]
+	project: Option<PathBuf>,
+}
+
+impl History {
+	pub fn main(self) -> Result<()> {
+		let project_path = project::resolve(self.project.clone().unwrap_or_default())?;
+
+		Config::load_workspace(project_path.get_parent());
+
+		let history = LocalHistory::new(project_path.get_parent())?;
+		let entries = history.entries()?;
+
+		match self.mode.clone().unwrap_or_default() {
+			HistoryMode::List => self.list(&history, &entries),
+			HistoryMode::Diff => self.diff(&history, &entries),
+			HistoryMode::Restore => self.restore(&history, &entries),
+		}
+	}
+
+	fn list(&self, history: &LocalHistory, entries: &[Entry]) -> Result<()> {
+		let selected = if self.entry.is_some() {
+			self.select(entries)?
+		} else {
+			entries
+				.iter()
+				.flat_map(|entry| (0..entry.records.len()).map(move |index| (entry, index)))
+				.filter(|(entry, index)| self.matches(&entry.records[*index]))
+				.collect()
+		};
+
+		if util::env_output_json() {
+			let records = selected
+				.iter()
+				.map(|(entry, index)| {
+					let record = &entry.records[*index];
+
+					json!({
+						"entry": entry.id,
+						"time": entry.time().to_rfc3339(),
+						"path": history.path(record),
+						"instance": record.instance,
+						"change": record.change,
+						"isDir": record.is_dir,
+					})
+				})
+				.collect::<Vec<_>>();
+
+			logger::emit("history", json!({ "records": records }));
+
+			return Ok(());
+		}
+
+		if selected.is_empty() {
+			argon_info!("No history entries found");
+			return Ok(());
+		}
+
+		let mut last = None;
+
+		for (entry, index) in selected {
+			if last != Some(entry.id) {
+				println!(
+					"{} {}",
+					entry.id.to_string().bold(),
+					entry.time().format("%Y-%m-%d %H:%M:%S")
+				);
+
+				last = Some(entry.id);
+			}
+
+			let record = &entry.records[index];
+
+			println!("    {} {}", format_record(record), format_instance(record));
+		}
+
+		Ok(())
+	}
+
+	fn diff(&self, history: &LocalHistory, entries: &[Entry]) -> Result<()> {
+		let selected = self.select(entries)?;
+		let mut diffs = vec![];
+
+		for (entry, index) in selected {
+			let record = &entry.records[index];
+
+			if record.is_dir {
+				continue;
+			}
+
+			let old = entry.contents(index)?.unwrap_or_default();
+			let new = fs::read(history.path(record)).unwrap_or_default();
+
+			if old == new {
+				continue;
+			}
+
+			let old = String::from_utf8_lossy(&old).into_owned();
+			let new = String::from_utf8_lossy(&new).into_owned();
+
+			diffs.push((record, old, new));
+		}
+
+		if util::env_output_json() {
+			let diffs = diffs
+				.iter()
+				.map(|(record, old, new)| {
+					json!({
+						"path": history.path(record),
+						"instance": record.instance,
+						"lines": history::diff(old, new).iter().filter_map(|line| match line {
+							DiffLine::Same(_) => None,
+							DiffLine::Added(line) => Some(format!("+{}", line)),
+							DiffLine::Removed(line) => Some(format!("-{}", line)),
+						}).collect::<Vec<_>>(),
+					})
+				})
+				.collect::<Vec<_>>();
+
+			logger::emit("historyDiff", json!({ "diffs": diffs }));
+
+			return Ok(());
+		}
+
+		if diffs.is_empty() {
+			argon_info!("Files are the same as in history");
+			return Ok(());
+		}
+
+		for (record, old, new) in &diffs {
+			println!("{} {}", format_record(record), format_instance(record));
+
+			let lines = history::diff(old, new);
+
+			let is_shown = |index: usize| {
+				let start = index.saturating_sub(DIFF_CONTEXT);
+				let end = (index + DIFF_CONTEXT + 1).min(lines.len());
+
+				lines[start..end].iter().any(|line| !matches!(line, DiffLine::Same(_)))
+			};
+
+			let mut skipped = false;
+
+			for (index, line) in lines.iter().enumerate() {
+				if !is_shown(index) {
+					skipped = true;
+					continue;
+				}
+
+				if skipped {
+					println!("    {}", "...".dimmed());
+					skipped = false;
+				}
+
+				match line {
+					DiffLine::Same(line) => println!("      {}", line),
+					DiffLine::Added(line) => println!("    {} {}", "+".green().bold(), line.green()),
+					DiffLine::Removed(line) => println!("    {} {}", "-".red().bold(), line.red()),
+				}
+			}
+
+			println!();
+		}
+
+		Ok(())
+	}
+
+	fn restore(&self, history: &LocalHistory, entries: &[Entry]) -> Result<()> {
+		let selected = self.select(entries)?;
+
+		if selected.is_empty() {
+			argon_info!("Nothing to restore");
+			return Ok(());
+		}
+
+		let message = format!(
+			"You are about to restore {} files to their previous state. Do you want to continue?",
+			selected.len().to_string().bold()
+		);
+
+		if !logger::prompt(&message, true) {
+			return Ok(());
+		}
+
+		let vfs = Vfs::new(false);
+		let mut restored = vec![];
+
+		for (entry, index) in selected {
+			let record = &entry.records[index];
+
+			if history.restore(entry, index, &vfs)? {
+				restored.push(history.path(record));
+			} else {
+				argon_warn!(
+					"Path {} was created by this change, remove it manually if needed",
+					history.path(record).to_string().bold()
+				);
+			}
+		}
+
+		if util::env_output_json() {
+			logger::emit("restored", json!({ "paths": restored }));
+		} else {
+			argon_info!("Restored {} files", restored.len().to_string().bold());
+		}
+
+		Ok(())
+	}
+
+	/// Returns records of the given entry or, when time is given, records with the
+	/// oldest state of every path changed since that time. Without an entry the latest
+	/// one that matches the instance filter is used
+	fn select<'a>(&self, entries: &'a [Entry]) -> Result<Vec<(&'a Entry, usize)>> {
+		let selected = match &self.entry {
+			Some(entry) => {
+				if let Ok(id) = entry.parse::<u64>() {
+					let entry = entries
+						.iter()
+						.find(|entry| entry.id == id)
+						.with_context(|| format!("History entry {} does not exist", id))?;
+
+					(0..entry.records.len()).map(|index| (entry, index)).collect()
+				} else {
+					let time = parse_time(entry)?;
+					let first = entries.partition_point(|entry| entry.id < time);
+
+					history::originals(&entries[first..])
+				}
+			}
+			None => {
+				let entry = entries
+					.iter()
+					.rev()
+					.find(|entry| entry.records.iter().any(|record| self.matches(record)));
+
+				match entry {
+					Some(entry) => (0..entry.records.len()).map(|index| (entry, index)).collect(),
+					None => vec![],
+				}
+			}
+		};
+
+		Ok(selected
+			.into_iter()
+			.filter(|(entry, index)| self.matches(&entry.records[*index]))
+			.collect())
+	}
+
+	fn matches(&self, record: &Record) -> bool {
+		match &self.instance {
+			Some(instance) => record.matches(instance),
+			None => true,
+		}
+	}
+}
+
+/// Parses local time into Unix time in milliseconds,
+/// times without a date refer to the current day
+fn parse_time(time: &str) -> Result<u64> {
+	let date_time = NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S")
+		.or_else(|_| NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M"))
+		.or_else(|_| {
+			NaiveTime::parse_from_str(time, "%H:%M:%S")
+				.or_else(|_| NaiveTime::parse_from_str(time, "%H:%M"))
+				.map(|naive| Local::now().date_naive().and_time(naive))
+		});
+
+	let date_time = match date_time {
+		Ok(date_time) => date_time,
+		Err(_) => bail!("Invalid entry ID or time: {}", time.bold()),
+	};
+
+	let time = Local
+		.from_local_datetime(&date_time)
+		.earliest()
+		.with_context(|| format!("Time {} does not exist in local time zone", date_time))?;
+
+	Ok(time.timestamp_millis() as u64)
+}
+
+fn format_record(record: &Record) -> String {
+	let path = record.path.to_string();
+
+	match record.change {
+		Change::Created => format!("{} {}", "+".green().bold(), path.green()),
+		Change::Removed => format!("{} {}", "-".red().bold(), path.red()),
+		Change::Modified => format!("{} {}", "~".blue().bold(), path.blue()),
+	}
+}
+
+fn format_instance(record: &Record) -> String {
+	match &record.instance {
+		Some(instance) => format!("({})", instance),
+		None => String::new(),
+	}
+}
+
+#[derive(Clone, Default, ValueEnum)]
+enum HistoryMode {
+	#[default]
+	List,
+	Diff,
+	Restore,
+}
@@ -1,6 +1,7 @@
 use anyhow::Result;
 use clap::{ArgAction, Parser};
 use colored::Colorize;
+use serde_json::json;
 use std::path::PathBuf;
 
 use crate::{
@@ -135,6 +136,7 @@ impl Init {
 		workspace::init(workspace_config)?;
 
 		argon_info!("Successfully initialized project: {}", project_path.to_string().bold());
+		logger::emit("init", json!({ "project": project_path }));
 
 		stats::projects_created(1);
 
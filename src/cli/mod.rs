@@ -1,24 +1,28 @@
 use anyhow::Result;
-use clap::{ColorChoice, Parser, Subcommand};
+use clap::{Args, ColorChoice, Parser, Subcommand, ValueEnum};
 use clap_verbosity_flag::Verbosity;
 use env_logger::fmt::WriteStyle;
 use log::LevelFilter;
 use std::env;
 
-use crate::util;
+use crate::{config::Config, util};
 
 mod build;
 mod config;
 mod debug;
+mod diff;
 mod doc;
 mod exec;
+mod history;
 mod init;
 mod plugin;
 mod serve;
 mod sourcemap;
 mod stop;
 mod studio;
+mod syncback;
 mod update;
+mod validate;
 
 macro_rules! about {
 	() => {
@@ -58,7 +62,7 @@ pub struct Cli {
 	backtrace: bool,
 
 	#[arg(long, hide = true, global = true)]
-	profile: bool,
+	profiler: bool,
 
 	#[arg(long, hide = true, global = true)]
 	use_virtual: bool,
@@ -74,6 +78,23 @@ pub struct Cli {
 		hide_possible_values = true
 	)]
 	pub color: ColorChoice,
+
+	/// Output format: text, json
+	#[arg(
+		long,
+		global = true,
+		value_name = "FORMAT",
+		default_value = "text",
+		hide_default_value = true,
+		hide_possible_values = true
+	)]
+	output_format: OutputFormat,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum OutputFormat {
+	Text,
+	Json,
 }
 
 impl Cli {
@@ -81,8 +102,8 @@ impl Cli {
 		Cli::parse()
 	}
 
-	pub fn profile(&self) -> bool {
-		self.profile
+	pub fn profiler(&self) -> bool {
+		self.profiler
 	}
 
 	pub fn use_virtual(&self) -> bool {
@@ -125,12 +146,24 @@ impl Cli {
 		}
 	}
 
+	pub fn output_json(&self) -> bool {
+		if env::var("RUST_OUTPUT_JSON").is_ok() {
+			return util::env_output_json();
+		}
+
+		self.output_format == OutputFormat::Json
+	}
+
 	pub fn main(self) -> Result<()> {
 		match self.command {
 			Commands::Init(command) => command.main(),
 			Commands::Serve(command) => command.main(),
 			Commands::Build(command) => command.main(),
 			Commands::Sourcemap(command) => command.main(),
+			Commands::Syncback(command) => command.main(),
+			Commands::Validate(command) => command.main(),
+			Commands::Diff(command) => command.main(),
+			Commands::History(command) => command.main(),
 			Commands::Stop(command) => command.main(),
 			Commands::Studio(command) => command.main(),
 			Commands::Debug(command) => command.main(),
@@ -149,6 +182,10 @@ pub enum Commands {
 	Serve(serve::Serve),
 	Build(build::Build),
 	Sourcemap(sourcemap::Sourcemap),
+	Syncback(syncback::Syncback),
+	Validate(validate::Validate),
+	Diff(diff::Diff),
+	History(history::History),
 	Stop(stop::Stop),
 	Studio(studio::Studio),
 	Debug(debug::Debug),
@@ -158,3 +195,46 @@ pub enum Commands {
 	Config(config::Config),
 	Doc(doc::Doc),
 }
+
+/// File watcher options of the commands that watch for changes
+#[derive(Args)]
+pub struct WatcherArgs {
+	/// Detect file changes by polling instead of native file system events
+	#[arg(long)]
+	poll: bool,
+
+	/// Interval in milliseconds between file system scans (implies --poll)
+	#[arg(long, value_name = "MS")]
+	poll_interval: Option<u64>,
+}
+
+impl WatcherArgs {
+	/// Overrides watcher settings of the `config` with the given options
+	pub fn apply(&self, config: &mut Config) {
+		if !self.poll && self.poll_interval.is_none() {
+			return;
+		}
+
+		config.watcher = String::from("poll");
+
+		if let Some(interval) = self.poll_interval {
+			config.poll_interval = interval;
+		}
+	}
+
+	/// Returns the options as arguments of the spawned child process
+	pub fn args(&self) -> Vec<String> {
+		let mut args = vec![];
+
+		if self.poll {
+			args.push(String::from("--poll"));
+		}
+
+		if let Some(interval) = self.poll_interval {
+			args.push(String::from("--poll-interval"));
+			args.push(interval.to_string());
+		}
+
+		args
+	}
+}
@@ -2,18 +2,27 @@ use anyhow::{bail, Result};
 use clap::Parser;
 use colored::Colorize;
 use log::{debug, info};
-use std::{path::PathBuf, process, sync::Arc, thread};
+use serde_json::json;
+use std::{
+	io::{self, IsTerminal},
+	path::PathBuf,
+	process,
+	sync::Arc,
+	thread,
+};
+use uuid::Uuid;
 
+use super::WatcherArgs;
 use crate::{
 	argon_error, argon_info, argon_warn,
 	config::Config,
 	core::Core,
 	ext::PathExt,
-	integration,
+	integration, logger,
 	program::{Program, ProgramName},
 	project::{self, Project},
 	server::{self, Server},
-	sessions,
+	sessions, util,
 };
 
 /// Start local server and listen for file changes
@@ -27,6 +36,10 @@ pub struct Serve {
 	#[arg()]
 	session: Option<String>,
 
+	/// Additional place project to serve alongside the main one (can be repeated)
+	#[arg(long = "extra-project", value_name = "PROJECT")]
+	projects: Vec<PathBuf>,
+
 	/// Server host name
 	#[arg(short = 'H', long)]
 	host: Option<String>,
@@ -39,10 +52,25 @@ pub struct Serve {
 	#[arg(short, long)]
 	sourcemap: bool,
 
+	/// Require session token for requests that modify the project
+	#[arg(short, long)]
+	auth: bool,
+
+	/// Shared secret used as session token (implies --auth)
+	#[arg(short = 'T', long, env = "ARGON_SESSION_TOKEN", hide_env_values = true)]
+	token: Option<String>,
+
 	/// Whether to run using roblox-ts
 	#[arg(short, long)]
 	ts: bool,
 
+	/// Project profile to apply
+	#[arg(long)]
+	profile: Option<String>,
+
+	#[command(flatten)]
+	watcher: WatcherArgs,
+
 	/// Run Argon asynchronously
 	#[arg(short = 'A', long = "async")]
 	run_async: bool,
@@ -57,17 +85,20 @@ impl Serve {
 		let project_path = project::resolve(self.project.clone().unwrap_or_default())?;
 
 		Config::load_workspace(project_path.get_parent());
+
+		if let Some(profile) = &self.profile {
+			Config::new_mut().profile = profile.to_owned();
+		}
+
+		self.watcher.apply(&mut Config::new_mut());
+
 		let config = Config::new();
 
 		if !self.argon_spawn && (self.run_async || config.run_async) {
 			return self.spawn();
 		}
 
-		let sourcemap_path = if self.sourcemap || config.with_sourcemap {
-			Some(project_path.with_file_name("sourcemap.json"))
-		} else {
-			None
-		};
+		let with_sourcemap = self.sourcemap || config.with_sourcemap;
 
 		if !project_path.exists() {
 			bail!(
@@ -77,37 +108,76 @@ impl Serve {
 			);
 		}
 
-		let project = Project::load(&project_path)?;
+		let mut projects = vec![(project_path.clone(), Project::load(&project_path)?)];
+
+		for path in &self.projects {
+			let path = project::resolve(path.clone())?;
 
-		if !project.is_place() {
-			bail!("Cannot serve non-place project!");
+			if !path.exists() {
+				bail!("No project files found in {}", path.get_parent().to_string().bold());
+			}
+
+			let project = Project::load(&path)?;
+
+			if projects.iter().any(|(_, other)| other.name == project.name) {
+				bail!("Project name {} is already in use", project.name.bold());
+			}
+
+			projects.push((path, project));
 		}
 
-		let use_wally = config.use_wally || (config.detect_project && project.is_wally());
-		let use_ts = self.ts || config.ts_mode || (config.detect_project && project.is_ts());
+		for (path, project) in &projects {
+			project.verify_profile()?;
+
+			if !project.is_place() {
+				bail!("Cannot serve non-place project: {}", path.to_string().bold());
+			}
 
-		if use_wally {
-			integration::check_wally_packages(&project.workspace_dir)?;
+			let use_wally = config.use_wally || (config.detect_project && project.is_wally());
+			let use_ts = self.ts || config.ts_mode || (config.detect_project && project.is_ts());
+
+			if use_wally {
+				integration::check_wally_packages(&project.workspace_dir)?;
+			}
+
+			if use_ts {
+				debug!("Starting roblox-ts");
+
+				let child = Program::new(ProgramName::Npx)
+					.message("Failed to serve roblox-ts project")
+					.current_dir(path.get_parent())
+					.arg("rbxtsc")
+					.arg("--watch")
+					.spawn()?;
+
+				if child.is_none() {
+					return Ok(());
+				}
+			}
 		}
 
-		if use_ts {
-			debug!("Starting roblox-ts");
+		// All projects share one file watcher
+		let vfs = Arc::new(Core::new_vfs(true));
+		let mut cores = vec![];
 
-			let working_dir = project_path.get_parent();
+		for (index, (path, project)) in projects.into_iter().enumerate() {
+			// Extra projects can be located in the same directory as the main one
+			let sourcemap_path = if index == 0 {
+				path.with_file_name("sourcemap.json")
+			} else {
+				path.with_file_name(format!("{}.sourcemap.json", project.name))
+			};
 
-			let child = Program::new(ProgramName::Npx)
-				.message("Failed to serve roblox-ts project")
-				.current_dir(working_dir)
-				.arg("rbxtsc")
-				.arg("--watch")
-				.spawn()?;
+			let core = Arc::new(Core::with_vfs(project, vfs.clone())?);
 
-			if child.is_none() {
-				return Ok(());
+			if with_sourcemap {
+				Self::watch_sourcemap(core.clone(), sourcemap_path)?;
 			}
+
+			cores.push(core);
 		}
 
-		let core = Core::new(project, true)?;
+		let core = &cores[0];
 		let host = self.host.unwrap_or(core.host().unwrap_or(config.host.clone()));
 		let mut port = self.port.unwrap_or(core.port().unwrap_or(config.port));
 
@@ -131,40 +201,26 @@ impl Serve {
 			}
 		}
 
-		let core = Arc::new(core);
-
-		if let Some(path) = sourcemap_path {
-			let core = core.clone();
-			let queue = core.queue();
-
-			queue.subscribe_internal().unwrap();
-			core.sourcemap(Some(path.clone()), false)?;
-
-			argon_info!("Generated sourcemap at: {}", path.to_string().bold());
-
-			thread::spawn(move || loop {
-				let _message = queue.get(0).unwrap();
-
-				info!("Regenerating sourcemap..");
+		let is_generated = self.token.is_none() && (self.auth || config.require_auth);
 
-				match core.sourcemap(Some(path.clone()), false) {
-					Ok(()) => (),
-					Err(err) => {
-						argon_error!("Failed to regenerate sourcemap: {}", err);
-					}
-				}
-			});
-		}
+		let token = if is_generated {
+			Some(Uuid::new_v4().simple().to_string())
+		} else {
+			self.token
+		};
 
 		sessions::add(
 			self.session,
 			Some(host.clone()),
 			Some(port),
+			token.clone(),
 			process::id(),
 			config.run_async,
 		)?;
 
-		let server = Server::new(core, &host, port);
+		let names = cores.iter().map(|core| core.name()).collect::<Vec<_>>();
+
+		let server = Server::new(cores, &host, port).with_token(token.clone());
 
 		argon_info!(
 			"Serving on: {}, project: {}",
@@ -172,11 +228,58 @@ impl Serve {
 			project_path.to_string().bold()
 		);
 
+		if names.len() > 1 {
+			let names = names.iter().skip(1).map(|name| name.bold().to_string());
+			argon_info!("Also serving: {}", names.collect::<Vec<_>>().join(", "));
+		}
+
+		logger::emit(
+			"serve",
+			json!({
+				"address": server::format_address(&host, port),
+				"project": project_path,
+				"projects": names,
+			}),
+		);
+
+		// Token is stored in the session file, so it's shown only
+		// to the user and never ends up in logs or redirected output
+		if let Some(token) = token.filter(|_| is_generated) {
+			if io::stdout().is_terminal() && !util::env_output_json() {
+				println!("Session token: {}", token.bold());
+			}
+		}
+
 		server.start()?;
 
 		Ok(())
 	}
 
+	fn watch_sourcemap(core: Arc<Core>, path: PathBuf) -> Result<()> {
+		let queue = core.queue();
+
+		queue.subscribe_internal().unwrap();
+		core.sourcemap(Some(path.clone()), false)?;
+
+		argon_info!("Generated sourcemap at: {}", path.to_string().bold());
+		logger::emit("sourcemap", json!({ "project": core.project().path, "output": path }));
+
+		thread::spawn(move || loop {
+			let _message = queue.get(0).unwrap();
+
+			info!("Regenerating sourcemap..");
+
+			match core.sourcemap(Some(path.clone()), false) {
+				Ok(()) => (),
+				Err(err) => {
+					argon_error!("Failed to regenerate sourcemap: {}", err);
+				}
+			}
+		});
+
+		Ok(())
+	}
+
 	fn spawn(self) -> Result<()> {
 		let mut args = vec![String::from("serve")];
 
@@ -188,6 +291,11 @@ impl Serve {
 			args.push(session);
 		}
 
+		for project in self.projects {
+			args.push(String::from("--extra-project"));
+			args.push(project.to_string());
+		}
+
 		if let Some(host) = self.host {
 			args.push(String::from("--host"));
 			args.push(host)
@@ -202,11 +310,29 @@ impl Serve {
 			args.push(String::from("--sourcemap"));
 		}
 
+		if self.auth {
+			args.push(String::from("--auth"));
+		}
+
 		if self.ts {
 			args.push(String::from("--ts"));
 		}
 
-		Program::new(ProgramName::Argon).args(args).spawn()?;
+		if let Some(profile) = self.profile {
+			args.push(String::from("--profile"));
+			args.push(profile);
+		}
+
+		args.extend(self.watcher.args());
+
+		let mut program = Program::new(ProgramName::Argon);
+
+		// Passed through environment, as arguments are visible in the process list
+		if let Some(token) = &self.token {
+			program.env("ARGON_SESSION_TOKEN", token);
+		}
+
+		program.args(args).spawn()?;
 
 		Ok(())
 	}
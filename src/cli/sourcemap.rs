@@ -2,13 +2,16 @@ use anyhow::{bail, Result};
 use clap::Parser;
 use colored::Colorize;
 use log::info;
+use serde_json::json;
 use std::{path::PathBuf, process};
 
+use super::WatcherArgs;
 use crate::{
 	argon_info,
 	config::Config,
 	core::Core,
 	ext::PathExt,
+	logger,
 	program::{Program, ProgramName},
 	project::{self, Project},
 	sessions,
@@ -37,6 +40,9 @@ pub struct Sourcemap {
 	#[arg(short, long)]
 	non_scripts: bool,
 
+	#[command(flatten)]
+	watcher: WatcherArgs,
+
 	/// Run Argon asynchronously
 	#[arg(short = 'A', long = "async")]
 	run_async: bool,
@@ -51,6 +57,9 @@ impl Sourcemap {
 		let project_path = project::resolve(self.project.clone().unwrap_or_default())?;
 
 		Config::load_workspace(project_path.get_parent());
+
+		self.watcher.apply(&mut Config::new_mut());
+
 		let config = Config::new();
 
 		if self.watch && !self.argon_spawn && (self.run_async || config.run_async) {
@@ -87,10 +96,15 @@ impl Sourcemap {
 				project_path.to_string().bold(),
 				output.resolve()?.to_string().bold()
 			);
+
+			logger::emit(
+				"sourcemap",
+				json!({ "project": project_path, "output": output.resolve()? }),
+			);
 		}
 
 		if self.watch {
-			sessions::add(self.session, None, None, process::id(), config.run_async)?;
+			sessions::add(self.session, None, None, None, process::id(), config.run_async)?;
 
 			if self.output.is_some() {
 				argon_info!("Watching for changes..");
@@ -134,6 +148,8 @@ impl Sourcemap {
 			args.push(String::from("--non-scripts"))
 		}
 
+		args.extend(self.watcher.args());
+
 		Program::new(ProgramName::Argon).args(args).spawn()?;
 
 		Ok(())
@@ -1,9 +1,14 @@
 use anyhow::Result;
 use clap::Parser;
 use colored::Colorize;
-use reqwest::blocking::Client;
+use reqwest::{blocking::Client, header::AUTHORIZATION};
 
-use crate::{argon_info, argon_warn, logger::Table, sessions, util};
+use crate::{
+	argon_info, argon_warn,
+	logger::Table,
+	sessions::{self, Session},
+	util,
+};
 
 /// Stop Argon session by address, ID or all running sessions
 #[derive(Parser)]
@@ -72,7 +77,7 @@ impl Stop {
 
 			for (_, session) in sessions {
 				if let Some(address) = session.get_address() {
-					Self::make_request(&address, session.pid);
+					Self::make_request(&address, &session);
 				} else {
 					Self::kill_process(session.pid);
 				}
@@ -84,7 +89,7 @@ impl Stop {
 		if self.session.is_empty() {
 			if let Some(session) = sessions::get(None, self.host, self.port)? {
 				if let Some(address) = session.get_address() {
-					Self::make_request(&address, session.pid);
+					Self::make_request(&address, &session);
 				} else {
 					Self::kill_process(session.pid);
 				}
@@ -101,7 +106,7 @@ impl Stop {
 			} else {
 				for session in sessions.values() {
 					if let Some(address) = session.get_address() {
-						Self::make_request(&address, session.pid);
+						Self::make_request(&address, session);
 					} else {
 						Self::kill_process(session.pid);
 					}
@@ -114,13 +119,18 @@ impl Stop {
 		Ok(())
 	}
 
-	fn make_request(address: &String, pid: u32) {
+	fn make_request(address: &String, session: &Session) {
 		let url = format!("{}/stop", address);
+		let mut request = Client::new().post(url);
 
-		match Client::new().post(url).send() {
+		if let Some(auth) = session.get_auth_header() {
+			request = request.header(AUTHORIZATION, auth);
+		}
+
+		match request.send().and_then(|response| response.error_for_status()) {
 			Ok(_) => argon_info!("Stopped Argon session with address: {}", address.bold()),
 			Err(_) => {
-				Self::kill_process(pid);
+				Self::kill_process(session.pid);
 			}
 		}
 	}
@@ -0,0 +1,156 @@
+use anyhow::{bail, Result};
+use clap::Parser;
+use colored::Colorize;
+use rbx_dom_weak::WeakDom;
+use serde_json::json;
+use std::{
+	fs::File,
+	io::BufReader,
+	path::{Path, PathBuf},
+};
+
+use crate::{
+	argon_info,
+	config::Config,
+	core::{processor, Core},
+	ext::PathExt,
+	logger,
+	project::{self, Project},
+};
+
+/// Sync back Roblox binary or XML place or model into the project
+#[derive(Parser)]
+pub struct Syncback {
+	/// Project path
+	#[arg()]
+	project: Option<PathBuf>,
+
+	/// Input path
+	#[arg(short, long)]
+	input: Option<PathBuf>,
+
+	/// Whether to read default input in XML format (.rbxlx or .rbxmx)
+	#[arg(short, long)]
+	xml: bool,
+
+	/// Project profile to apply
+	#[arg(long)]
+	profile: Option<String>,
+}
+
+impl Syncback {
+	pub fn main(self) -> Result<()> {
+		let project_path = project::resolve(self.project.clone().unwrap_or_default())?;
+
+		Config::load_workspace(project_path.get_parent());
+
+		if let Some(profile) = &self.profile {
+			Config::new_mut().profile = profile.to_owned();
+		}
+
+		if !project_path.exists() {
+			bail!(
+				"No project files found in {}",
+				project_path.get_parent().to_string().bold()
+			);
+		}
+
+		let project = Project::load(&project_path)?;
+		project.verify_profile()?;
+
+		let path = if let Some(path) = self.input.clone() {
+			path
+		} else {
+			self.get_default_file(&project)
+		}
+		.resolve()?;
+
+		if !path.exists() {
+			bail!("Input file {} does not exist", path.to_string().bold());
+		}
+
+		let ext = path.get_ext();
+
+		if ext.starts_with("rbxm") && project.is_place() {
+			bail!("Cannot sync back model into place project");
+		} else if ext.starts_with("rbxl") && !project.is_place() {
+			bail!("Cannot sync back place into plugin or model project");
+		}
+
+		let dom = read_file(&path)?;
+
+		let core = Core::new(project, false)?;
+		let changes = core.diff(&dom)?;
+
+		if changes.is_empty() {
+			argon_info!("Project is already up to date with {}", path.to_string().bold());
+			return Ok(());
+		}
+
+		let (additions, updates, removals) = (changes.additions.len(), changes.updates.len(), changes.removals.len());
+
+		if !processor::confirm_changes(&changes) {
+			argon_info!("Syncback aborted! No changes were applied");
+			return Ok(());
+		}
+
+		core.apply(changes)?;
+
+		argon_info!(
+			"Successfully synced back {} to project: {} ({}, {} and {})",
+			path.to_string().bold(),
+			project_path.to_string().bold(),
+			format!("{} additions", additions).bold().green(),
+			format!("{} updates", updates).bold().blue(),
+			format!("{} removals", removals).bold().red(),
+		);
+
+		logger::emit(
+			"syncback",
+			json!({
+				"input": path,
+				"project": project_path,
+				"additions": additions,
+				"updates": updates,
+				"removals": removals,
+			}),
+		);
+
+		Ok(())
+	}
+
+	fn get_default_file(&self, project: &Project) -> PathBuf {
+		let ext = if project.is_place() {
+			if self.xml {
+				"rbxlx"
+			} else {
+				"rbxl"
+			}
+		} else if self.xml {
+			"rbxmx"
+		} else {
+			"rbxm"
+		};
+
+		PathBuf::from(format!("{}.{}", project.name, ext))
+	}
+}
+
+/// Reads Roblox binary or XML place or model file
+pub(super) fn read_file(path: &Path) -> Result<WeakDom> {
+	let reader = BufReader::new(File::open(path)?);
+	let ext = path.get_ext();
+
+	Ok(match ext {
+		"rbxl" | "rbxm" => rbx_binary::from_reader(reader)?,
+		"rbxlx" | "rbxmx" => rbx_xml::from_reader_default(reader)?,
+		_ => bail!(
+			"Invalid file extension: {}. Only {}, {}, {}, {} extensions are allowed",
+			ext.bold(),
+			"rbxl".bold(),
+			"rbxlx".bold(),
+			"rbxm".bold(),
+			"rbxmx".bold(),
+		),
+	})
+}
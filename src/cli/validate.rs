@@ -0,0 +1,96 @@
+use anyhow::{bail, Result};
+use clap::Parser;
+use colored::Colorize;
+use std::path::PathBuf;
+
+use crate::{
+	argon_error, argon_info, argon_warn,
+	config::Config,
+	core::Core,
+	diagnostics::{self, Diagnostic},
+	ext::PathExt,
+	project::{self, Project},
+	util,
+};
+
+/// Check the project for problems without building it
+#[derive(Parser)]
+pub struct Validate {
+	/// Project path
+	#[arg()]
+	project: Option<PathBuf>,
+
+	/// Fail on warnings as well
+	#[arg(short, long)]
+	strict: bool,
+
+	/// Project profile to apply
+	#[arg(long)]
+	profile: Option<String>,
+}
+
+impl Validate {
+	pub fn main(self) -> Result<()> {
+		let project_path = project::resolve(self.project.unwrap_or_default())?;
+
+		Config::load_workspace(project_path.get_parent());
+
+		if let Some(profile) = &self.profile {
+			Config::new_mut().profile = profile.to_owned();
+		}
+
+		if !project_path.exists() {
+			bail!(
+				"No project files found in {}",
+				project_path.get_parent().to_string().bold()
+			);
+		}
+
+		diagnostics::collect();
+
+		let core = Project::load(&project_path).and_then(|project| {
+			project.verify_profile()?;
+			Core::new(project, false)
+		});
+
+		let mut diagnostics = diagnostics::take();
+
+		match core {
+			Ok(core) => diagnostics.extend(diagnostics::check_tree(&core.tree(), &core.project())),
+			Err(err) => diagnostics.push(Diagnostic::error(&err.to_string(), &project_path)),
+		}
+
+		let errors = diagnostics.iter().filter(|diagnostic| diagnostic.is_error()).count();
+		let warnings = diagnostics.len() - errors;
+
+		for diagnostic in &diagnostics {
+			if util::env_output_json() {
+				diagnostic.emit();
+			} else if diagnostic.is_error() {
+				argon_error!("{}", diagnostic);
+			} else {
+				argon_warn!("{}", diagnostic);
+			}
+		}
+
+		if diagnostics.is_empty() {
+			argon_info!("No problems found in project: {}", project_path.to_string().bold());
+			return Ok(());
+		}
+
+		if errors > 0 || (self.strict && warnings > 0) {
+			bail!(
+				"Validation failed with {} and {}",
+				format!("{} errors", errors).bold().red(),
+				format!("{} warnings", warnings).bold().yellow(),
+			);
+		}
+
+		argon_info!(
+			"Validation passed with {}",
+			format!("{} warnings", warnings).bold().yellow()
+		);
+
+		Ok(())
+	}
+}
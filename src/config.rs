@@ -53,6 +53,8 @@ pub struct Config {
 	pub run_async: bool,
 	/// Scan for the first available port if selected one is in use
 	pub scan_ports: bool,
+	/// Require session token for requests that modify the project or control the server
+	pub require_auth: bool,
 	/// Automatically detect project type
 	pub detect_project: bool,
 	/// Use smart path resolver when running commands
@@ -82,8 +84,22 @@ pub struct Config {
 	pub move_to_bin: bool,
 	/// Number of changes allowed before prompting user for confirmation
 	pub changes_threshold: usize,
+	/// What to do with changes exceeding the threshold (prompt, accept, reject, client)
+	pub changes_policy: String,
+	/// Number of syncback batches kept in local history (0 to disable)
+	pub history_size: usize,
 	/// Maximum number of unsynced changes before showing a warning
 	pub max_unsynced_changes: u16,
+	/// Derive instance IDs from their source so they persist across restarts
+	pub stable_ids: bool,
+	/// Project profile to apply when loading project files
+	pub profile: String,
+	/// File watcher to use when serving or building (native, poll)
+	pub watcher: String,
+	/// Interval in milliseconds between file system scans when polling
+	pub poll_interval: u64,
+	/// Compare file contents when polling, not only modification time and size
+	pub poll_hash: bool,
 
 	/// Use .lua file extension instead of .luau when writing scripts
 	pub lua_extension: bool,
@@ -112,6 +128,7 @@ impl Default for Config {
 
 			run_async: false,
 			scan_ports: true,
+			require_auth: false,
 			detect_project: true,
 			smart_paths: false,
 			with_sourcemap: false,
@@ -128,7 +145,14 @@ impl Default for Config {
 			rename_instances: true,
 			move_to_bin: false,
 			changes_threshold: 5,
+			changes_policy: String::from("prompt"),
+			history_size: 100,
 			max_unsynced_changes: 10,
+			stable_ids: false,
+			profile: String::new(),
+			watcher: String::from("native"),
+			poll_interval: 500,
+			poll_hash: false,
 
 			lua_extension: false,
 			line_ending: String::from("LF"),
@@ -22,10 +22,20 @@ pub const MAX_PAYLOAD_SIZE: usize = 536_870_912;
 /// the client request and sending back an empty `Changes`
 pub const QUEUE_TIMEOUT: Duration = Duration::from_secs(60);
 
-// VFS events will be ignored for this amount of time
-// after the last change that has been made by the client,
-// this saves a lot of computing time
-pub const SYNCBACK_DEBOUNCE_TIME: Duration = Duration::from_millis(200);
+/// How often the server should send a keep-alive
+/// comment to the clients connected to `/stream`
+/// when there are no messages in the queue
+pub const STREAM_KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+// Number of the most recent queue messages kept in memory,
+// so clients that reconnect can receive changes they missed
+// instead of requesting the full snapshot of the project
+pub const QUEUE_HISTORY_SIZE: usize = 256;
+
+// Changes that appear in the queue within this time
+// are merged together when building in watch mode,
+// so bursts of file changes trigger only one rebuild
+pub const BUILD_DEBOUNCE_TIME: Duration = Duration::from_millis(100);
 
 // Set of default sync rules that is used to determine
 // what middleware should be used to process a file
@@ -38,6 +48,9 @@ pub fn default_sync_rules() -> &'static Vec<SyncRule> {
 			SyncRule::new(Middleware::Project)
 				.with_pattern("*.project.json")
 				.with_child_pattern("default.project.json"),
+			SyncRule::new(Middleware::Project)
+				.with_pattern("*.project.toml")
+				.with_child_pattern("default.project.toml"),
 			SyncRule::new(Middleware::InstanceData)
 				.with_pattern("*.data.json")
 				.with_child_pattern(".data.json"),
@@ -0,0 +1,300 @@
+use rbx_dom_weak::{
+	types::{Ref, Variant},
+	WeakDom,
+};
+use rbx_reflection::{PropertyKind, PropertySerialization, Scriptability};
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+	collections::{BTreeSet, HashMap},
+	mem,
+};
+
+use super::{
+	changes::Changes,
+	meta::{Meta, SyncbackFilter},
+	snapshot::{Snapshot, UpdatedSnapshot},
+	tree::Tree,
+};
+use crate::{resolution::UnresolvedValue, util, Properties};
+
+/// Human readable summary of changes returned by [`diff`],
+/// described as changes that building the project would make to the DOM
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Report {
+	/// Instances that exist only in the project
+	pub added: Vec<InstanceEntry>,
+	/// Instances that exist only in the DOM
+	pub removed: Vec<InstanceEntry>,
+	/// Instances that exist in both but have different properties
+	pub modified: Vec<ModifiedEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InstanceEntry {
+	pub path: String,
+	pub class: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModifiedEntry {
+	pub path: String,
+	pub class: String,
+	pub properties: Vec<PropertyEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PropertyEntry {
+	pub name: String,
+	/// Value in the DOM, `None` if not set
+	pub old: Option<Value>,
+	/// Value in the project, `None` if not set
+	pub new: Option<Value>,
+}
+
+impl Report {
+	pub fn is_empty(&self) -> bool {
+		self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+	}
+}
+
+/// Compares the tree (starting with `id`) with the given DOM (starting with `dom_id`)
+/// and returns changes that have to be applied to the tree to match the DOM
+pub fn diff(tree: &Tree, id: Ref, dom: &WeakDom, dom_id: Ref) -> Changes {
+	let mut changes = Changes::new();
+	let mut ids = HashMap::new();
+
+	walk(tree, id, dom, dom_id, &mut changes, &mut ids);
+
+	// Refs can point to any instance, so properties
+	// are compared only once all instances are paired
+	for (&dom_id, &id) in &ids {
+		let instance = match tree.get_instance(id) {
+			Some(instance) => instance,
+			None => continue,
+		};
+
+		let dom_instance = dom.get_by_ref(dom_id).unwrap();
+		let properties = map_refs(filter_properties(&dom_instance.class, &dom_instance.properties), &ids);
+
+		if properties != filter_properties(&instance.class, &instance.properties) {
+			let mut snapshot = UpdatedSnapshot::new(id);
+			snapshot.properties = Some(properties);
+
+			changes.update(snapshot);
+		}
+	}
+
+	fn map_snapshot_refs(snapshot: &mut Snapshot, ids: &HashMap<Ref, Ref>) {
+		snapshot.properties = map_refs(mem::take(&mut snapshot.properties), ids);
+
+		for child in snapshot.children.iter_mut() {
+			map_snapshot_refs(child, ids);
+		}
+	}
+
+	for snapshot in changes.additions.iter_mut() {
+		snapshot.properties = map_refs(mem::take(&mut snapshot.properties), &ids);
+
+		for child in snapshot.children.iter_mut() {
+			map_snapshot_refs(child, &ids);
+		}
+	}
+
+	changes
+}
+
+/// Turns changes returned by [`diff`] into a report,
+/// skipping instances and properties matched by the `filter`
+pub fn report(changes: &Changes, tree: &Tree, filter: &SyncbackFilter) -> Report {
+	let mut report = Report::default();
+	let is_ignored = |name: &str, class: &str| filter.matches_name(name) || filter.matches_class(class);
+
+	// Instances that are only in the project are removed when
+	// syncing back, so they are the ones building would add
+	for &id in &changes.removals {
+		if let Some(instance) = tree.get_instance(id) {
+			if !is_ignored(&instance.name, &instance.class) {
+				report.added.push(InstanceEntry {
+					path: util::get_instance_path(tree.inner(), id).unwrap_or_default(),
+					class: instance.class.clone(),
+				});
+			}
+		}
+	}
+
+	for snapshot in &changes.additions {
+		if !is_ignored(&snapshot.name, &snapshot.class) {
+			let parent = util::get_instance_path(tree.inner(), snapshot.parent).unwrap_or_default();
+
+			report.removed.push(InstanceEntry {
+				path: if parent.is_empty() {
+					snapshot.name.clone()
+				} else {
+					format!("{}/{}", parent, snapshot.name)
+				},
+				class: snapshot.class.clone(),
+			});
+		}
+	}
+
+	for snapshot in &changes.updates {
+		let instance = match tree.get_instance(snapshot.id) {
+			Some(instance) if !is_ignored(&instance.name, &instance.class) => instance,
+			_ => continue,
+		};
+
+		let dom_properties = snapshot.properties.clone().unwrap_or_default();
+		let properties = filter_properties(&instance.class, &instance.properties);
+
+		let names = dom_properties
+			.keys()
+			.chain(properties.keys())
+			.filter(|property| !filter.matches_property(property))
+			.collect::<BTreeSet<_>>();
+
+		let render = |property: &str, value: Option<&Variant>| {
+			value.map(|value| match value {
+				Variant::Ref(id) => util::get_instance_path(tree.inner(), *id).map_or(Value::Null, Value::String),
+				_ => serde_json::to_value(UnresolvedValue::from_variant(value.clone(), &instance.class, property))
+					.unwrap_or(Value::Null),
+			})
+		};
+
+		let properties = names
+			.into_iter()
+			.filter(|&property| dom_properties.get(property) != properties.get(property))
+			.map(|property| PropertyEntry {
+				name: property.to_owned(),
+				old: render(property, dom_properties.get(property)),
+				new: render(property, properties.get(property)),
+			})
+			.collect::<Vec<_>>();
+
+		if !properties.is_empty() {
+			report.modified.push(ModifiedEntry {
+				path: util::get_instance_path(tree.inner(), snapshot.id).unwrap_or_default(),
+				class: instance.class.clone(),
+				properties,
+			});
+		}
+	}
+
+	report.added.sort_by(|a, b| a.path.cmp(&b.path));
+	report.removed.sort_by(|a, b| a.path.cmp(&b.path));
+	report.modified.sort_by(|a, b| a.path.cmp(&b.path));
+
+	report
+}
+
+fn walk(tree: &Tree, id: Ref, dom: &WeakDom, dom_id: Ref, changes: &mut Changes, ids: &mut HashMap<Ref, Ref>) {
+	let instance = tree.get_instance(id).unwrap();
+	let dom_instance = dom.get_by_ref(dom_id).unwrap();
+
+	ids.insert(dom_id, id);
+
+	let mut paired = vec![false; dom_instance.children().len()];
+
+	for &child_id in instance.children() {
+		let child = tree.get_instance(child_id).unwrap();
+
+		let dom_child = dom_instance.children().iter().enumerate().find(|(index, &dom_child)| {
+			if paired[*index] {
+				return false;
+			}
+
+			let dom_child = dom.get_by_ref(dom_child).unwrap();
+
+			if dom_child.name == child.name && dom_child.class == child.class {
+				paired[*index] = true;
+				return true;
+			}
+
+			false
+		});
+
+		if let Some((_, &dom_child)) = dom_child {
+			walk(tree, child_id, dom, dom_child, changes, ids);
+		} else {
+			changes.remove(child_id);
+		}
+	}
+
+	for (index, &dom_child) in dom_instance.children().iter().enumerate() {
+		if !paired[index] {
+			changes.add(new_snapshot(dom, dom_child, ids), id);
+		}
+	}
+}
+
+fn new_snapshot(dom: &WeakDom, id: Ref, ids: &mut HashMap<Ref, Ref>) -> Snapshot {
+	let instance = dom.get_by_ref(id).unwrap();
+	let new_id = Ref::new();
+
+	ids.insert(id, new_id);
+
+	Snapshot::new()
+		.with_id(new_id)
+		.with_meta(Meta::new())
+		.with_name(&instance.name)
+		.with_class(&instance.class)
+		.with_properties(filter_properties(&instance.class, &instance.properties))
+		.with_children(
+			instance
+				.children()
+				.iter()
+				.map(|&child| new_snapshot(dom, child, ids))
+				.collect(),
+		)
+}
+
+/// Replaces DOM referents of `Ref` properties with tree referents,
+/// skipping references to instances that are not synced
+fn map_refs(properties: Properties, ids: &HashMap<Ref, Ref>) -> Properties {
+	properties
+		.into_iter()
+		.filter_map(|(property, value)| match value {
+			Variant::Ref(id) => ids.get(&id).map(|id| (property, Variant::Ref(*id))),
+			_ => Some((property, value)),
+		})
+		.collect()
+}
+
+/// Returns only properties that can be synced back,
+/// skipping default values and properties not accessible by scripts
+pub fn filter_properties(class: &str, properties: &Properties) -> Properties {
+	let database = rbx_reflection_database::get();
+
+	let class = if let Some(class) = database.classes.get(class) {
+		class
+	} else {
+		return properties.clone();
+	};
+
+	properties
+		.iter()
+		.filter(|(property, value)| {
+			let descriptor = database
+				.superclasses(class)
+				.unwrap_or_default()
+				.into_iter()
+				.find_map(|class| class.properties.get(property.as_str()));
+
+			match descriptor {
+				Some(descriptor) => {
+					let is_scriptable = !matches!(descriptor.scriptability, Scriptability::None);
+					let is_serialized = !matches!(
+						descriptor.kind,
+						PropertyKind::Canonical {
+							serialization: PropertySerialization::DoesNotSerialize
+						}
+					);
+
+					is_scriptable && is_serialized && database.find_default_property(class, property) != Some(value)
+				}
+				None => false,
+			}
+		})
+		.map(|(property, value)| (property.to_owned(), value.to_owned()))
+		.collect()
+}
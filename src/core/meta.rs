@@ -1,16 +1,19 @@
+use colored::Colorize;
 use serde::{Deserialize, Serialize};
 use std::{
+	collections::HashMap,
 	fmt::Display,
 	path::{Path, PathBuf},
 };
 
 use crate::{
+	argon_warn,
 	config::Config,
 	constants::default_sync_rules,
 	ext::PathExt,
 	glob::Glob,
 	middleware::Middleware,
-	project::{Project, ProjectNode},
+	project::{Project, ProjectNode, SyncbackModel},
 };
 
 #[derive(Debug, Clone, PartialEq)]
@@ -76,6 +79,8 @@ pub enum SourceEntry {
 	Folder(PathBuf),
 	Data(PathBuf),
 	Project(PathBuf),
+	/// Path from the project that does not exist yet
+	Missing(PathBuf),
 }
 
 impl SourceEntry {
@@ -85,6 +90,17 @@ impl SourceEntry {
 			SourceEntry::Folder(path) => path,
 			SourceEntry::Data(path) => path,
 			SourceEntry::Project(path) => path,
+			SourceEntry::Missing(path) => path,
+		}
+	}
+
+	pub fn path_mut(&mut self) -> &mut PathBuf {
+		match self {
+			SourceEntry::File(path) => path,
+			SourceEntry::Folder(path) => path,
+			SourceEntry::Data(path) => path,
+			SourceEntry::Project(path) => path,
+			SourceEntry::Missing(path) => path,
 		}
 	}
 
@@ -97,7 +113,7 @@ impl SourceEntry {
 pub struct Source {
 	// Source used to rebuild the snapshot
 	inner: SourceKind,
-	// Existing paths associated with the instance
+	// Paths associated with the instance, existing or awaited
 	relevant: Vec<SourceEntry>,
 }
 
@@ -157,6 +173,10 @@ impl Source {
 		self.relevant.push(SourceEntry::Project(path.to_owned()))
 	}
 
+	pub fn add_missing(&mut self, path: &Path) {
+		self.relevant.push(SourceEntry::Missing(path.to_owned()))
+	}
+
 	pub fn set_data(&mut self, path: Option<&Path>) {
 		self.relevant.retain(|entry| !matches!(entry, SourceEntry::Data(_)));
 
@@ -208,6 +228,35 @@ impl Source {
 	pub fn paths(&self) -> Vec<&Path> {
 		self.relevant.iter().map(|entry| entry.path()).collect()
 	}
+
+	/// Replaces `from` prefix of all paths with `to`, project files are left untouched
+	pub fn move_paths(&mut self, from: &Path, to: &Path) {
+		if let SourceKind::Path(path) = &mut self.inner {
+			if let Ok(suffix) = path.strip_prefix(from) {
+				*path = join_suffix(to, suffix);
+			}
+		}
+
+		for entry in &mut self.relevant {
+			if let SourceEntry::Project(_) = entry {
+				continue;
+			}
+
+			let path = entry.path_mut();
+
+			if let Ok(suffix) = path.strip_prefix(from) {
+				*path = join_suffix(to, suffix);
+			}
+		}
+	}
+}
+
+fn join_suffix(path: &Path, suffix: &Path) -> PathBuf {
+	if suffix.as_os_str().is_empty() {
+		path.to_owned()
+	} else {
+		path.join(suffix)
+	}
 }
 
 impl Default for Source {
@@ -396,6 +445,54 @@ impl SyncbackFilter {
 	}
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelRule {
+	pub middleware: Middleware,
+	pub class: Option<String>,
+	pub pattern: Option<IgnoreRule>,
+}
+
+impl ModelRule {
+	/// Whether instance of the given class that would be written at `path`
+	/// (without file extension) should be collapsed into a single model file
+	pub fn matches(&self, class: &str, path: &Path) -> bool {
+		if self.class.is_none() && self.pattern.is_none() {
+			return false;
+		}
+
+		self.class.as_ref().is_none_or(|rule_class| rule_class == class)
+			&& self.pattern.as_ref().is_none_or(|pattern| pattern.matches(path))
+	}
+
+	pub fn from_models(models: &[SyncbackModel], path: &Path) -> Vec<Self> {
+		models
+			.iter()
+			.filter_map(|model| {
+				if model.middleware.is_model() {
+					Some(ModelRule {
+						middleware: model.middleware.clone(),
+						class: model.class.clone(),
+						pattern: model.pattern.clone().map(|pattern| IgnoreRule {
+							pattern,
+							path: path.to_owned(),
+						}),
+					})
+				} else {
+					argon_warn!(
+						"Syncback model type has to be {}, {} or {}, got: {}! Skipping..",
+						"JsonModel".bold(),
+						"RbxmModel".bold(),
+						"RbxmxModel".bold(),
+						model.middleware.to_string().bold()
+					);
+
+					None
+				}
+			})
+			.collect()
+	}
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Context {
 	/// Rules that define how files are synced
@@ -404,6 +501,8 @@ pub struct Context {
 	ignore_rules: Vec<IgnoreRule>,
 	/// Filter which ignores specific instances and properties
 	syncback_filter: SyncbackFilter,
+	/// Rules that define which instances are synced back as model files
+	model_rules: Vec<ModelRule>,
 	/// Whether to use legacy script context
 	legacy_scripts: bool,
 }
@@ -414,6 +513,7 @@ impl Context {
 			sync_rules: Vec::new(),
 			ignore_rules: Vec::new(),
 			syncback_filter: SyncbackFilter::default(),
+			model_rules: Vec::new(),
 			legacy_scripts: true,
 		}
 	}
@@ -461,6 +561,18 @@ impl Context {
 		&self.syncback_filter
 	}
 
+	pub fn model_rules(&self) -> &Vec<ModelRule> {
+		&self.model_rules
+	}
+
+	/// Returns model middleware for the given instance if it matches any model rule
+	pub fn model_middleware(&self, class: &str, path: &Path) -> Option<Middleware> {
+		self.model_rules
+			.iter()
+			.find(|rule| rule.matches(class, path))
+			.map(|rule| rule.middleware.clone())
+	}
+
 	pub fn use_legacy_scripts(&self) -> bool {
 		self.legacy_scripts
 	}
@@ -488,6 +600,13 @@ pub struct Meta {
 	pub original_name: Option<String>,
 	/// Custom Mesh Part source path
 	pub mesh_source: Option<String>,
+	/// Paths of instances referenced by properties,
+	/// resolved once the whole tree is built
+	#[serde(skip)]
+	pub refs: HashMap<String, String>,
+	/// Custom stable ID of the instance
+	#[serde(skip)]
+	pub id: Option<String>,
 }
 
 impl Meta {
@@ -500,10 +619,18 @@ impl Meta {
 			keep_unknowns: false,
 			original_name: None,
 			mesh_source: None,
+			refs: HashMap::new(),
+			id: None,
 		}
 	}
 
 	pub fn from_project(project: &Project) -> Self {
+		let model_rules = if let Some(syncback) = &project.syncback {
+			ModelRule::from_models(&syncback.models, &project.workspace_dir)
+		} else {
+			Vec::new()
+		};
+
 		let syncback_filter = if let Some(syncback) = &project.syncback {
 			SyncbackFilter {
 				ignore_rules: IgnoreRule::from_globs(syncback.ignore_globs.clone(), project.workspace_dir.clone()),
@@ -519,6 +646,7 @@ impl Meta {
 			sync_rules: project.sync_rules.clone(),
 			ignore_rules: IgnoreRule::from_globs(project.ignore_globs.clone(), project.workspace_dir.clone()),
 			syncback_filter,
+			model_rules,
 			legacy_scripts: project.legacy_scripts.unwrap_or(true),
 		};
 
@@ -553,6 +681,11 @@ impl Meta {
 		self
 	}
 
+	pub fn with_refs(mut self, refs: HashMap<String, String>) -> Self {
+		self.refs = refs;
+		self
+	}
+
 	// Overwriting meta fields
 
 	pub fn set_source<S: Into<Source>>(&mut self, source: S) {
@@ -574,4 +707,8 @@ impl Meta {
 	pub fn set_mesh_source(&mut self, mesh_source: Option<String>) {
 		self.mesh_source = mesh_source;
 	}
+
+	pub fn set_refs(&mut self, refs: HashMap<String, String>) {
+		self.refs = refs;
+	}
 }
@@ -1,25 +1,37 @@
 use anyhow::{bail, Result};
 use log::trace;
-use rbx_dom_weak::types::Ref;
+use rbx_dom_weak::{types::Ref, WeakDom};
 use serde::Serialize;
+use serde_json::json;
 use snapshot::AddedSnapshot;
 use std::{
-	env,
+	collections::HashSet,
 	fs::File,
 	io::BufWriter,
 	path::{Path, PathBuf},
 	sync::{Arc, Mutex, MutexGuard},
+	time::Duration,
 };
 
 use self::{
+	changes::Changes,
 	meta::{Meta, SourceEntry},
-	processor::Processor,
+	processor::{outcome::Outcomes, Processor},
 	queue::Queue,
 	tree::Tree,
 };
-use crate::{core::snapshot::Snapshot, lock, middleware::new_snapshot, project::Project, stats, util, vfs::Vfs};
+use crate::{
+	core::snapshot::Snapshot,
+	lock, logger,
+	middleware::new_snapshot,
+	project::Project,
+	server::{Message, SyncChanges},
+	stats, util,
+	vfs::Vfs,
+};
 
 pub mod changes;
+pub mod diff;
 pub mod meta;
 pub mod processor;
 pub mod queue;
@@ -31,22 +43,30 @@ pub struct Core {
 	tree: Arc<Mutex<Tree>>,
 	queue: Arc<Queue>,
 	processor: Arc<Processor>,
-	_vfs: Arc<Vfs>,
+	vfs: Arc<Vfs>,
 }
 
 impl Core {
-	#[profiling::function]
 	pub fn new(project: Project, watch: bool) -> Result<Self> {
-		profiling::start_frame!();
+		Self::with_vfs(project, Arc::new(Self::new_vfs(watch)))
+	}
 
+	pub fn new_vfs(watch: bool) -> Vfs {
 		trace!("Initializing VFS");
 
-		let vfs = if env::var("RUST_USE_VIRTUAL_FS").is_ok() {
+		if util::env_use_virtual() {
 			trace!("Using in-memory VFS");
-			Vfs::new_virtual()	
+			Vfs::new_virtual()
 		} else {
 			Vfs::new(watch)
-		};
+		}
+	}
+
+	/// Create core that uses already existing VFS,
+	/// so multiple projects can share the same watcher
+	#[profiling::function]
+	pub fn with_vfs(project: Project, vfs: Arc<Vfs>) -> Result<Self> {
+		profiling::start_frame!();
 
 		trace!("Snapshotting root project");
 
@@ -59,8 +79,10 @@ impl Core {
 
 		trace!("Building Tree and Queue");
 
-		let vfs = Arc::new(vfs);
-		let tree = Arc::new(Mutex::new(Tree::new(snapshot)));
+		let mut tree = Tree::new(snapshot);
+		tree.seed_hashes(&vfs);
+
+		let tree = Arc::new(Mutex::new(tree));
 		let queue = Arc::new(Queue::new());
 
 		trace!("Starting Processor");
@@ -80,7 +102,7 @@ impl Core {
 			tree,
 			queue,
 			processor,
-			_vfs: vfs,
+			vfs,
 		})
 	}
 
@@ -156,6 +178,32 @@ impl Core {
 		)
 	}
 
+	/// Compare the tree with the place or model DOM
+	pub fn diff(&self, dom: &WeakDom) -> Result<Changes> {
+		let tree = self.tree();
+
+		let dom_root = if self.project().is_place() {
+			dom.root_ref()
+		} else if let [root] = dom.root().children() {
+			*root
+		} else {
+			bail!("Model file has to contain exactly one root instance");
+		};
+
+		Ok(diff::diff(&tree, tree.root_ref(), dom, dom_root))
+	}
+
+	/// Apply changes to the tree and write them to the file system
+	pub fn apply(&self, changes: Changes) -> Result<()> {
+		let mut tree = self.tree();
+
+		self.vfs.pause();
+		let result = processor::write::apply_changes(changes, &mut tree, &self.vfs, &mut Outcomes::new());
+		self.vfs.resume();
+
+		result
+	}
+
 	/// Build the tree into a file, either XML or binary
 	pub fn build(&self, path: &Path, xml: bool) -> Result<()> {
 		let writer = BufWriter::new(File::create(path)?);
@@ -178,6 +226,75 @@ impl Core {
 		Ok(())
 	}
 
+	/// Waits for changes of the internal subscriber, bursts of
+	/// changes arriving within `debounce` are merged into one batch
+	pub fn wait_for_changes(&self, debounce: Duration) -> Result<Changes> {
+		let queue = self.queue();
+		let mut changes = Changes::new();
+
+		loop {
+			if let Some(Message::SyncChanges(SyncChanges(new_changes))) = queue.get(0)? {
+				changes.extend(new_changes);
+				break;
+			}
+		}
+
+		while let Some(message) = queue.try_get(0, debounce)? {
+			if let Message::SyncChanges(SyncChanges(new_changes)) = message {
+				changes.extend(new_changes);
+			}
+		}
+
+		Ok(changes)
+	}
+
+	/// Check whether the given changes affect the output of `build`,
+	/// changes of `ignored` files (like the build output itself) are skipped
+	pub fn affects_build(&self, changes: &Changes, ignored: &[PathBuf]) -> bool {
+		let tree = self.tree();
+		let root_ref = tree.root_ref();
+		let is_place = self.project().is_place();
+
+		let is_ignored = |meta: &Meta| {
+			let paths = meta.source.paths();
+			!paths.is_empty() && paths.iter().all(|path| ignored.iter().any(|ignored| path == ignored))
+		};
+
+		// Instances added and removed within the same batch never reach the output
+		let added: HashSet<Ref> = changes.additions.iter().map(|snapshot| snapshot.id).collect();
+		let removed: HashSet<Ref> = changes.removals.iter().copied().collect();
+
+		if changes
+			.additions
+			.iter()
+			.any(|snapshot| !removed.contains(&snapshot.id) && !is_ignored(&snapshot.meta))
+		{
+			return true;
+		}
+
+		if changes.removals.iter().any(|id| !added.contains(id)) {
+			return true;
+		}
+
+		changes.updates.iter().any(|snapshot| {
+			// Properties of the place root (DataModel) are not saved
+			if is_place && snapshot.id == root_ref {
+				return false;
+			}
+
+			// Instance was removed later in the same batch
+			if !tree.exists(snapshot.id) || tree.get_meta(snapshot.id).is_some_and(is_ignored) {
+				return false;
+			}
+
+			// Meta (like source paths) is used by sourcemap only
+			snapshot.name.is_some()
+				|| snapshot.class.is_some()
+				|| snapshot.parent.is_some()
+				|| snapshot.properties.is_some()
+		})
+	}
+
 	/// Write sourcemap of the tree
 	pub fn sourcemap(&self, path: Option<PathBuf>, non_scripts: bool) -> Result<()> {
 		let tree = lock!(&self.tree);
@@ -228,6 +345,8 @@ impl Core {
 		if let Some(path) = path {
 			let writer = BufWriter::new(File::create(path)?);
 			serde_json::to_writer(writer, &sourcemap)?;
+		} else if util::env_output_json() {
+			logger::emit("sourcemap", json!({ "sourcemap": sourcemap }));
 		} else {
 			println!("{}", serde_json::to_string(&sourcemap)?);
 		}
@@ -1,4 +1,6 @@
+use anyhow::Result;
 use colored::Colorize;
+use rbx_dom_weak::types::{Ref, Variant};
 use std::{
 	collections::HashMap,
 	path::{Path, PathBuf},
@@ -8,9 +10,15 @@ use uuid::Uuid;
 use crate::{
 	argon_error, argon_warn,
 	config::Config,
-	core::meta::{Meta, SyncbackFilter},
+	core::{
+		meta::{Meta, SourceKind, SyncbackFilter},
+		processor::outcome::{Outcome, Outcomes},
+		tree::Tree,
+	},
 	ext::PathExt,
+	middleware::Middleware,
 	resolution::UnresolvedValue,
+	util,
 	vfs::Vfs,
 	Properties,
 };
@@ -27,7 +35,7 @@ const FORBIDDEN_FILE_NAMES: [&str; 22] = [
 	"LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
 ];
 
-pub fn verify_name(name: &mut String, meta: &mut Meta) -> bool {
+pub fn verify_name(id: Ref, name: &mut String, meta: &mut Meta, outcomes: &mut Outcomes) -> bool {
 	let (messages, renamed) = {
 		let mut messages = vec![];
 		let mut name = name.clone();
@@ -118,6 +126,8 @@ pub fn verify_name(name: &mut String, meta: &mut Meta) -> bool {
 				messages.iter().map(|m| m.as_str()).collect::<Vec<&str>>().join(" & ")
 			);
 
+			outcomes.report(id, Outcome::Renamed { name: renamed.clone() });
+
 			meta.set_original_name(Some(name.to_owned()));
 			*name = renamed;
 
@@ -129,6 +139,13 @@ pub fn verify_name(name: &mut String, meta: &mut Meta) -> bool {
 				messages.iter().map(|m| m.as_str()).collect::<Vec<&str>>().join(" & ")
 			);
 
+			outcomes.report(
+				id,
+				Outcome::Skipped {
+					reason: String::from("name is not a valid file name"),
+				},
+			);
+
 			return false;
 		}
 	} else if meta.original_name.is_some() {
@@ -138,7 +155,14 @@ pub fn verify_name(name: &mut String, meta: &mut Meta) -> bool {
 	true
 }
 
-pub fn verify_path(path: &mut PathBuf, name: &mut String, meta: &mut Meta, vfs: &Vfs) -> bool {
+pub fn verify_path(
+	id: Ref,
+	path: &mut PathBuf,
+	name: &mut String,
+	meta: &mut Meta,
+	vfs: &Vfs,
+	outcomes: &mut Outcomes,
+) -> bool {
 	if !vfs.exists(path) || meta.source.get().path().is_some_and(|p| p == path) {
 		return true;
 	}
@@ -155,6 +179,8 @@ pub fn verify_path(path: &mut PathBuf, name: &mut String, meta: &mut Meta, vfs:
 			renamed_path.to_string().bold()
 		);
 
+		outcomes.report(id, Outcome::Renamed { name: renamed.clone() });
+
 		meta.set_original_name(Some(name.to_owned()));
 
 		*path = renamed_path;
@@ -167,6 +193,13 @@ pub fn verify_path(path: &mut PathBuf, name: &mut String, meta: &mut Meta, vfs:
 			path.to_string().bold()
 		);
 
+		outcomes.report(
+			id,
+			Outcome::Skipped {
+				reason: format!("path {} already exists", path.display()),
+			},
+		);
+
 		false
 	}
 }
@@ -195,6 +228,34 @@ pub fn serialize_properties(class: &str, properties: Properties) -> HashMap<Stri
 		.collect()
 }
 
+/// Returns paths of instances referenced by `Ref` properties,
+/// references to instances that are not in the tree are skipped
+pub fn locate_refs(properties: &Properties, tree: &Tree) -> HashMap<String, String> {
+	properties
+		.iter()
+		.filter_map(|(property, value)| match value {
+			Variant::Ref(id) if id.is_some() => {
+				util::get_instance_path(tree.inner(), *id).map(|path| (property.to_owned(), path))
+			}
+			_ => None,
+		})
+		.collect()
+}
+
+/// Replaces `Ref` properties with paths of the referenced instances
+/// so they can be serialized, unresolved references are dropped
+pub fn serialize_refs(properties: Properties, refs: &HashMap<String, String>) -> Properties {
+	properties
+		.into_iter()
+		.filter_map(|(property, value)| match value {
+			Variant::Ref(_) => refs
+				.get(&property)
+				.map(|path| (property, Variant::String(path.to_owned()))),
+			_ => Some((property, value)),
+		})
+		.collect()
+}
+
 pub fn rename_path(path: &Path, from: &str, to: &str) -> PathBuf {
 	path.with_file_name(format!(
 		"{}{}",
@@ -202,3 +263,38 @@ pub fn rename_path(path: &Path, from: &str, to: &str) -> PathBuf {
 		path.get_name().strip_prefix(from).unwrap_or_default()
 	))
 }
+
+/// Returns the closest instance (including the given one) that is stored in
+/// a model file (`.model.json`, `.rbxm` or `.rbxmx`) together with its path and middleware
+pub fn locate_model(id: Ref, tree: &Tree) -> Option<(Ref, PathBuf, Middleware)> {
+	let mut id = id;
+
+	loop {
+		let meta = tree.get_meta(id)?;
+
+		match meta.source.get() {
+			SourceKind::Path(_) => {
+				let path = meta.source.get_file()?.path();
+				let middleware = Middleware::from_path(path, &meta.context)?;
+
+				return if middleware.is_model() {
+					Some((id, path.to_owned(), middleware))
+				} else {
+					None
+				};
+			}
+			// Descendants of model files have no source on their own
+			SourceKind::None => id = tree.get_instance(id)?.parent(),
+			SourceKind::Project(_, _, _, _) => return None,
+		}
+	}
+}
+
+/// Writes the whole subtree of the model instance back to its file
+pub fn write_model(id: Ref, tree: &Tree, vfs: &Vfs) -> Result<()> {
+	if let Some((id, path, middleware)) = locate_model(id, tree) {
+		middleware.write_model(tree.inner(), id, &path, vfs)?;
+	}
+
+	Ok(())
+}
@@ -2,25 +2,32 @@ use anyhow::Result;
 use colored::Colorize;
 use crossbeam_channel::{select, Sender};
 use log::{debug, error, info, trace, warn};
+use rbx_dom_weak::types::Ref;
 use serde::Deserialize;
 use std::{
+	collections::{HashMap, VecDeque},
+	path::{Path, PathBuf},
 	sync::{Arc, Mutex},
 	thread::Builder,
 };
 
+use self::outcome::{Outcome, Outcomes};
 use super::{changes::Changes, queue::Queue, tree::Tree};
 use crate::{
-	argon_error,
+	argon_error, argon_warn,
 	config::Config,
 	constants::BLACKLISTED_PATHS,
+	ext::PathExt,
+	history::History,
 	lock, logger,
 	project::{Project, ProjectDetails},
-	server, stats,
-	vfs::{Vfs, VfsEvent},
+	server, stats, util,
+	vfs::{journal::Backup, Vfs, VfsEvent},
 };
 
 mod helpers;
 
+pub mod outcome;
 pub mod read;
 pub mod write;
 
@@ -31,8 +38,20 @@ pub struct WriteRequest {
 	pub client_id: u32,
 }
 
+/// Answer of the client to the `ConfirmChanges` message
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfirmRequest {
+	pub client_id: u32,
+	pub accept: bool,
+}
+
+/// Requests waiting for client confirmation, followed by the ones sent after them
+type Pending = HashMap<u32, VecDeque<WriteRequest>>;
+
 pub struct Processor {
 	writer: Sender<WriteRequest>,
+	confirmer: Sender<ConfirmRequest>,
 }
 
 impl Processor {
@@ -46,34 +65,48 @@ impl Processor {
 
 		let handler = handler.clone();
 		let (sender, receiver) = crossbeam_channel::unbounded();
+		let (confirm_sender, confirm_receiver) = crossbeam_channel::unbounded();
+		let listener = vfs.listener();
 
 		Builder::new()
 			.name("processor".to_owned())
 			.spawn(move || -> Result<()> {
-				let vfs_receiver = vfs.receiver();
 				let client_receiver = receiver;
+				let mut pending = Pending::new();
 
 				loop {
 					select! {
-						recv(vfs_receiver) -> event => {
+						recv(listener.receiver()) -> event => {
 							handler.on_vfs_event(event?);
 						}
 						recv(client_receiver) -> request => {
-							vfs.pause();
-							handler.on_client_event(request?);
-							vfs.resume();
+							listener.pause();
+							handler.on_client_event(request?, &mut pending);
+							listener.resume();
+						}
+						recv(confirm_receiver) -> request => {
+							listener.pause();
+							handler.on_client_confirm(request?, &mut pending);
+							listener.resume();
 						}
 					}
 				}
 			})
 			.unwrap();
 
-		Self { writer: sender }
+		Self {
+			writer: sender,
+			confirmer: confirm_sender,
+		}
 	}
 
 	pub fn write(&self, request: WriteRequest) {
 		self.writer.send(request).unwrap();
 	}
+
+	pub fn confirm(&self, request: ConfirmRequest) {
+		self.confirmer.send(request).unwrap();
+	}
 }
 
 struct Handler {
@@ -93,38 +126,40 @@ impl Handler {
 		let mut tree = lock!(self.tree);
 		let path = event.path();
 
-		let changes = {
-			if BLACKLISTED_PATHS.iter().any(|blacklisted| path.ends_with(blacklisted)) {
-				trace!("Processing of {:?} aborted: blacklisted", path);
-				return;
+		// Editors and formatters often save files without changing them
+		// and files written during syncback emit events as well
+		let hash = match &event {
+			VfsEvent::Write(path) | VfsEvent::Create(path) => {
+				self.vfs.read(path).ok().map(|contents| util::hash(&contents))
 			}
+			_ => None,
+		};
 
-			let ids = {
-				let mut current_path = path;
-
-				loop {
-					if let Some(ids) = tree.get_ids(current_path) {
-						break ids.to_owned();
-					}
+		if let Some(hash) = hash {
+			if !tree.update_hash(path, hash) {
+				trace!("Processing of {:?} skipped: contents unchanged", path);
+				return;
+			}
+		}
 
-					match current_path.parent() {
-						Some(parent) => current_path = parent,
-						None => {
-							trace!("No ID found for path {:?}", path);
-							return;
-						}
-					}
+		let mut changes = match &event {
+			VfsEvent::Rename { from, to } => match read::process_rename(from, to, &mut tree, &self.vfs) {
+				Some(changes) => changes,
+				None => {
+					let mut changes = self.process_path(&VfsEvent::Delete(from.to_owned()), &mut tree);
+					changes.extend(self.process_path(&VfsEvent::Create(to.to_owned()), &mut tree));
+					changes
 				}
-			};
-
-			let mut changes = Changes::new();
+			},
+			_ => self.process_path(&event, &mut tree),
+		};
 
-			for id in ids {
-				changes.extend(read::process_changes(id, &mut tree, &self.vfs));
-			}
+		read::relink_refs(&mut changes, &mut tree);
 
-			changes
-		};
+		// Newly created files are tracked only once they are processed
+		if let Some(hash) = hash {
+			tree.update_hash(path, hash);
+		}
 
 		if !changes.is_empty() {
 			stats::files_synced(changes.total() as u32);
@@ -141,8 +176,14 @@ impl Handler {
 			trace!("No changes detected when processing path: {:?}", path);
 		}
 
-		if lock!(self.project).path == path {
-			if let VfsEvent::Write(_) = event {
+		let is_project = |path: &Path| {
+			let project = lock!(self.project);
+			project.path == path || project.inherited.iter().any(|inherited| inherited == path)
+		};
+
+		match &event {
+			// Editors often save files by renaming temporary ones
+			VfsEvent::Write(path) | VfsEvent::Rename { to: path, .. } if is_project(path) => {
 				debug!("Project file was modified. Reloading project..");
 
 				match lock!(self.project).reload() {
@@ -158,71 +199,326 @@ impl Handler {
 					}
 					Err(err) => error!("Failed to reload project: {}", err),
 				}
-			} else if let VfsEvent::Delete(_) = event {
+			}
+			VfsEvent::Delete(path) | VfsEvent::Rename { from: path, .. } if is_project(path) => {
 				argon_error!("Warning! Top level project file was deleted. This might cause unexpected behavior. Skipping processing of changes!");
-				return;
+			}
+			_ => {}
+		}
+	}
+
+	/// Resnapshots instances that are affected by the change of the event path
+	fn process_path(&self, event: &VfsEvent, tree: &mut Tree) -> Changes {
+		let path = event.path();
+
+		if BLACKLISTED_PATHS.iter().any(|blacklisted| path.ends_with(blacklisted)) {
+			trace!("Processing of {:?} aborted: blacklisted", path);
+			return Changes::new();
+		}
+
+		let mut ids = {
+			let mut current_path = path;
+
+			loop {
+				if let Some(ids) = tree.get_ids(current_path) {
+					break ids.to_owned();
+				}
+
+				match current_path.parent() {
+					Some(parent) => current_path = parent,
+					None => break vec![],
+				}
+			}
+		};
+
+		// Project paths can be created or removed together with their
+		// ancestor that is not synced, so it would not be found above
+		if let VfsEvent::Create(_) | VfsEvent::Delete(_) = event {
+			for id in tree.get_project_ids(path) {
+				if !ids.contains(&id) {
+					ids.push(id);
+				}
+			}
+		}
+
+		if ids.is_empty() {
+			trace!("No ID found for path {:?}", path);
+			return Changes::new();
+		}
+
+		let mut changes = Changes::new();
+
+		for id in ids {
+			// Instance might have been removed while processing previous one
+			if tree.exists(id) {
+				changes.extend(read::process_changes(id, tree, &self.vfs));
 			}
 		}
+
+		changes
 	}
 
 	#[profiling::function]
-	fn on_client_event(&self, request: WriteRequest) {
+	fn on_client_event(&self, request: WriteRequest, pending: &mut Pending) {
 		profiling::start_frame!();
 
-		let changes = request.changes;
+		trace!("Received client event: {:?} changes", request.changes.total());
+
+		// Clients that disconnected will never answer
+		pending.retain(|client_id, _| self.queue.is_subscribed(*client_id));
+
+		if let Some(queued) = pending.get_mut(&request.client_id) {
+			trace!(
+				"Client {} has changes waiting for confirmation, queueing",
+				request.client_id
+			);
+			queued.push_back(request);
+			return;
+		}
+
+		match self.decide(&request) {
+			Decision::Accept => self.apply(request),
+			Decision::Reject => self.reject(request),
+			Decision::AskClient => {
+				pending.insert(request.client_id, VecDeque::from([request]));
+			}
+		}
+	}
+
+	fn on_client_confirm(&self, request: ConfirmRequest, pending: &mut Pending) {
 		let client_id = request.client_id;
 
-		trace!("Received client event: {:?} changes", changes.total());
-
-		if changes.total() > Config::new().changes_threshold {
-			let accept = logger::prompt(
-				&format!(
-					"You are about to apply {}, {} and {}. Do you want to continue?",
-					format!("{} additions", changes.additions.len()).bold().green(),
-					format!("{} updates", changes.updates.len()).bold().blue(),
-					format!("{} removals", changes.removals.len()).bold().red(),
-				),
-				true,
+		let Some(mut queued) = pending.remove(&client_id) else {
+			warn!(
+				"Client {} sent confirmation, but has no changes waiting for it",
+				client_id
 			);
+			return;
+		};
 
-			if !accept {
-				trace!(
-					"Aborted applying client event! {} changes were not applied",
-					changes.total()
-				);
+		let write_request = queued.pop_front().unwrap();
 
-				match self.queue.disconnect("Client and server got out of sync!", client_id) {
-					Ok(()) => trace!("Client {} disconnected", client_id),
-					Err(err) => warn!("Failed to disconnect client: {}", err),
-				}
+		if request.accept {
+			info!(
+				"Client {} accepted {} changes, applying",
+				client_id,
+				write_request.changes.total()
+			);
 
-				return;
+			self.apply(write_request);
+		} else {
+			info!(
+				"Client {} rejected {} changes",
+				client_id,
+				write_request.changes.total()
+			);
+
+			self.reject(write_request);
+		}
+
+		// Requests sent in the meantime may need confirmation again
+		for request in queued {
+			if !self.queue.is_subscribed(client_id) {
+				break;
 			}
+
+			self.on_client_event(request, pending);
 		}
+	}
 
-		let mut tree = lock!(self.tree);
+	/// Decides what to do with changes exceeding `changes_threshold`
+	/// according to `changes_policy` setting
+	fn decide(&self, request: &WriteRequest) -> Decision {
+		let changes = &request.changes;
+		let config = Config::new();
 
-		let result = || -> Result<()> {
-			for snapshot in changes.additions {
-				write::apply_addition(snapshot, &mut tree, &self.vfs)?;
-			}
+		if changes.total() <= config.changes_threshold {
+			return Decision::Accept;
+		}
 
-			for snapshot in changes.updates {
-				write::apply_update(snapshot, &mut tree, &self.vfs)?;
+		let (accept, by) = match config.changes_policy.to_lowercase().as_str() {
+			"accept" => (true, "policy"),
+			"reject" => (false, "policy"),
+			"client" => {
+				let message = server::ConfirmChanges {
+					additions: changes.additions.len(),
+					updates: changes.updates.len(),
+					removals: changes.removals.len(),
+				};
+
+				match self.queue.push(message, Some(request.client_id)) {
+					Ok(()) => {
+						info!(
+							"Waiting for client {} to confirm {} changes",
+							request.client_id,
+							changes.total()
+						);
+
+						return Decision::AskClient;
+					}
+					Err(err) => {
+						error!("Failed to ask client for confirmation: {}", err);
+						return Decision::Reject;
+					}
+				}
 			}
+			policy => {
+				if policy != "prompt" {
+					argon_warn!("Unknown changes policy: {}, prompting instead", policy);
+				}
 
-			for id in changes.removals {
-				write::apply_removal(id, &mut tree, &self.vfs)?;
+				(confirm_changes(changes), "user")
 			}
+		};
+
+		if accept {
+			info!("{} changes were accepted by {}", changes.total(), by);
+			Decision::Accept
+		} else {
+			info!("{} changes were rejected by {}", changes.total(), by);
+			Decision::Reject
+		}
+	}
+
+	/// Keeps previous state of files modified by the syncback in local history
+	fn save_history(&self, backups: HashMap<PathBuf, Backup>, tree: &Tree) {
+		let size = Config::new().history_size;
+
+		if size == 0 || backups.is_empty() {
+			return;
+		}
 
-			Ok(())
-		}();
+		let root = lock!(self.project).path.get_parent().to_owned();
+
+		let result = History::new(&root).and_then(|history| {
+			history.record(backups, tree, &self.vfs)?;
+			history.prune(size)
+		});
 
 		match result {
-			Ok(()) => trace!("Changes applied successfully"),
-			Err(err) => error!("Failed to apply changes: {}", err),
+			Ok(()) => trace!("Saved changes to history"),
+			Err(err) => warn!("Failed to save changes to history: {}", err),
+		}
+	}
+
+	fn reject(&self, request: WriteRequest) {
+		let client_id = request.client_id;
+
+		trace!(
+			"Aborted applying client event! {} changes were not applied",
+			request.changes.total()
+		);
+
+		match self.queue.disconnect("Client and server got out of sync!", client_id) {
+			Ok(()) => trace!("Client {} disconnected", client_id),
+			Err(err) => warn!("Failed to disconnect client: {}", err),
 		}
+	}
+
+	fn apply(&self, request: WriteRequest) {
+		let changes = request.changes;
+		let client_id = request.client_id;
+
+		let ids: Vec<Ref> = changes
+			.additions
+			.iter()
+			.map(|snapshot| snapshot.id)
+			.chain(changes.updates.iter().map(|snapshot| snapshot.id))
+			.chain(changes.removals.iter().copied())
+			.collect();
+
+		let mut tree = lock!(self.tree);
+
+		let transaction = self.vfs.begin();
+
+		let mut outcomes = Outcomes::new();
+		let result = write::apply_changes(changes, &mut tree, &self.vfs, &mut outcomes);
+
+		let reply = match result {
+			Ok(()) => {
+				let mut journal = transaction.commit();
+
+				trace!("Changes applied successfully");
+
+				// So their events are recognized as unchanged and skipped
+				for (path, hash) in journal.take_written() {
+					tree.update_hash(&path, hash);
+				}
+
+				self.save_history(journal.into_backups(), &tree);
+
+				server::SyncbackResult {
+					success: true,
+					error: None,
+					rolled_back: false,
+					instances: outcomes.complete(&ids, Outcome::Applied),
+				}
+			}
+			Err(err) => {
+				error!("Failed to apply changes: {}. Rolling back..", err);
+
+				let rolled_back = match transaction.rollback() {
+					Ok(()) => true,
+					Err(err) => {
+						error!("Failed to roll back changes: {}", err);
+						false
+					}
+				};
+
+				// Bring the tree back in line with restored files,
+				// so clients revert already applied changes as well
+				let mut changes = read::process_changes(tree.root_ref(), &mut tree, &self.vfs);
+				read::relink_refs(&mut changes, &mut tree);
+
+				if !changes.is_empty() {
+					match self.queue.push(server::SyncChanges(changes), None) {
+						Ok(()) => trace!("Reverted changes added to the queue"),
+						Err(err) => error!("Failed to add reverted changes to the queue: {}", err),
+					}
+				}
+
+				// Other outcomes no longer apply as the whole batch was discarded
+				outcomes.retain(|outcome| matches!(outcome, Outcome::Failed { .. }));
+
+				server::SyncbackResult {
+					success: false,
+					error: Some(err.to_string()),
+					rolled_back,
+					instances: outcomes.complete(
+						&ids,
+						Outcome::Failed {
+							reason: String::from("another change in the batch failed"),
+						},
+					),
+				}
+			}
+		};
 
+		self.queue.push(reply.clone(), Some(client_id)).ok();
+		self.queue.push(reply, Some(0)).ok();
 		self.queue.push(server::SyncbackChanges(), Some(0)).ok();
 	}
 }
+
+enum Decision {
+	Accept,
+	Reject,
+	AskClient,
+}
+
+/// Prompt user for confirmation if number of changes exceeds `changes_threshold`
+pub fn confirm_changes(changes: &Changes) -> bool {
+	if changes.total() <= Config::new().changes_threshold {
+		return true;
+	}
+
+	logger::prompt(
+		&format!(
+			"You are about to apply {}, {} and {}. Do you want to continue?",
+			format!("{} additions", changes.additions.len()).bold().green(),
+			format!("{} updates", changes.updates.len()).bold().blue(),
+			format!("{} removals", changes.removals.len()).bold().red(),
+		),
+		true,
+	)
+}
@@ -0,0 +1,64 @@
+use rbx_dom_weak::types::Ref;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// What happened to a single instance sent by the client
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum Outcome {
+	Applied,
+	/// Instance or one of its files does not pass the syncback filter
+	Filtered {
+		path: Option<PathBuf>,
+	},
+	/// Instance was written under a different name
+	Renamed {
+		name: String,
+	},
+	Skipped {
+		reason: String,
+	},
+	Failed {
+		reason: String,
+	},
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InstanceOutcome {
+	pub id: Ref,
+	#[serde(flatten)]
+	pub outcome: Outcome,
+}
+
+/// Outcomes reported while applying a single batch of client changes
+#[derive(Debug, Default)]
+pub struct Outcomes(Vec<InstanceOutcome>);
+
+impl Outcomes {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn report(&mut self, id: Ref, outcome: Outcome) {
+		self.0.push(InstanceOutcome { id, outcome });
+	}
+
+	/// Keeps only outcomes that match the predicate
+	pub fn retain(&mut self, f: impl Fn(&Outcome) -> bool) {
+		self.0.retain(|outcome| f(&outcome.outcome));
+	}
+
+	/// Adds the `fallback` outcome for every instance that has none reported
+	pub fn complete(mut self, ids: &[Ref], fallback: Outcome) -> Vec<InstanceOutcome> {
+		for id in ids {
+			if !self.0.iter().any(|outcome| outcome.id == *id) {
+				self.0.push(InstanceOutcome {
+					id: *id,
+					outcome: fallback.clone(),
+				});
+			}
+		}
+
+		self.0
+	}
+}
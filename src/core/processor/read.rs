@@ -1,10 +1,11 @@
 use log::{error, trace};
 use rbx_dom_weak::types::Ref;
+use std::path::Path;
 
 use crate::{
 	core::{
 		changes::Changes,
-		meta::SourceKind,
+		meta::{Meta, NodePath, SourceKind},
 		snapshot::{Snapshot, UpdatedSnapshot},
 		tree::Tree,
 	},
@@ -13,6 +14,10 @@ use crate::{
 	vfs::Vfs,
 };
 
+/// Minimal similarity of two instances with different
+/// names to be treated as the same, renamed instance
+const RENAME_SIMILARITY: f32 = 0.5;
+
 pub fn process_changes(id: Ref, tree: &mut Tree, vfs: &Vfs) -> Changes {
 	trace!("Processing changes for instance: {:?}", id);
 
@@ -47,6 +52,15 @@ pub fn process_changes(id: Ref, tree: &mut Tree, vfs: &Vfs) -> Changes {
 	// Handle additions, modifications and child removals
 	if let Some(snapshot) = snapshot {
 		process_child_changes(id, snapshot, &mut changes, tree);
+
+		// Resolve refs once all new instances are in the tree
+		for snapshot in changes.additions.iter_mut() {
+			snapshot.properties.extend(tree.link_instance_refs(snapshot.id));
+
+			for child in snapshot.children.iter_mut() {
+				link_refs(child, tree);
+			}
+		}
 	// Handle regular removals
 	} else {
 		tree.remove_instance(id);
@@ -56,11 +70,106 @@ pub fn process_changes(id: Ref, tree: &mut Tree, vfs: &Vfs) -> Changes {
 	changes
 }
 
+/// Moves instances synced from `from` path to `to` path in place, so they keep
+/// their IDs. Returns `None` if the rename cannot be applied this way and both
+/// paths should be processed as a removal and creation instead
+pub fn process_rename(from: &Path, to: &Path, tree: &mut Tree, vfs: &Vfs) -> Option<Changes> {
+	trace!("Processing rename from {:?} to {:?}", from, to);
+
+	let ids = tree.get_ids(from)?.to_owned();
+
+	// Instance paths that are managed by project nodes or shared with
+	// other files (like `init.luau` or `.data.json`) have to be resnapshotted
+	let is_movable = ids.iter().all(|id| {
+		tree.get_meta(*id)
+			.is_some_and(|meta| matches!(meta.source.get(), SourceKind::Path(path) if path == from))
+	});
+
+	if !is_movable || tree.get_ids(to).is_some() {
+		return None;
+	}
+
+	let parent = match tree.get_ids(to.parent()?)?.as_slice() {
+		[parent] => *parent,
+		_ => return None,
+	};
+
+	tree.move_paths(from, to);
+
+	let mut changes = Changes::new();
+
+	for id in ids {
+		let is_moved = tree
+			.get_instance(id)
+			.is_some_and(|instance| instance.parent() != parent);
+
+		if is_moved {
+			tree.move_instance(id, parent);
+		}
+
+		let mut instance_changes = process_changes(id, tree, vfs);
+
+		if is_moved && !instance_changes.removals.contains(&id) {
+			if let Some(snapshot) = instance_changes.updates.iter_mut().find(|snapshot| snapshot.id == id) {
+				snapshot.parent = Some(parent);
+			} else {
+				let mut snapshot = UpdatedSnapshot::new(id);
+				snapshot.parent = Some(parent);
+
+				instance_changes.update(snapshot);
+			}
+		}
+
+		changes.extend(instance_changes);
+	}
+
+	Some(changes)
+}
+
+/// Resolves refs of instances that reference the changed ones again, as
+/// refs are otherwise resolved only when the referencing file is processed
+pub fn relink_refs(changes: &mut Changes, tree: &mut Tree) {
+	let ids: Vec<Ref> = changes
+		.additions
+		.iter()
+		.map(|snapshot| snapshot.id)
+		.chain(
+			changes
+				.updates
+				.iter()
+				.filter(|snapshot| snapshot.name.is_some() || snapshot.parent.is_some())
+				.map(|snapshot| snapshot.id),
+		)
+		.collect();
+
+	if ids.is_empty() && changes.removals.is_empty() {
+		return;
+	}
+
+	for id in tree.relink_referrers(&ids) {
+		let properties = tree.get_instance(id).unwrap().properties.clone();
+
+		if let Some(snapshot) = changes.updates.iter_mut().find(|snapshot| snapshot.id == id) {
+			snapshot.properties = Some(properties);
+		} else {
+			let mut snapshot = UpdatedSnapshot::new(id);
+			snapshot.properties = Some(properties);
+
+			changes.update(snapshot);
+		}
+	}
+}
+
 fn process_child_changes(id: Ref, mut snapshot: Snapshot, changes: &mut Changes, tree: &mut Tree) {
 	// Process instance changes
 	let mut updated_snapshot = UpdatedSnapshot::new(id);
 
+	let refs = tree.resolve_refs(id, &snapshot.meta.refs);
+	snapshot.properties.extend(refs);
+
 	updated_snapshot.meta = if snapshot.meta != *tree.get_meta(id).expect("Instance meta not found") {
+		// Keep source paths up to date, as renamed instances are updated in place
+		tree.update_meta(id, snapshot.meta.clone());
 		Some(snapshot.meta)
 	} else {
 		None
@@ -99,34 +208,26 @@ fn process_child_changes(id: Ref, mut snapshot: Snapshot, changes: &mut Changes,
 		changes.update(updated_snapshot);
 	}
 
-	let mut hydrated = vec![false; snapshot.children.len()];
-
-	// Pair instances and find removed children
-	#[allow(clippy::unnecessary_to_owned)]
-	for child_id in instance.children().to_owned() {
-		let instance = tree.get_instance(child_id).unwrap();
+	let children = instance.children().to_owned();
 
-		let snapshot = snapshot.children.iter_mut().enumerate().find(|(index, child)| {
-			if hydrated[*index] {
-				return false;
-			}
-
-			if child.name == instance.name && child.class == instance.class {
-				hydrated[*index] = true;
-				return true;
-			}
+	tree.index_refs(id);
 
-			false
-		});
+	let pairs = pair_children(&children, &snapshot.children, tree);
 
-		if let Some((_, child)) = snapshot {
-			child.set_id(child_id);
-		} else {
+	// Find removed children
+	for child_id in children {
+		if !pairs.contains(&Some(child_id)) {
 			tree.remove_instance(child_id);
 			changes.remove(child_id);
 		}
 	}
 
+	for (child, pair) in snapshot.children.iter_mut().zip(pairs) {
+		if let Some(child_id) = pair {
+			child.set_id(child_id);
+		}
+	}
+
 	// Process child changes and find new children
 	for child in snapshot.children {
 		if child.id.is_some() {
@@ -145,6 +246,149 @@ fn process_child_changes(id: Ref, mut snapshot: Snapshot, changes: &mut Changes,
 	}
 }
 
+/// Pairs existing children with their new snapshots, returning
+/// ID of the existing child for every snapshot that was paired.
+/// Children are paired by source path first, then by name and class,
+/// and finally remaining children of the same class are treated as renamed
+/// if similar enough. Property similarity decides between multiple candidates
+fn pair_children(children: &[Ref], snapshots: &[Snapshot], tree: &Tree) -> Vec<Option<Ref>> {
+	let mut pairs = vec![None; snapshots.len()];
+
+	let is_free = |id: &Ref, pairs: &[Option<Ref>]| !pairs.contains(&Some(*id));
+
+	for (index, snapshot) in snapshots.iter().enumerate() {
+		let key = match source_key(&snapshot.meta) {
+			Some(key) => key,
+			None => continue,
+		};
+
+		let candidates = children
+			.iter()
+			.filter(|&id| {
+				is_free(id, &pairs)
+					&& tree.get_instance(*id).unwrap().class == snapshot.class
+					&& tree.get_meta(*id).and_then(source_key) == Some(key)
+			})
+			.collect::<Vec<_>>();
+
+		// Source paths are unique for files, but not for everything else
+		if let [&id] = candidates[..] {
+			pairs[index] = Some(id);
+		}
+	}
+
+	for (index, snapshot) in snapshots.iter().enumerate() {
+		if pairs[index].is_some() {
+			continue;
+		}
+
+		let candidates = children.iter().copied().filter(|id| {
+			let instance = tree.get_instance(*id).unwrap();
+			is_free(id, &pairs) && instance.name == snapshot.name && instance.class == snapshot.class
+		});
+
+		pairs[index] = most_similar(candidates, snapshot, 0.0, tree);
+	}
+
+	for (index, snapshot) in snapshots.iter().enumerate() {
+		if pairs[index].is_some() {
+			continue;
+		}
+
+		let candidates = children
+			.iter()
+			.copied()
+			.filter(|id| is_free(id, &pairs) && tree.get_instance(*id).unwrap().class == snapshot.class);
+
+		pairs[index] = most_similar(candidates, snapshot, RENAME_SIMILARITY, tree);
+	}
+
+	pairs
+}
+
+/// Returns the first of the candidates most similar to the snapshot,
+/// as long as its similarity is at least the given `threshold`
+fn most_similar(
+	candidates: impl Iterator<Item = Ref>,
+	snapshot: &Snapshot,
+	threshold: f32,
+	tree: &Tree,
+) -> Option<Ref> {
+	candidates
+		.map(|id| (id, similarity(id, snapshot, tree)))
+		.filter(|(_, score)| *score >= threshold)
+		.fold(None, |best: Option<(Ref, f32)>, (id, score)| match best {
+			Some((_, best_score)) if best_score >= score => best,
+			_ => Some((id, score)),
+		})
+		.map(|(id, _)| id)
+}
+
+/// Returns what identifies the instance in the file system, regardless of its name
+fn source_key(meta: &Meta) -> Option<(&Path, Option<&NodePath>)> {
+	match meta.source.get() {
+		SourceKind::Path(path) => Some((path, None)),
+		SourceKind::Project(_, path, _, node_path) => Some((path, Some(node_path))),
+		SourceKind::None => None,
+	}
+}
+
+/// Returns ratio of equal properties and children (by name and class)
+/// to all properties and children of the instance and its snapshot
+fn similarity(id: Ref, snapshot: &Snapshot, tree: &Tree) -> f32 {
+	let instance = tree.get_instance(id).unwrap();
+
+	let mut total = 0;
+	let mut equal = 0;
+
+	for (property, value) in &snapshot.properties {
+		total += 1;
+
+		if instance.properties.get(property) == Some(value) {
+			equal += 1;
+		}
+	}
+
+	total += instance
+		.properties
+		.keys()
+		.filter(|property| !snapshot.properties.contains_key(*property))
+		.count();
+
+	let children = instance
+		.children()
+		.iter()
+		.map(|&child| tree.get_instance(child).unwrap())
+		.collect::<Vec<_>>();
+
+	for child in &snapshot.children {
+		total += 1;
+
+		if children
+			.iter()
+			.any(|instance| instance.name == child.name && instance.class == child.class)
+		{
+			equal += 1;
+		}
+	}
+
+	total += children
+		.iter()
+		.filter(|instance| {
+			!snapshot
+				.children
+				.iter()
+				.any(|child| child.name == instance.name && child.class == instance.class)
+		})
+		.count();
+
+	if total == 0 {
+		return 1.0;
+	}
+
+	equal as f32 / total as f32
+}
+
 fn insert_children(snapshot: &mut Snapshot, parent: Ref, tree: &mut Tree) {
 	let id = tree.insert_instance_non_recursive(snapshot.clone(), parent);
 
@@ -154,3 +398,11 @@ fn insert_children(snapshot: &mut Snapshot, parent: Ref, tree: &mut Tree) {
 		insert_children(child, id, tree);
 	}
 }
+
+fn link_refs(snapshot: &mut Snapshot, tree: &mut Tree) {
+	snapshot.properties.extend(tree.link_instance_refs(snapshot.id));
+
+	for child in snapshot.children.iter_mut() {
+		link_refs(child, tree);
+	}
+}
@@ -1,17 +1,28 @@
 use anyhow::{anyhow, Context as AnyhowContext, Result};
 use log::{error, trace, warn};
 use path_clean::PathClean;
-use rbx_dom_weak::{types::Ref, Instance};
+use rbx_dom_weak::{
+	types::{Ref, Variant},
+	Instance,
+};
 use std::{
 	collections::HashMap,
+	mem,
 	path::{Path, PathBuf},
 };
 
-use super::helpers::syncback::{rename_path, serialize_properties, validate_properties, verify_name, verify_path};
+use super::{
+	helpers::syncback::{
+		locate_model, locate_refs, rename_path, serialize_properties, serialize_refs, validate_properties, verify_name,
+		verify_path, write_model,
+	},
+	outcome::{Outcome, Outcomes},
+};
 use crate::{
 	config::Config,
 	core::{
-		meta::{Meta, NodePath, Source, SourceEntry, SourceKind},
+		changes::Changes,
+		meta::{Meta, NodePath, Source, SourceEntry, SourceKind, SyncbackFilter},
 		snapshot::{AddedSnapshot, Snapshot, UpdatedSnapshot},
 		tree::Tree,
 	},
@@ -21,24 +32,136 @@ use crate::{
 		dir, Middleware,
 	},
 	project::{Project, ProjectNode},
+	util,
 	vfs::Vfs,
 	Properties,
 };
 
 macro_rules! filter_warn {
-	($id:expr) => {
+	($outcomes:expr, $id:expr) => {
 		warn!("Instance {} does not pass syncback filter! Skipping..", $id);
+		$outcomes.report($id, Outcome::Filtered { path: None });
 	};
-	($id:expr, $path:expr) => {
+	($outcomes:expr, $id:expr, $path:expr) => {
 		warn!(
 			"Path: {} (source of instance: {}) does not pass syncback filter! Skipping..",
 			$path.display(),
 			$id
 		);
+		$outcomes.report(
+			$id,
+			Outcome::Filtered {
+				path: Some($path.to_owned()),
+			},
+		);
 	};
 }
 
-pub fn apply_addition(snapshot: AddedSnapshot, tree: &mut Tree, vfs: &Vfs) -> Result<()> {
+pub fn apply_changes(changes: Changes, tree: &mut Tree, vfs: &Vfs, outcomes: &mut Outcomes) -> Result<()> {
+	fn take_refs(id: Ref, properties: &mut Properties, children: &mut [Snapshot], linked: &mut Vec<UpdatedSnapshot>) {
+		if properties.values().any(|value| matches!(value, Variant::Ref(_))) {
+			let mut snapshot = UpdatedSnapshot::new(id);
+			snapshot.properties = Some(properties.clone());
+
+			properties.retain(|_, value| !matches!(value, Variant::Ref(_)));
+			linked.push(snapshot);
+		}
+
+		for child in children {
+			take_refs(child.id, &mut child.properties, &mut child.children, linked);
+		}
+	}
+
+	// Refs may point to instances that are added in the same batch,
+	// so they are written once all additions are applied
+	let mut linked = vec![];
+
+	let renamed: Vec<Ref> = changes
+		.updates
+		.iter()
+		.filter(|snapshot| snapshot.name.is_some())
+		.map(|snapshot| snapshot.id)
+		.collect();
+	let has_removals = !changes.removals.is_empty();
+
+	for mut snapshot in changes.additions {
+		take_refs(
+			snapshot.id,
+			&mut snapshot.properties,
+			&mut snapshot.children,
+			&mut linked,
+		);
+		let id = snapshot.id;
+		report_failure(id, apply_addition(snapshot, tree, vfs, outcomes), outcomes)?;
+	}
+
+	for snapshot in changes.updates.into_iter().chain(linked) {
+		let id = snapshot.id;
+		report_failure(id, apply_update(snapshot, tree, vfs, outcomes), outcomes)?;
+	}
+
+	for id in changes.removals {
+		report_failure(id, apply_removal(id, tree, vfs, outcomes), outcomes)?;
+	}
+
+	// Referencing files store paths of the referenced instances,
+	// so they have to be written again once their targets are renamed
+	if !renamed.is_empty() || has_removals {
+		for id in tree.stale_referrers(&renamed, false) {
+			let mut snapshot = UpdatedSnapshot::new(id);
+			snapshot.properties = Some(tree.get_instance(id).unwrap().properties.clone());
+
+			report_failure(id, apply_update(snapshot, tree, vfs, outcomes), outcomes)?;
+		}
+	}
+
+	Ok(())
+}
+
+fn report_failure(id: Ref, result: Result<()>, outcomes: &mut Outcomes) -> Result<()> {
+	if let Err(err) = &result {
+		outcomes.report(
+			id,
+			Outcome::Failed {
+				reason: err.to_string(),
+			},
+		);
+	}
+
+	result
+}
+
+fn missing(what: &str) -> Outcome {
+	Outcome::Skipped {
+		reason: format!("{} does not exist", what),
+	}
+}
+
+/// Nodes inherited from base projects are not stored in the project
+/// that extends them, so changes to them are skipped instead
+fn is_inherited(id: Ref, what: &str, node: &ProjectNode, outcomes: &mut Outcomes) -> bool {
+	let Some(origin) = &node.origin else {
+		return false;
+	};
+
+	warn!(
+		"Project node of {} {:?} is defined in base project {}! Skipping..",
+		what,
+		id,
+		origin.display()
+	);
+
+	outcomes.report(
+		id,
+		Outcome::Skipped {
+			reason: format!("{} is defined in base project {}", what, origin.display()),
+		},
+	);
+
+	true
+}
+
+pub fn apply_addition(snapshot: AddedSnapshot, tree: &mut Tree, vfs: &Vfs, outcomes: &mut Outcomes) -> Result<()> {
 	trace!("Adding {:?} with parent {:?}", snapshot.id, snapshot.parent);
 
 	if !tree.exists(snapshot.parent) {
@@ -46,6 +169,7 @@ pub fn apply_addition(snapshot: AddedSnapshot, tree: &mut Tree, vfs: &Vfs) -> Re
 			"Attempted to add instance: {:?} whose parent doesn't exist: {:?}",
 			snapshot.id, snapshot.parent
 		);
+		outcomes.report(snapshot.id, missing("parent"));
 		return Ok(());
 	}
 
@@ -55,12 +179,44 @@ pub fn apply_addition(snapshot: AddedSnapshot, tree: &mut Tree, vfs: &Vfs) -> Re
 	let filter = parent_meta.context.syncback_filter();
 
 	if filter.matches_name(&snapshot.name) || filter.matches_class(&snapshot.class) {
-		filter_warn!(snapshot.id);
+		filter_warn!(outcomes, snapshot.id);
 		return Ok(());
 	}
 
 	snapshot.properties = validate_properties(snapshot.properties, filter);
 
+	fn insert_model_instances(
+		parent_id: Ref,
+		mut snapshot: Snapshot,
+		filter: &SyncbackFilter,
+		tree: &mut Tree,
+		outcomes: &mut Outcomes,
+	) {
+		let children = mem::take(&mut snapshot.children);
+		let id = snapshot.id;
+
+		tree.insert_instance_with_ref(snapshot, parent_id);
+
+		for mut child in children {
+			if filter.matches_name(&child.name) || filter.matches_class(&child.class) {
+				filter_warn!(outcomes, child.id);
+				continue;
+			}
+
+			child.properties = validate_properties(child.properties, filter);
+			insert_model_instances(id, child, filter, tree, outcomes);
+		}
+	}
+
+	// Instances added to a model file are stored in the same file
+	if let Some((model_id, _, _)) = locate_model(parent_id, tree) {
+		let filter = tree.get_meta(model_id).unwrap().context.syncback_filter().clone();
+
+		insert_model_instances(parent_id, snapshot, &filter, tree, outcomes);
+
+		return write_model(parent_id, tree, vfs);
+	}
+
 	fn locate_instance_data(is_dir: bool, path: &Path, snapshot: &Snapshot, parent_meta: &Meta) -> Result<PathBuf> {
 		parent_meta
 			.context
@@ -76,6 +232,7 @@ pub fn apply_addition(snapshot: AddedSnapshot, tree: &mut Tree, vfs: &Vfs) -> Re
 		snapshot: &mut Snapshot,
 		parent_meta: &Meta,
 		vfs: &Vfs,
+		outcomes: &mut Outcomes,
 	) -> Result<Option<Meta>> {
 		let mut meta = snapshot.meta.clone().with_context(&parent_meta.context);
 		let filter = parent_meta.context.syncback_filter();
@@ -98,11 +255,11 @@ pub fn apply_addition(snapshot: AddedSnapshot, tree: &mut Tree, vfs: &Vfs) -> Re
 
 			if has_children {
 				if filter.matches_path(path) {
-					filter_warn!(snapshot.id, path);
+					filter_warn!(outcomes, snapshot.id, path);
 					return Ok(None);
 				}
 
-				if !verify_path(path, &mut snapshot.name, &mut meta, vfs) {
+				if !verify_path(snapshot.id, path, &mut snapshot.name, &mut meta, vfs, outcomes) {
 					return Ok(None);
 				}
 
@@ -110,7 +267,14 @@ pub fn apply_addition(snapshot: AddedSnapshot, tree: &mut Tree, vfs: &Vfs) -> Re
 
 				meta.set_source(Source::child_file(path, &file_path));
 			} else {
-				if !verify_path(&mut file_path, &mut snapshot.name, &mut meta, vfs) {
+				if !verify_path(
+					snapshot.id,
+					&mut file_path,
+					&mut snapshot.name,
+					&mut meta,
+					vfs,
+					outcomes,
+				) {
 					return Ok(None);
 				}
 
@@ -118,7 +282,7 @@ pub fn apply_addition(snapshot: AddedSnapshot, tree: &mut Tree, vfs: &Vfs) -> Re
 			}
 
 			if filter.matches_path(&file_path) {
-				filter_warn!(snapshot.id, &file_path);
+				filter_warn!(outcomes, snapshot.id, &file_path);
 				return Ok(None);
 			}
 
@@ -126,18 +290,18 @@ pub fn apply_addition(snapshot: AddedSnapshot, tree: &mut Tree, vfs: &Vfs) -> Re
 			let data_path = locate_instance_data(has_children, path, snapshot, parent_meta)?;
 
 			if filter.matches_path(&data_path) {
-				filter_warn!(snapshot.id, &data_path);
+				filter_warn!(outcomes, snapshot.id, &data_path);
 			} else {
 				let data_path = data::write_data(true, &snapshot.class, properties, &data_path, &meta, vfs)?;
 				meta.source.set_data(data_path);
 			}
 		} else {
 			if filter.matches_path(path) {
-				filter_warn!(snapshot.id, path);
+				filter_warn!(outcomes, snapshot.id, path);
 				return Ok(None);
 			}
 
-			if !verify_path(path, &mut snapshot.name, &mut meta, vfs) {
+			if !verify_path(snapshot.id, path, &mut snapshot.name, &mut meta, vfs, outcomes) {
 				return Ok(None);
 			}
 
@@ -148,7 +312,7 @@ pub fn apply_addition(snapshot: AddedSnapshot, tree: &mut Tree, vfs: &Vfs) -> Re
 			let data_path = locate_instance_data(true, path, snapshot, parent_meta)?;
 
 			if filter.matches_path(&data_path) {
-				filter_warn!(snapshot.id, &data_path);
+				filter_warn!(outcomes, snapshot.id, &data_path);
 			} else {
 				let data_path = data::write_data(false, &snapshot.class, properties, &data_path, &meta, vfs)?;
 				meta.source.set_data(data_path);
@@ -158,6 +322,62 @@ pub fn apply_addition(snapshot: AddedSnapshot, tree: &mut Tree, vfs: &Vfs) -> Re
 		Ok(Some(meta))
 	}
 
+	#[allow(clippy::too_many_arguments)]
+	fn write_model_instance(
+		middleware: Middleware,
+		path: &Path,
+		mut snapshot: Snapshot,
+		parent_id: Ref,
+		parent_meta: &Meta,
+		tree: &mut Tree,
+		vfs: &Vfs,
+		outcomes: &mut Outcomes,
+	) -> Result<()> {
+		let mut meta = snapshot.meta.clone().with_context(&parent_meta.context);
+		let filter = parent_meta.context.syncback_filter();
+
+		let mut file_path = parent_meta
+			.context
+			.sync_rules_of_type(&middleware)
+			.iter()
+			.find_map(|rule| rule.locate(path, &snapshot.name, false))
+			.with_context(|| format!("Failed to locate file path for parent: {}", path.display()))?;
+
+		if !verify_path(
+			snapshot.id,
+			&mut file_path,
+			&mut snapshot.name,
+			&mut meta,
+			vfs,
+			outcomes,
+		) {
+			return Ok(());
+		}
+
+		if filter.matches_path(&file_path) {
+			filter_warn!(outcomes, snapshot.id, &file_path);
+			return Ok(());
+		}
+
+		meta.set_source(Source::file(&file_path));
+
+		// Model file can't store the original name of the instance
+		let data_path = locate_instance_data(false, path, &snapshot, parent_meta)?;
+
+		if filter.matches_path(&data_path) {
+			filter_warn!(outcomes, snapshot.id, &data_path);
+		} else {
+			let data_path = data::write_data(true, &snapshot.class, Properties::new(), &data_path, &meta, vfs)?;
+			meta.source.set_data(data_path);
+		}
+
+		let id = snapshot.id;
+
+		insert_model_instances(parent_id, snapshot.with_meta(meta), filter, tree, outcomes);
+
+		middleware.write_model(tree.inner(), id, &file_path, vfs)
+	}
+
 	fn add_non_project_instances(
 		parent_id: Ref,
 		parent_path: &Path,
@@ -165,6 +385,7 @@ pub fn apply_addition(snapshot: AddedSnapshot, tree: &mut Tree, vfs: &Vfs) -> Re
 		parent_meta: &mut Meta,
 		tree: &mut Tree,
 		vfs: &Vfs,
+		outcomes: &mut Outcomes,
 	) -> Result<Source> {
 		let config = Config::new();
 
@@ -191,7 +412,14 @@ pub fn apply_addition(snapshot: AddedSnapshot, tree: &mut Tree, vfs: &Vfs) -> Re
 			let name = sync_rule.get_name(&parent_path);
 			let mut folder_path = parent_path.with_file_name(&name);
 
-			if !verify_path(&mut folder_path, &mut snapshot.name, parent_meta, vfs) {
+			if !verify_path(
+				snapshot.id,
+				&mut folder_path,
+				&mut snapshot.name,
+				parent_meta,
+				vfs,
+				outcomes,
+			) {
 				return Ok(parent_meta.source.clone());
 			}
 
@@ -229,26 +457,28 @@ pub fn apply_addition(snapshot: AddedSnapshot, tree: &mut Tree, vfs: &Vfs) -> Re
 			parent_meta.source.clone()
 		};
 
-		if !verify_name(&mut snapshot.name, &mut snapshot.meta) {
+		if !verify_name(snapshot.id, &mut snapshot.name, &mut snapshot.meta, outcomes) {
 			return Ok(parent_source);
 		}
 
 		let mut path = parent_path.join(&snapshot.name);
 
-		if snapshot.children.is_empty() {
-			if let Some(meta) = write_instance(false, &mut path, &mut snapshot, parent_meta, vfs)? {
+		if let Some(middleware) = parent_meta.context.model_middleware(&snapshot.class, &path) {
+			write_model_instance(middleware, &path, snapshot, parent_id, parent_meta, tree, vfs, outcomes)?;
+		} else if snapshot.children.is_empty() {
+			if let Some(meta) = write_instance(false, &mut path, &mut snapshot, parent_meta, vfs, outcomes)? {
 				let snapshot = snapshot.with_meta(meta);
 
 				tree.insert_instance_with_ref(snapshot, parent_id);
 			}
-		} else if let Some(mut meta) = write_instance(true, &mut path, &mut snapshot, parent_meta, vfs)? {
+		} else if let Some(mut meta) = write_instance(true, &mut path, &mut snapshot, parent_meta, vfs, outcomes)? {
 			let snapshot = snapshot.with_meta(meta.clone());
 
 			tree.insert_instance_with_ref(snapshot.clone(), parent_id);
 
 			for mut child in snapshot.children {
 				child.properties = validate_properties(child.properties.clone(), meta.context.syncback_filter());
-				add_non_project_instances(snapshot.id, &path, child, &mut meta, tree, vfs)?;
+				add_non_project_instances(snapshot.id, &path, child, &mut meta, tree, vfs, outcomes)?;
 			}
 		}
 
@@ -289,15 +519,40 @@ pub fn apply_addition(snapshot: AddedSnapshot, tree: &mut Tree, vfs: &Vfs) -> Re
 
 		for mut child in snapshot.children {
 			child.properties = validate_properties(child.properties, filter);
-			add_project_instances(parent_id, path, node_path.clone(), child, &mut node, parent_meta, tree);
+			add_project_instances(
+				snapshot.id,
+				path,
+				node_path.clone(),
+				child,
+				&mut node,
+				parent_meta,
+				tree,
+			);
 		}
 
 		parent_node.tree.insert(snapshot.name, node);
 	}
 
 	match parent_meta.source.get().clone() {
+		// Parent is the root of the project
+		SourceKind::Path(path) if Middleware::from_path(&path, &parent_meta.context) == Some(Middleware::Project) => {
+			let mut project = Project::load_local(&path)?;
+
+			add_project_instances(
+				parent_id,
+				&path,
+				NodePath::new(),
+				snapshot,
+				&mut project.node,
+				&parent_meta,
+				tree,
+			);
+
+			save_project(&project, &path, tree, vfs)?;
+		}
 		SourceKind::Path(path) => {
-			let parent_source = add_non_project_instances(parent_id, &path, snapshot, &mut parent_meta, tree, vfs)?;
+			let parent_source =
+				add_non_project_instances(parent_id, &path, snapshot, &mut parent_meta, tree, vfs, outcomes)?;
 
 			parent_meta.set_source(parent_source);
 			tree.update_meta(parent_id, parent_meta);
@@ -306,8 +561,15 @@ pub fn apply_addition(snapshot: AddedSnapshot, tree: &mut Tree, vfs: &Vfs) -> Re
 			if let Some(custom_path) = &node.path {
 				let custom_path = path.with_file_name(custom_path.path()).clean();
 
-				let parent_source =
-					add_non_project_instances(parent_id, &custom_path, snapshot, &mut parent_meta, tree, vfs)?;
+				let parent_source = add_non_project_instances(
+					parent_id,
+					&custom_path,
+					snapshot,
+					&mut parent_meta,
+					tree,
+					vfs,
+					outcomes,
+				)?;
 
 				let parent_source = Source::project(&name, &path, node.clone(), node_path.clone())
 					.with_relevant(parent_source.relevant().to_owned());
@@ -315,7 +577,11 @@ pub fn apply_addition(snapshot: AddedSnapshot, tree: &mut Tree, vfs: &Vfs) -> Re
 				parent_meta.set_source(parent_source);
 				tree.update_meta(parent_id, parent_meta);
 			} else {
-				let mut project = Project::load(&path)?;
+				if is_inherited(snapshot.id, "parent", &node, outcomes) {
+					return Ok(());
+				}
+
+				let mut project = Project::load_local(&path)?;
 
 				let node = project
 					.find_node_by_path(&node_path)
@@ -323,7 +589,7 @@ pub fn apply_addition(snapshot: AddedSnapshot, tree: &mut Tree, vfs: &Vfs) -> Re
 
 				add_project_instances(parent_id, &path, node_path.clone(), snapshot, node, &parent_meta, tree);
 
-				project.save(&path)?;
+				save_project(&project, &path, tree, vfs)?;
 			}
 		}
 		SourceKind::None => panic!(
@@ -335,32 +601,44 @@ pub fn apply_addition(snapshot: AddedSnapshot, tree: &mut Tree, vfs: &Vfs) -> Re
 	Ok(())
 }
 
-pub fn apply_update(snapshot: UpdatedSnapshot, tree: &mut Tree, vfs: &Vfs) -> Result<()> {
+pub fn apply_update(snapshot: UpdatedSnapshot, tree: &mut Tree, vfs: &Vfs, outcomes: &mut Outcomes) -> Result<()> {
 	trace!("Updating {:?}", snapshot.id);
 
 	if let Some(instance) = tree.get_instance(snapshot.id) {
 		let filter = tree.get_meta(snapshot.id).unwrap().context.syncback_filter();
 
 		if filter.matches_name(&instance.name) || filter.matches_class(&instance.class) {
-			filter_warn!(snapshot.id);
+			filter_warn!(outcomes, snapshot.id);
 			return Ok(());
 		}
 
 		if snapshot.name.as_ref().is_some_and(|name| filter.matches_name(name)) {
-			filter_warn!(snapshot.id);
+			filter_warn!(outcomes, snapshot.id);
 			return Ok(());
 		}
 
 		if snapshot.class.as_ref().is_some_and(|class| filter.matches_class(class)) {
-			filter_warn!(snapshot.id);
+			filter_warn!(outcomes, snapshot.id);
 			return Ok(());
 		}
 	} else {
 		warn!("Attempted to update instance that doesn't exist: {:?}", snapshot.id);
+		outcomes.report(snapshot.id, missing("instance"));
 		return Ok(());
 	}
 
+	// Currently Argon client sends moved instances as removals and additions
+	if snapshot.parent.is_some() {
+		warn!("Attempted to change parent of {:?}, this is not supported", snapshot.id);
+	}
+
 	let mut meta = tree.get_meta(snapshot.id).unwrap().clone();
+	let model = locate_model(snapshot.id, tree);
+	let refs = snapshot
+		.properties
+		.as_ref()
+		.map(|properties| locate_refs(properties, tree))
+		.unwrap_or_default();
 	let instance = tree.get_instance_mut(snapshot.id).unwrap();
 
 	fn locate_instance_data(name: &str, path: &Path, meta: &Meta, vfs: &Vfs) -> Option<PathBuf> {
@@ -383,27 +661,39 @@ pub fn apply_update(snapshot: UpdatedSnapshot, tree: &mut Tree, vfs: &Vfs) -> Re
 	fn update_non_project_properties(
 		path: &Path,
 		properties: Properties,
+		refs: HashMap<String, String>,
 		instance: &mut Instance,
 		meta: &mut Meta,
 		vfs: &Vfs,
+		outcomes: &mut Outcomes,
 	) -> Result<()> {
 		let filter = meta.context.syncback_filter();
 
 		if filter.matches_path(path) {
-			filter_warn!(instance.referent(), path);
+			filter_warn!(outcomes, instance.referent(), path);
 			return Ok(());
 		}
 
 		let mut properties = validate_properties(properties, filter);
 
-		if let Some(middleware) = Middleware::from_class(
-			&instance.class,
-			if !meta.context.use_legacy_scripts() {
-				Some(&mut properties)
-			} else {
-				None
-			},
-		) {
+		// Keep data modules (JSON, TOML, etc.) in their original format
+		let middleware = meta
+			.source
+			.get_file()
+			.and_then(|file| Middleware::from_path(file.path(), &meta.context))
+			.filter(|middleware| middleware.is_data_module() && instance.class == "ModuleScript")
+			.or_else(|| {
+				Middleware::from_class(
+					&instance.class,
+					if !meta.context.use_legacy_scripts() {
+						Some(&mut properties)
+					} else {
+						None
+					},
+				)
+			});
+
+		if let Some(middleware) = middleware {
 			let new_path = meta
 				.context
 				.sync_rules_of_type(&middleware)
@@ -432,11 +722,11 @@ pub fn apply_update(snapshot: UpdatedSnapshot, tree: &mut Tree, vfs: &Vfs) -> Re
 			};
 
 			if let Some(file_path) = file_path {
-				let properties = middleware.write(properties.clone(), &file_path, vfs)?;
+				let properties = middleware.write(serialize_refs(properties.clone(), &refs), &file_path, vfs)?;
 
 				if let Some(data_path) = locate_instance_data(&instance.name, path, meta, vfs) {
 					if filter.matches_path(&data_path) {
-						filter_warn!(instance.referent(), &data_path);
+						filter_warn!(outcomes, instance.referent(), &data_path);
 					} else {
 						let data_path = data::write_data(true, &instance.class, properties, &data_path, meta, vfs)?;
 						meta.source.set_data(data_path)
@@ -447,14 +737,16 @@ pub fn apply_update(snapshot: UpdatedSnapshot, tree: &mut Tree, vfs: &Vfs) -> Re
 			}
 		} else if let Some(data_path) = locate_instance_data(&instance.name, path, meta, vfs) {
 			if filter.matches_path(&data_path) {
-				filter_warn!(instance.referent(), &data_path);
+				filter_warn!(outcomes, instance.referent(), &data_path);
 			} else {
-				let data_path = data::write_data(false, &instance.class, properties.clone(), &data_path, meta, vfs)?;
+				let serialized = serialize_refs(properties.clone(), &refs);
+				let data_path = data::write_data(false, &instance.class, serialized, &data_path, meta, vfs)?;
 				meta.source.set_data(data_path)
 			}
 		}
 
 		instance.properties = properties;
+		meta.set_refs(refs);
 
 		Ok(())
 	}
@@ -464,13 +756,13 @@ pub fn apply_update(snapshot: UpdatedSnapshot, tree: &mut Tree, vfs: &Vfs) -> Re
 			if let Some(mut name) = snapshot.name {
 				let original_name = meta.original_name.clone();
 
-				if !verify_name(&mut name, &mut meta) {
+				if !verify_name(snapshot.id, &mut name, &mut meta, outcomes) {
 					return Ok(());
 				}
 
 				path = rename_path(&path, &instance.name, &name);
 
-				if !verify_path(&mut path, &mut name, &mut meta, vfs) {
+				if !verify_path(snapshot.id, &mut path, &mut name, &mut meta, vfs, outcomes) {
 					return Ok(());
 				}
 
@@ -482,7 +774,7 @@ pub fn apply_update(snapshot: UpdatedSnapshot, tree: &mut Tree, vfs: &Vfs) -> Re
 					let new_path = path.with_file_name(&name);
 
 					if filter.matches_path(path) && filter.matches_path(&new_path) {
-						filter_warn!(snapshot.id, path);
+						filter_warn!(outcomes, snapshot.id, path);
 					} else {
 						vfs.rename(path, &new_path)?;
 						*path = new_path.clone();
@@ -503,7 +795,7 @@ pub fn apply_update(snapshot: UpdatedSnapshot, tree: &mut Tree, vfs: &Vfs) -> Re
 								let new_path = rename_path(path, &instance.name, &name);
 
 								if filter.matches_path(path) && filter.matches_path(&new_path) {
-									filter_warn!(snapshot.id, path);
+									filter_warn!(outcomes, snapshot.id, path);
 									continue;
 								}
 
@@ -518,7 +810,7 @@ pub fn apply_update(snapshot: UpdatedSnapshot, tree: &mut Tree, vfs: &Vfs) -> Re
 				if original_name != meta.original_name && snapshot.properties.is_none() {
 					if let Some(data_path) = locate_instance_data(&name, &path, &meta, vfs) {
 						if filter.matches_path(&data_path) {
-							filter_warn!(instance.referent(), &data_path);
+							filter_warn!(outcomes, instance.referent(), &data_path);
 						} else {
 							write_original_name(&data_path, &meta, vfs)?;
 						}
@@ -529,11 +821,19 @@ pub fn apply_update(snapshot: UpdatedSnapshot, tree: &mut Tree, vfs: &Vfs) -> Re
 			}
 
 			if let Some(properties) = snapshot.properties {
-				update_non_project_properties(&path, properties, instance, &mut meta, vfs)?;
+				if model.is_some() {
+					instance.properties = validate_properties(properties, meta.context.syncback_filter());
+				} else {
+					update_non_project_properties(&path, properties, refs, instance, &mut meta, vfs, outcomes)?;
+				}
 			}
 
 			tree.update_meta(snapshot.id, meta);
 
+			if model.is_some() {
+				write_model(snapshot.id, tree, vfs)?;
+			}
+
 			if let Some(_class) = snapshot.class {
 				// You can't change the class of an instance inside Roblox Studio
 				unreachable!()
@@ -545,13 +845,17 @@ pub fn apply_update(snapshot: UpdatedSnapshot, tree: &mut Tree, vfs: &Vfs) -> Re
 			}
 		}
 		SourceKind::Project(name, path, node, node_path) => {
-			let mut project = Project::load(&path)?;
+			if is_inherited(snapshot.id, "instance", &node, outcomes) {
+				return Ok(());
+			}
+
+			let mut project = Project::load_local(&path)?;
 
 			if let Some(properties) = snapshot.properties {
 				if let Some(custom_path) = node.path {
 					let custom_path = path.with_file_name(custom_path.path()).clean();
 
-					update_non_project_properties(&custom_path, properties, instance, &mut meta, vfs)?;
+					update_non_project_properties(&custom_path, properties, refs, instance, &mut meta, vfs, outcomes)?;
 
 					let node = project
 						.find_node_by_path(&node_path)
@@ -569,11 +873,12 @@ pub fn apply_update(snapshot: UpdatedSnapshot, tree: &mut Tree, vfs: &Vfs) -> Re
 					let class = node.class_name.as_ref().unwrap_or(&name);
 					let properties = validate_properties(properties, meta.context.syncback_filter());
 
-					node.properties = serialize_properties(class, properties.clone());
+					node.properties = serialize_properties(class, serialize_refs(properties.clone(), &refs));
 					node.tags = vec![];
 					node.keep_unknowns = None;
 
 					instance.properties = properties;
+					meta.set_refs(refs);
 				}
 			}
 
@@ -598,7 +903,7 @@ pub fn apply_update(snapshot: UpdatedSnapshot, tree: &mut Tree, vfs: &Vfs) -> Re
 			}
 
 			tree.update_meta(snapshot.id, meta);
-			project.save(&path)?;
+			save_project(&project, &path, tree, vfs)?;
 
 			if let Some(_class) = snapshot.class {
 				// You can't change the class of an instance inside Roblox Studio
@@ -610,41 +915,64 @@ pub fn apply_update(snapshot: UpdatedSnapshot, tree: &mut Tree, vfs: &Vfs) -> Re
 				unreachable!()
 			}
 		}
+		// Instance is a descendant of the model file
+		SourceKind::None if model.is_some() => {
+			let (model_id, _, _) = model.unwrap();
+			let filter = tree.get_meta(model_id).unwrap().context.syncback_filter().clone();
+			let instance = tree.get_instance_mut(snapshot.id).unwrap();
+
+			if let Some(name) = snapshot.name {
+				instance.name = name;
+			}
+
+			if let Some(properties) = snapshot.properties {
+				instance.properties = validate_properties(properties, &filter);
+			}
+
+			write_model(model_id, tree, vfs)?;
+		}
 		SourceKind::None => panic!("Attempted to update instance with no source: {:?}", snapshot.id),
 	}
 
 	Ok(())
 }
 
-pub fn apply_removal(id: Ref, tree: &mut Tree, vfs: &Vfs) -> Result<()> {
+pub fn apply_removal(id: Ref, tree: &mut Tree, vfs: &Vfs, outcomes: &mut Outcomes) -> Result<()> {
 	trace!("Removing {:?}", id);
 
 	if let Some(instance) = tree.get_instance(id) {
 		let filter = tree.get_meta(id).unwrap().context.syncback_filter();
 
 		if filter.matches_name(&instance.name) || filter.matches_class(&instance.class) {
-			filter_warn!(id);
+			filter_warn!(outcomes, id);
 			return Ok(());
 		}
 	} else {
 		warn!("Attempted to remove instance that doesn't exist: {:?}", id);
+		outcomes.report(id, missing("instance"));
 		return Ok(());
 	}
 
 	let meta = tree.get_meta(id).unwrap().clone();
 
-	fn remove_non_project_instances(id: Ref, meta: &Meta, tree: &mut Tree, vfs: &Vfs) -> Result<()> {
+	fn remove_non_project_instances(
+		id: Ref,
+		meta: &Meta,
+		tree: &mut Tree,
+		vfs: &Vfs,
+		outcomes: &mut Outcomes,
+	) -> Result<()> {
 		let filter = meta.context.syncback_filter();
 
 		for entry in meta.source.relevant() {
 			match entry {
-				SourceEntry::Project(_) => continue,
+				SourceEntry::Project(_) | SourceEntry::Missing(_) => continue,
 				_ => {
 					let path = entry.path();
 
 					if vfs.exists(path) {
 						if filter.matches_path(path) {
-							filter_warn!(id, path);
+							filter_warn!(outcomes, id, path);
 						} else {
 							vfs.remove(path)?
 						}
@@ -705,9 +1033,13 @@ pub fn apply_removal(id: Ref, tree: &mut Tree, vfs: &Vfs) -> Result<()> {
 	}
 
 	match meta.source.get() {
-		SourceKind::Path(_) => remove_non_project_instances(id, &meta, tree, vfs)?,
+		SourceKind::Path(_) => remove_non_project_instances(id, &meta, tree, vfs, outcomes)?,
 		SourceKind::Project(name, path, node, node_path) => {
-			let mut project = Project::load(path)?;
+			if is_inherited(id, "instance", node, outcomes) {
+				return Ok(());
+			}
+
+			let mut project = Project::load_local(path)?;
 			let parent_node = project.find_node_by_path(&node_path.parent());
 
 			parent_node.and_then(|node| node.tree.remove(name)).ok_or(anyhow!(
@@ -717,10 +1049,18 @@ pub fn apply_removal(id: Ref, tree: &mut Tree, vfs: &Vfs) -> Result<()> {
 			))?;
 
 			if node.path.is_some() {
-				remove_non_project_instances(id, &meta, tree, vfs)?;
+				remove_non_project_instances(id, &meta, tree, vfs, outcomes)?;
 			}
 
-			project.save(path)?;
+			save_project(&project, path, tree, vfs)?;
+		}
+		// Instance is a descendant of the model file
+		SourceKind::None if locate_model(id, tree).is_some() => {
+			let parent = tree.get_instance(id).unwrap().parent();
+
+			tree.remove_instance(id);
+
+			return write_model(parent, tree, vfs);
 		}
 		SourceKind::None => panic!("Attempted to remove instance with no source: {:?}", id),
 	}
@@ -729,3 +1069,12 @@ pub fn apply_removal(id: Ref, tree: &mut Tree, vfs: &Vfs) -> Result<()> {
 
 	Ok(())
 }
+
+/// Saves the project and stores its hash, so the resulting event is skipped
+fn save_project(project: &Project, path: &Path, tree: &mut Tree, vfs: &Vfs) -> Result<()> {
+	vfs.track(path);
+	project.save(path)?;
+	tree.update_hash(path, util::hash(&vfs.read(path)?));
+
+	Ok(())
+}
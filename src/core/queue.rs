@@ -1,13 +1,18 @@
 use anyhow::{bail, Result};
 use colored::Colorize;
-use crossbeam_channel::{Receiver, Sender};
-use std::{collections::HashMap, sync::RwLock};
+use crossbeam_channel::{Receiver, Sender, TryRecvError};
+use std::{
+	collections::{HashMap, VecDeque},
+	sync::{Arc, RwLock},
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::{sync::Notify, time};
 
 use crate::{
 	argon_warn,
 	config::Config,
-	constants::QUEUE_TIMEOUT,
-	server::{self, Message},
+	constants::{QUEUE_HISTORY_SIZE, QUEUE_TIMEOUT},
+	server::{self, Message, SequencedMessage},
 };
 
 macro_rules! read {
@@ -27,26 +32,59 @@ struct Listener {
 	pub id: u32,
 	pub name: String,
 	pub is_internal: bool,
+	pub last_sequence: u64,
 }
 
 #[derive(Debug)]
 struct Channel {
-	sender: Sender<Message>,
-	receiver: Receiver<Message>,
+	sender: Sender<SequencedMessage>,
+	receiver: Receiver<SequencedMessage>,
+	/// Wakes up async listeners waiting in `get_sequenced_async`
+	notify: Arc<Notify>,
+}
+
+impl Channel {
+	fn new() -> Self {
+		let (sender, receiver) = crossbeam_channel::unbounded();
+
+		Self {
+			sender,
+			receiver,
+			notify: Arc::new(Notify::new()),
+		}
+	}
+
+	fn send(&self, message: SequencedMessage) -> Result<()> {
+		self.sender.send(message)?;
+		self.notify.notify_one();
+
+		Ok(())
+	}
 }
 
 #[derive(Debug)]
 pub struct Queue {
 	queues: RwLock<HashMap<u32, Channel>>,
 	listeners: RwLock<Vec<Listener>>,
+	history: RwLock<VecDeque<SequencedMessage>>,
+	sequence: RwLock<u64>,
 	unsynced_changes: RwLock<u16>,
 }
 
 impl Queue {
 	pub fn new() -> Self {
+		// Sequence starts at the current time, so numbers from
+		// the previous server runs are never mistaken for recent ones
+		let sequence = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.map(|duration| duration.as_micros() as u64)
+			.unwrap_or_default();
+
 		Self {
 			queues: RwLock::new(HashMap::new()),
 			listeners: RwLock::new(Vec::new()),
+			history: RwLock::new(VecDeque::new()),
+			sequence: RwLock::new(sequence),
 			unsynced_changes: RwLock::new(0),
 		}
 	}
@@ -55,27 +93,41 @@ impl Queue {
 	where
 		M: Into<Message>,
 	{
+		// Messages sent to a single listener are not recorded
+		// in the history and share the last broadcast sequence
 		if let Some(id) = id {
 			if !self.is_subscribed(id) {
 				bail!("Not subscribed")
 			}
 
-			let queues = read!(self.queues);
-			let sender = queues.get(&id).unwrap().sender.clone();
-
-			sender.send(message.into())?;
+			read!(self.queues).get(&id).unwrap().send(SequencedMessage {
+				sequence: self.sequence(),
+				message: message.into(),
+			})?;
 
 			return Ok(());
 		}
 
-		let message: Message = message.into();
+		let mut history = write!(self.history);
+		let mut sequence = write!(self.sequence);
+
+		*sequence += 1;
+
+		let message = SequencedMessage {
+			sequence: *sequence,
+			message: message.into(),
+		};
+
+		history.push_back(message.clone());
+
+		if history.len() > QUEUE_HISTORY_SIZE {
+			history.pop_front();
+		}
+
 		let mut did_push = false;
 
 		for listener in read!(self.listeners).iter() {
-			let queues = read!(self.queues);
-			let sender = queues.get(&listener.id).unwrap().sender.clone();
-
-			sender.send(message.clone())?;
+			read!(self.queues).get(&listener.id).unwrap().send(message.clone())?;
 
 			did_push = true;
 		}
@@ -109,10 +161,20 @@ impl Queue {
 
 		let message = receiver.recv().ok();
 
-		Ok(message)
+		Ok(self.delivered(id, message))
 	}
 
 	pub fn get_timeout(&self, id: u32) -> Result<Option<Message>> {
+		Ok(self
+			.get_sequenced(id, QUEUE_TIMEOUT)?
+			.map(|sequenced| sequenced.message))
+	}
+
+	pub fn try_get(&self, id: u32, timeout: Duration) -> Result<Option<Message>> {
+		Ok(self.get_sequenced(id, timeout)?.map(|sequenced| sequenced.message))
+	}
+
+	pub fn get_sequenced(&self, id: u32, timeout: Duration) -> Result<Option<SequencedMessage>> {
 		if !self.is_subscribed(id) {
 			bail!("Not subscribed")
 		}
@@ -122,23 +184,76 @@ impl Queue {
 
 		drop(queues);
 
-		let message = receiver.recv_timeout(QUEUE_TIMEOUT).ok();
+		let message = receiver.recv_timeout(timeout).ok();
+
+		if let Some(message) = &message {
+			self.set_last_sequence(id, message.sequence);
+		}
+
+		Ok(message)
+	}
+
+	/// Same as `get_sequenced` but waits for the message
+	/// asynchronously instead of blocking the current thread
+	pub async fn get_sequenced_async(&self, id: u32, timeout: Duration) -> Result<Option<SequencedMessage>> {
+		if !self.is_subscribed(id) {
+			bail!("Not subscribed")
+		}
+
+		let (receiver, notify) = {
+			let queues = read!(self.queues);
+			let channel = queues.get(&id).unwrap();
+
+			(channel.receiver.clone(), channel.notify.clone())
+		};
+
+		// Messages sent before waiting are not missed, as
+		// `notify_one` stores a permit when nobody is waiting
+		let wait = async {
+			loop {
+				match receiver.try_recv() {
+					Ok(message) => return Some(message),
+					Err(TryRecvError::Disconnected) => return None,
+					Err(TryRecvError::Empty) => notify.notified().await,
+				}
+			}
+		};
+
+		let message = time::timeout(timeout, wait).await.ok().flatten();
+
+		if let Some(message) = &message {
+			self.set_last_sequence(id, message.sequence);
+		}
 
 		Ok(message)
 	}
 
+	fn delivered(&self, id: u32, message: Option<SequencedMessage>) -> Option<Message> {
+		let message = message?;
+
+		self.set_last_sequence(id, message.sequence);
+
+		Some(message.message)
+	}
+
+	fn set_last_sequence(&self, id: u32, sequence: u64) {
+		if let Some(listener) = write!(self.listeners).iter_mut().find(|listener| listener.id == id) {
+			listener.last_sequence = sequence;
+		}
+	}
+
 	pub fn subscribe(&self, id: u32, name: &str) -> Result<()> {
 		if self.is_subscribed(id) {
 			bail!("Already subscribed")
 		}
 
-		let (sender, receiver) = crossbeam_channel::unbounded();
-		let channel = Channel { sender, receiver };
+		let channel = Channel::new();
 
 		let listener = Listener {
 			id,
 			name: name.to_owned(),
 			is_internal: false,
+			last_sequence: self.sequence(),
 		};
 
 		write!(self.listeners).push(listener);
@@ -158,13 +273,13 @@ impl Queue {
 			id += 1;
 		}
 
-		let (sender, receiver) = crossbeam_channel::unbounded();
-		let channel = Channel { sender, receiver };
+		let channel = Channel::new();
 
 		let listener = Listener {
 			id,
 			name: format!("Internal listener #{}", id),
 			is_internal: true,
+			last_sequence: self.sequence(),
 		};
 
 		write!(self.listeners).push(listener);
@@ -173,13 +288,91 @@ impl Queue {
 		Ok(())
 	}
 
+	/// Subscribes the listener and queues all messages pushed after the given `sequence`,
+	/// returns `false` if some of them are no longer available and full resnapshot is required
+	pub fn resume(&self, id: u32, name: &str, sequence: u64) -> Result<bool> {
+		// Hold the history so no new messages are pushed in the meantime
+		let history = write!(self.history);
+
+		self.subscribe(id, name)?;
+
+		let current = self.sequence();
+		let messages = if let Some(messages) = Self::history_after(&history, sequence, current) {
+			messages
+		} else {
+			return Ok(false);
+		};
+
+		let queues = read!(self.queues);
+		let channel = queues.get(&id).unwrap();
+
+		for message in messages {
+			channel.send(message)?;
+		}
+
+		Ok(true)
+	}
+
+	/// Returns messages that were already delivered to the listener after the given `sequence`,
+	/// used when the listener reconnects and might have missed them, `None` if they are no longer available
+	pub fn redeliver(&self, id: u32, sequence: u64) -> Option<Vec<SequencedMessage>> {
+		let history = write!(self.history);
+
+		let last_sequence = read!(self.listeners)
+			.iter()
+			.find(|listener| listener.id == id)?
+			.last_sequence;
+
+		let messages = Self::history_after(&history, sequence, self.sequence())?;
+
+		Some(
+			messages
+				.into_iter()
+				.filter(|message| message.sequence <= last_sequence)
+				.collect(),
+		)
+	}
+
+	fn history_after(
+		history: &VecDeque<SequencedMessage>,
+		sequence: u64,
+		current: u64,
+	) -> Option<Vec<SequencedMessage>> {
+		if sequence == current {
+			return Some(vec![]);
+		}
+
+		let oldest = history.front()?.sequence;
+
+		if sequence > current || sequence + 1 < oldest {
+			return None;
+		}
+
+		Some(
+			history
+				.iter()
+				.filter(|message| message.sequence > sequence)
+				.cloned()
+				.collect(),
+		)
+	}
+
+	/// Returns sequence number of the last broadcast message
+	pub fn sequence(&self) -> u64 {
+		*read!(self.sequence)
+	}
+
 	pub fn unsubscribe(&self, id: u32) -> Result<()> {
 		if !self.is_subscribed(id) {
 			bail!("Not subscribed")
 		}
 
 		write!(self.listeners).retain(|listener| listener.id != id);
-		write!(self.queues).remove(&id);
+
+		// Wake up async listener, so it notices the channel was closed
+		if let Some(channel) = write!(self.queues).remove(&id) {
+			channel.notify.notify_one();
+		}
 
 		Ok(())
 	}
@@ -112,7 +112,12 @@ impl Snapshot {
 			self.meta.set_mesh_source(Some(mesh_source));
 		}
 
+		if let Some(id) = data.id {
+			self.meta.id = Some(id);
+		}
+
 		self.extend_properties(data.properties);
+		self.meta.refs.extend(data.refs);
 		self.meta.source.add_data(&data.path);
 	}
 
@@ -212,6 +217,7 @@ impl From<AddedSnapshot> for Snapshot {
 pub struct UpdatedSnapshot {
 	pub id: Ref,
 	pub meta: Option<Meta>,
+	pub parent: Option<Ref>,
 	pub name: Option<String>,
 	pub class: Option<String>,
 	pub properties: Option<Properties>,
@@ -221,6 +227,7 @@ impl UpdatedSnapshot {
 	pub fn new(id: Ref) -> Self {
 		Self {
 			id,
+			parent: None,
 			name: None,
 			class: None,
 			properties: None,
@@ -229,6 +236,6 @@ impl UpdatedSnapshot {
 	}
 
 	pub fn is_empty(&self) -> bool {
-		self.name.is_none() && self.class.is_none() && self.properties.is_none()
+		self.parent.is_none() && self.name.is_none() && self.class.is_none() && self.properties.is_none()
 	}
 }
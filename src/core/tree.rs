@@ -1,29 +1,57 @@
+use colored::Colorize;
 use multimap::MultiMap;
-use rbx_dom_weak::{types::Ref, Instance, InstanceBuilder, WeakDom};
+use rbx_dom_weak::{
+	types::{Ref, Variant},
+	Instance, InstanceBuilder, WeakDom,
+};
 use std::{
-	collections::HashMap,
+	collections::{HashMap, HashSet},
 	path::{Path, PathBuf},
+	str::FromStr,
 };
 
-use super::{meta::Meta, snapshot::Snapshot};
+use super::{
+	meta::{Meta, SourceEntry, SourceKind},
+	snapshot::Snapshot,
+};
+use crate::{argon_warn, config::Config, util, vfs::Vfs, Properties};
 
 #[derive(Debug)]
 pub struct Tree {
 	dom: WeakDom,
 	path_to_ids: MultiMap<PathBuf, Ref>,
 	id_to_meta: HashMap<Ref, Meta>,
+	path_to_hash: HashMap<PathBuf, u64>,
+	/// Instances referencing the key instance, the ones with
+	/// unresolved references are stored under `Ref::none()`
+	ref_to_referrers: HashMap<Ref, HashSet<Ref>>,
+	referrer_to_refs: HashMap<Ref, HashSet<Ref>>,
+	stable_ids: bool,
 }
 
 impl Tree {
 	pub fn new(snapshot: Snapshot) -> Self {
-		let builder = InstanceBuilder::new(snapshot.class)
-			.with_name(snapshot.name)
+		Self::with_stable_ids(snapshot, Config::new().stable_ids)
+	}
+
+	/// Create tree with explicitly set `stable_ids` instead of the one from config
+	pub fn with_stable_ids(snapshot: Snapshot, stable_ids: bool) -> Self {
+		let mut builder = InstanceBuilder::new(&snapshot.class)
+			.with_name(&snapshot.name)
 			.with_properties(snapshot.properties);
 
+		if stable_ids {
+			builder = builder.with_referent(stable_ref(&stable_key(&snapshot.meta, None)));
+		}
+
 		let mut tree = Self {
 			dom: WeakDom::new(builder),
 			id_to_meta: HashMap::new(),
 			path_to_ids: MultiMap::new(),
+			path_to_hash: HashMap::new(),
+			ref_to_referrers: HashMap::new(),
+			referrer_to_refs: HashMap::new(),
+			stable_ids,
 		};
 
 		let root_ref = tree.dom.root_ref();
@@ -34,12 +62,194 @@ impl Tree {
 			tree.insert_instance(child, root_ref);
 		}
 
+		tree.link_refs();
+
 		tree
 	}
 
+	/// Resolves paths of the referenced instances into `Ref` properties,
+	/// relative paths are resolved from the instance with the given `id`
+	pub fn resolve_refs(&self, id: Ref, refs: &HashMap<String, String>) -> Properties {
+		let mut properties = HashMap::new();
+
+		for (property, path) in refs {
+			match util::find_instance(&self.dom, id, path) {
+				Ok(target) => {
+					properties.insert(property.to_owned(), Variant::Ref(target));
+				}
+				Err(err) => argon_warn!(
+					"Failed to resolve {} property of {}: {}",
+					property.bold(),
+					util::get_instance_path(&self.dom, id).unwrap_or_default().bold(),
+					err
+				),
+			}
+		}
+
+		properties
+	}
+
+	/// Same as `resolve_refs` but without warnings, missing or ambiguous instances are `Ref::none()`
+	fn find_refs(&self, id: Ref, refs: &HashMap<String, String>) -> HashMap<String, Ref> {
+		refs.iter()
+			.map(|(property, path)| {
+				let target = util::find_instance(&self.dom, id, path).unwrap_or_else(|_| Ref::none());
+				(property.to_owned(), target)
+			})
+			.collect()
+	}
+
+	/// Returns instances currently referenced by `Ref` properties listed in the instance meta
+	fn linked_refs(&self, id: Ref) -> HashMap<String, Ref> {
+		let (Some(meta), Some(instance)) = (self.id_to_meta.get(&id), self.dom.get_by_ref(id)) else {
+			return HashMap::new();
+		};
+
+		meta.refs
+			.keys()
+			.map(|property| {
+				let target = match instance.properties.get(property) {
+					Some(Variant::Ref(target)) => *target,
+					_ => Ref::none(),
+				};
+
+				(property.to_owned(), target)
+			})
+			.collect()
+	}
+
+	/// Updates the reverse index of references made by the instance with the given `id`
+	pub fn index_refs(&mut self, id: Ref) {
+		self.unindex_refs(id);
+
+		let targets: HashSet<Ref> = self.linked_refs(id).into_values().collect();
+
+		if targets.is_empty() {
+			return;
+		}
+
+		for target in &targets {
+			self.ref_to_referrers.entry(*target).or_default().insert(id);
+		}
+
+		self.referrer_to_refs.insert(id, targets);
+	}
+
+	fn unindex_refs(&mut self, id: Ref) {
+		let Some(targets) = self.referrer_to_refs.remove(&id) else {
+			return;
+		};
+
+		for target in targets {
+			if let Some(referrers) = self.ref_to_referrers.get_mut(&target) {
+				referrers.remove(&id);
+
+				if referrers.is_empty() {
+					self.ref_to_referrers.remove(&target);
+				}
+			}
+		}
+	}
+
+	/// Returns instances referencing the given instances or their descendants
+	/// (or referencing from inside of them, as relative paths may change),
+	/// instances that no longer exist and unresolved ones if `unresolved` is set,
+	/// whose stored reference paths now resolve to different instances
+	pub fn stale_referrers(&self, ids: &[Ref], unresolved: bool) -> Vec<Ref> {
+		let mut candidates = HashSet::new();
+
+		for id in ids {
+			if !self.exists(*id) {
+				continue;
+			}
+
+			for instance in self.dom.descendants_of(*id) {
+				let id = instance.referent();
+
+				if let Some(referrers) = self.ref_to_referrers.get(&id) {
+					candidates.extend(referrers);
+				}
+
+				if self.referrer_to_refs.contains_key(&id) {
+					candidates.insert(id);
+				}
+			}
+		}
+
+		for (target, referrers) in &self.ref_to_referrers {
+			if (target.is_some() && !self.exists(*target)) || (target.is_none() && unresolved) {
+				candidates.extend(referrers);
+			}
+		}
+
+		candidates
+			.into_iter()
+			.filter(|id| {
+				self.get_meta(*id)
+					.is_some_and(|meta| self.find_refs(*id, &meta.refs) != self.linked_refs(*id))
+			})
+			.collect()
+	}
+
+	/// Resolves `Ref` properties of the stale referrers of the given instances again,
+	/// references that can no longer be resolved are cleared. Returns updated instances
+	pub fn relink_referrers(&mut self, ids: &[Ref]) -> Vec<Ref> {
+		let referrers = self.stale_referrers(ids, true);
+
+		for id in &referrers {
+			let refs = self.id_to_meta[id].refs.clone();
+			let mut properties = self.resolve_refs(*id, &refs);
+
+			for property in refs.keys() {
+				properties
+					.entry(property.to_owned())
+					.or_insert(Variant::Ref(Ref::none()));
+			}
+
+			if let Some(instance) = self.dom.get_by_ref_mut(*id) {
+				instance.properties.extend(properties);
+			}
+
+			self.index_refs(*id);
+		}
+
+		referrers
+	}
+
+	/// Resolves `Ref` properties of all instances that have references stored in their meta
+	pub fn link_refs(&mut self) {
+		let ids: Vec<Ref> = self
+			.id_to_meta
+			.iter()
+			.filter(|(_, meta)| !meta.refs.is_empty())
+			.map(|(id, _)| *id)
+			.collect();
+
+		for id in ids {
+			self.link_instance_refs(id);
+		}
+	}
+
+	/// Resolves `Ref` properties of a single instance, returns resolved properties
+	pub fn link_instance_refs(&mut self, id: Ref) -> Properties {
+		let properties = match self.id_to_meta.get(&id) {
+			Some(meta) if !meta.refs.is_empty() => self.resolve_refs(id, &meta.refs),
+			_ => return HashMap::new(),
+		};
+
+		if let Some(instance) = self.dom.get_by_ref_mut(id) {
+			instance.properties.extend(properties.clone());
+		}
+
+		self.index_refs(id);
+
+		properties
+	}
+
 	pub fn insert_instance(&mut self, snapshot: Snapshot, parent: Ref) -> Ref {
-		let builder = InstanceBuilder::new(snapshot.class)
-			.with_name(snapshot.name)
+		let builder = InstanceBuilder::new(&snapshot.class)
+			.with_name(&snapshot.name)
+			.with_referent(self.new_ref(&snapshot, parent))
 			.with_properties(snapshot.properties);
 
 		let id = self.dom.insert(parent, builder);
@@ -54,8 +264,9 @@ impl Tree {
 	}
 
 	pub fn insert_instance_non_recursive(&mut self, snapshot: Snapshot, parent: Ref) -> Ref {
-		let builder = InstanceBuilder::new(snapshot.class)
-			.with_name(snapshot.name)
+		let builder = InstanceBuilder::new(&snapshot.class)
+			.with_name(&snapshot.name)
+			.with_referent(self.new_ref(&snapshot, parent))
 			.with_properties(snapshot.properties);
 
 		let id = self.dom.insert(parent, builder);
@@ -76,6 +287,30 @@ impl Tree {
 		self.insert_meta(id, snapshot.meta);
 	}
 
+	/// Returns random `Ref` or the one derived from the instance source
+	/// if `stable_ids` setting is enabled, so it persists across restarts
+	fn new_ref(&self, snapshot: &Snapshot, parent: Ref) -> Ref {
+		if !self.stable_ids {
+			return Ref::new();
+		}
+
+		let id = stable_ref(&stable_key(&snapshot.meta, Some((self, snapshot, parent))));
+
+		if self.dom.get_by_ref(id).is_some() {
+			if let Some(custom_id) = &snapshot.meta.id {
+				argon_warn!(
+					"Instance ID {} of {} is already in use, using random ID instead",
+					custom_id.bold(),
+					snapshot.name.bold()
+				);
+			}
+
+			return Ref::new();
+		}
+
+		id
+	}
+
 	pub fn remove_instance(&mut self, id: Ref) {
 		let mut to_remove = vec![id];
 
@@ -97,6 +332,28 @@ impl Tree {
 		self.dom.destroy(id);
 	}
 
+	/// Moves the instance under a new parent, keeping its ID and descendants
+	pub fn move_instance(&mut self, id: Ref, parent: Ref) {
+		self.dom.transfer_within(id, parent);
+	}
+
+	/// Updates source paths of all instances after `from` path was renamed to `to`
+	pub fn move_paths(&mut self, from: &Path, to: &Path) {
+		let ids: Vec<Ref> = self
+			.id_to_meta
+			.iter()
+			.filter(|(_, meta)| meta.source.paths().iter().any(|path| path.starts_with(from)))
+			.map(|(id, _)| *id)
+			.collect();
+
+		for id in ids {
+			let mut meta = self.id_to_meta[&id].clone();
+			meta.source.move_paths(from, to);
+
+			self.update_meta(id, meta);
+		}
+	}
+
 	pub fn get_instance(&self, id: Ref) -> Option<&Instance> {
 		self.dom.get_by_ref(id)
 	}
@@ -110,7 +367,10 @@ impl Tree {
 			self.path_to_ids.insert(path.to_owned(), id);
 		}
 
-		self.id_to_meta.insert(id, meta)
+		let old_meta = self.id_to_meta.insert(id, meta);
+		self.index_refs(id);
+
+		old_meta
 	}
 
 	pub fn update_meta(&mut self, id: Ref, meta: Meta) -> Option<Meta> {
@@ -133,6 +393,7 @@ impl Tree {
 
 			for path in removed {
 				self.path_to_ids.remove(path);
+				self.path_to_hash.remove(path);
 			}
 
 			for path in added {
@@ -141,16 +402,19 @@ impl Tree {
 		}
 
 		self.id_to_meta.insert(id, meta);
+		self.index_refs(id);
 
 		old_meta
 	}
 
 	pub fn remove_meta(&mut self, id: Ref) -> Option<Meta> {
 		let meta = self.id_to_meta.remove(&id);
+		self.unindex_refs(id);
 
 		if let Some(meta) = &meta {
 			for path in meta.source.paths() {
 				self.path_to_ids.remove(path);
+				self.path_to_hash.remove(path);
 			}
 		}
 
@@ -165,10 +429,51 @@ impl Tree {
 		self.id_to_meta.get_mut(&id)
 	}
 
+	/// Stores hash of the file contents, returns whether it differs from the
+	/// previous one. Only paths of synced instances are tracked, it's cleared
+	/// once the path is no longer used by any instance
+	pub fn update_hash(&mut self, path: &Path, hash: u64) -> bool {
+		if !self.path_to_ids.contains_key(path) {
+			return true;
+		}
+
+		self.path_to_hash.insert(path.to_owned(), hash) != Some(hash)
+	}
+
+	/// Stores hashes of all synced files, so saving them
+	/// without changes is recognized right after startup
+	pub fn seed_hashes(&mut self, vfs: &Vfs) {
+		for path in self.path_to_ids.keys() {
+			if !vfs.is_file(path) {
+				continue;
+			}
+
+			if let Ok(contents) = vfs.read(path) {
+				self.path_to_hash.insert(path.to_owned(), util::hash(&contents));
+			}
+		}
+	}
+
 	pub fn get_ids(&self, path: &Path) -> Option<&Vec<Ref>> {
 		self.path_to_ids.get_vec(path)
 	}
 
+	/// Returns IDs of project nodes with paths (including the
+	/// ones that do not exist yet) inside the given path
+	pub fn get_project_ids(&self, path: &Path) -> Vec<Ref> {
+		self.id_to_meta
+			.iter()
+			.filter(|(_, meta)| {
+				matches!(meta.source.get(), SourceKind::Project(..))
+					&& meta.source.relevant().iter().any(|entry| match entry {
+						SourceEntry::Project(_) => false,
+						_ => entry.path().starts_with(path),
+					})
+			})
+			.map(|(id, _)| *id)
+			.collect()
+	}
+
 	pub fn exists(&self, id: Ref) -> bool {
 		self.dom.get_by_ref(id).is_some()
 	}
@@ -197,3 +502,44 @@ impl Tree {
 		self.dom.root().children()
 	}
 }
+
+/// Returns key that identifies the instance across restarts, instances
+/// without a source are identified by their parent, name, class and position
+fn stable_key(meta: &Meta, child: Option<(&Tree, &Snapshot, Ref)>) -> String {
+	if let Some(id) = &meta.id {
+		return format!("id:{}", id);
+	}
+
+	match meta.source.get() {
+		SourceKind::Path(path) => format!("path:{}", path.display()),
+		SourceKind::Project(_, path, _, node_path) => format!("project:{}:{}", path.display(), node_path),
+		SourceKind::None => {
+			if let Some((tree, snapshot, parent)) = child {
+				let index = tree.dom.get_by_ref(parent).map_or(0, |parent| {
+					parent
+						.children()
+						.iter()
+						.filter_map(|child| tree.dom.get_by_ref(*child))
+						.filter(|child| child.name == snapshot.name && child.class == snapshot.class)
+						.count()
+				});
+
+				format!("child:{}:{}:{}:{}", parent, snapshot.name, snapshot.class, index)
+			} else {
+				String::from("root")
+			}
+		}
+	}
+}
+
+/// 128-bit FNV-1a hash of the `key` used as instance referent
+fn stable_ref(key: &str) -> Ref {
+	const OFFSET: u128 = 0x6c62272e07bb014262b821756295c58d;
+	const PRIME: u128 = 0x0000000001000000000000000000013b;
+
+	let hash = key
+		.bytes()
+		.fold(OFFSET, |hash, byte| (hash ^ byte as u128).wrapping_mul(PRIME));
+
+	Ref::from_str(&format!("{:032x}", hash)).unwrap_or_else(|_| Ref::new())
+}
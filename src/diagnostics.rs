@@ -0,0 +1,349 @@
+use lazy_static::lazy_static;
+use log::{error, warn};
+use rbx_dom_weak::types::Ref;
+use serde_json::json;
+use std::{
+	collections::HashSet,
+	fmt::{self, Display, Formatter},
+	fs,
+	path::{Path, PathBuf},
+	sync::Mutex,
+};
+use toml_edit::{ImDocument, Item, TableLike, Value};
+
+use crate::{
+	core::{
+		meta::{NodePath, SourceEntry, SourceKind, SyncRule},
+		tree::Tree,
+	},
+	ext::PathExt,
+	jsonc, lock, logger,
+	project::Project,
+	util,
+};
+
+lazy_static! {
+	static ref DIAGNOSTICS: Mutex<Option<Vec<Diagnostic>>> = Mutex::new(None);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+	Error,
+	Warning,
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+	pub severity: Severity,
+	pub message: String,
+	pub path: PathBuf,
+	pub json_path: Option<String>,
+	pub node_path: Option<NodePath>,
+	pub instance: Option<String>,
+}
+
+impl Diagnostic {
+	pub fn error(message: &str, path: &Path) -> Self {
+		Self::new(Severity::Error, message, path)
+	}
+
+	pub fn warning(message: &str, path: &Path) -> Self {
+		Self::new(Severity::Warning, message, path)
+	}
+
+	fn new(severity: Severity, message: &str, path: &Path) -> Self {
+		Self {
+			severity,
+			message: message.to_owned(),
+			path: path.to_owned(),
+			json_path: None,
+			node_path: None,
+			instance: None,
+		}
+	}
+
+	pub fn with_json_path(mut self, json_path: &str) -> Self {
+		self.json_path = Some(json_path.to_owned());
+		self
+	}
+
+	pub fn with_node_path(mut self, node_path: &NodePath) -> Self {
+		self.node_path = Some(node_path.to_owned());
+		self
+	}
+
+	pub fn with_instance(mut self, instance: &str) -> Self {
+		self.instance = Some(instance.to_owned());
+		self
+	}
+
+	pub fn is_error(&self) -> bool {
+		self.severity == Severity::Error
+	}
+
+	/// Returns the line the diagnostic refers to, either reported by the parser
+	/// in the message or located by following the JSON or node path in the parsed file
+	pub fn line(&self) -> Option<usize> {
+		if let Some(line) = parse_line(&self.message) {
+			return Some(line);
+		}
+
+		let json_path = self
+			.json_path
+			.clone()
+			.or_else(|| self.node_path.as_ref().map(|path| path.to_string()))?;
+
+		let contents = fs::read_to_string(&self.path).ok()?;
+
+		let position = if self.path.get_name().ends_with(".toml") {
+			locate_toml(&contents, &json_path)?
+		} else {
+			jsonc::locate(&contents, &json_path).ok()?
+		};
+
+		Some(contents[..position].matches('\n').count() + 1)
+	}
+
+	/// Emits the diagnostic as a `diagnostic` event in JSON output mode
+	pub fn emit(&self) {
+		logger::emit(
+			"diagnostic",
+			json!({
+				"severity": if self.is_error() { "error" } else { "warning" },
+				"message": self.message,
+				"path": self.path,
+				"line": self.line(),
+				"jsonPath": self.json_path,
+				"nodePath": self.node_path.as_ref().map(|path| path.to_string()),
+				"instance": self.instance,
+			}),
+		);
+	}
+}
+
+impl Display for Diagnostic {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		write!(f, "{} at {}", self.message, self.path.display())?;
+
+		if let Some(json_path) = &self.json_path {
+			write!(f, ", JSON path: {}", json_path)?;
+		}
+
+		if let Some(node_path) = &self.node_path {
+			write!(f, ", node path: {}", node_path)?;
+		}
+
+		if let Some(instance) = &self.instance {
+			write!(f, ", instance: {}", instance)?;
+		}
+
+		Ok(())
+	}
+}
+
+/// Starts collecting diagnostics instead of logging them
+pub fn collect() {
+	*lock!(DIAGNOSTICS) = Some(Vec::new());
+}
+
+pub fn is_collecting() -> bool {
+	lock!(DIAGNOSTICS).is_some()
+}
+
+/// Stops collecting and returns all diagnostics reported so far
+pub fn take() -> Vec<Diagnostic> {
+	lock!(DIAGNOSTICS).take().unwrap_or_default()
+}
+
+/// Collects the diagnostic if enabled, logs it otherwise
+pub fn report(diagnostic: Diagnostic) {
+	if let Some(diagnostics) = lock!(DIAGNOSTICS).as_mut() {
+		diagnostics.push(diagnostic);
+		return;
+	}
+
+	if util::env_output_json() {
+		diagnostic.emit();
+		return;
+	}
+
+	match diagnostic.severity {
+		Severity::Error => error!("{}", diagnostic),
+		Severity::Warning => warn!("{}", diagnostic),
+	}
+}
+
+/// Checks the whole tree for problems that do not
+/// prevent it from being built but cause issues later
+pub fn check_tree(tree: &Tree, project: &Project) -> Vec<Diagnostic> {
+	let mut diagnostics = Vec::new();
+
+	check_instance(tree, tree.root_ref(), &mut diagnostics);
+
+	for (index, rule) in project.sync_rules.iter().enumerate() {
+		if !tree.id_map().keys().any(|path| matches_rule(rule, path)) {
+			diagnostics.push(
+				Diagnostic::warning("Sync rule never matches any file", &project.path)
+					.with_json_path(&format!("syncRules/{}", index)),
+			);
+		}
+	}
+
+	diagnostics
+}
+
+fn check_instance(tree: &Tree, id: Ref, diagnostics: &mut Vec<Diagnostic>) {
+	let database = rbx_reflection_database::get();
+	let instance = tree.get_instance(id).unwrap();
+
+	let located = |diagnostic: Diagnostic| {
+		let diagnostic = if let Some(path) = util::get_instance_path(tree.inner(), id) {
+			diagnostic.with_instance(&path)
+		} else {
+			diagnostic
+		};
+
+		match tree.get_meta(id).map(|meta| meta.source.get()) {
+			Some(SourceKind::Project(_, _, _, node_path)) => diagnostic.with_node_path(node_path),
+			_ => diagnostic,
+		}
+	};
+
+	let path = source_path(tree, id);
+
+	match database.classes.get(instance.class.as_str()) {
+		Some(class) => {
+			for property in instance.properties.keys() {
+				let is_known = database
+					.superclasses(class)
+					.unwrap_or_default()
+					.into_iter()
+					.any(|class| class.properties.contains_key(property.as_str()));
+
+				if !is_known {
+					diagnostics.push(located(Diagnostic::warning(
+						&format!("Unknown property {} of class {}", property, instance.class),
+						&path,
+					)));
+				}
+			}
+		}
+		None => {
+			diagnostics.push(located(Diagnostic::error(
+				&format!("Unknown class {}", instance.class),
+				&path,
+			)));
+		}
+	}
+
+	if let Some(meta) = tree.get_meta(id) {
+		for (property, target) in &meta.refs {
+			if let Err(err) = util::find_instance(tree.inner(), id, target) {
+				diagnostics.push(located(Diagnostic::warning(
+					&format!("Cannot resolve {} property: {}", property, err),
+					&path,
+				)));
+			}
+		}
+	}
+
+	let mut names = HashSet::new();
+	let mut duplicates = HashSet::new();
+
+	for &child in instance.children() {
+		let name = &tree.get_instance(child).unwrap().name;
+
+		if !names.insert(name) && duplicates.insert(name) {
+			diagnostics.push(located(Diagnostic::warning(
+				&format!("Multiple children named {} cannot be synced back", name),
+				&path,
+			)));
+		}
+	}
+
+	for &child in instance.children() {
+		check_instance(tree, child, diagnostics);
+	}
+}
+
+/// Returns the file that defines the instance or its closest ancestor
+fn source_path(tree: &Tree, id: Ref) -> PathBuf {
+	let mut current = id;
+
+	loop {
+		if let Some(meta) = tree.get_meta(current) {
+			match meta.source.get() {
+				SourceKind::Path(path) => {
+					return meta
+						.source
+						.relevant()
+						.iter()
+						.find_map(|entry| match entry {
+							SourceEntry::File(path) => Some(path.to_owned()),
+							_ => None,
+						})
+						.unwrap_or(path.to_owned());
+				}
+				SourceKind::Project(_, path, _, _) => return path.to_owned(),
+				SourceKind::None => {}
+			}
+		}
+
+		match tree.get_instance(current).map(|instance| instance.parent()) {
+			Some(parent) if parent.is_some() => current = parent,
+			_ => return PathBuf::new(),
+		}
+	}
+}
+
+/// Extracts the line number from parser errors like `... at line 3 column 5`
+/// Same as `jsonc::locate` but for TOML files, uses spans of the keys
+fn locate_toml(source: &str, path: &str) -> Option<usize> {
+	let document = ImDocument::parse(source).ok()?;
+
+	let mut table: &dyn TableLike = document.as_table();
+	let mut position = 0;
+	let mut segments = path.split('/').filter(|segment| !segment.is_empty());
+
+	while let Some(segment) = segments.next() {
+		let Some((key, item)) = table.get_key_value(segment) else {
+			break;
+		};
+
+		position = key.span().map_or(position, |span| span.start);
+
+		table = match item {
+			Item::Table(child) => child,
+			Item::Value(Value::InlineTable(child)) => child,
+			Item::ArrayOfTables(array) => match segments.next().and_then(|index| array.get(index.parse().ok()?)) {
+				Some(child) => {
+					position = child.span().map_or(position, |span| span.start);
+					child
+				}
+				None => break,
+			},
+			_ => break,
+		};
+	}
+
+	Some(position)
+}
+
+fn parse_line(message: &str) -> Option<usize> {
+	let index = message.find("line ")?;
+
+	message[index + 5..]
+		.chars()
+		.take_while(|char| char.is_ascii_digit())
+		.collect::<String>()
+		.parse()
+		.ok()
+}
+
+fn matches_rule(rule: &SyncRule, path: &Path) -> bool {
+	rule.pattern.as_ref().is_some_and(|pattern| pattern.matches_path(path))
+		|| rule
+			.child_pattern
+			.as_ref()
+			.is_some_and(|pattern| pattern.matches(path.get_name()))
+}
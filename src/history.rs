@@ -0,0 +1,352 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::{HashMap, HashSet},
+	fs,
+	path::{Path, PathBuf},
+};
+
+use crate::{
+	core::tree::Tree,
+	ext::PathExt,
+	util,
+	vfs::{journal::Backup, Vfs},
+};
+
+/// Maximum size of the line comparison table, larger files are diffed as a whole
+const MAX_DIFF_SIZE: usize = 4_000_000;
+
+/// What the syncback did to the path
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Change {
+	Created,
+	Modified,
+	Removed,
+}
+
+/// Single path modified by the syncback, its previous
+/// contents are stored next to the entry under its index
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Record {
+	/// Path relative to the project directory
+	pub path: PathBuf,
+	pub instance: Option<String>,
+	pub change: Change,
+	pub is_dir: bool,
+}
+
+impl Record {
+	/// Whether the record belongs to the given instance or its descendants
+	pub fn matches(&self, instance: &str) -> bool {
+		let instance = instance.trim_matches('/');
+
+		self.instance.as_ref().is_some_and(|path| {
+			path == instance || path.strip_prefix(instance).is_some_and(|rest| rest.starts_with('/'))
+		})
+	}
+}
+
+/// All paths modified by a single syncback batch
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Entry {
+	/// Unix time of the change in milliseconds, unique within the project
+	pub id: u64,
+	pub records: Vec<Record>,
+	#[serde(skip)]
+	dir: PathBuf,
+}
+
+impl Entry {
+	pub fn time(&self) -> DateTime<Local> {
+		Local
+			.timestamp_millis_opt(self.id as i64)
+			.single()
+			.unwrap_or_else(Local::now)
+	}
+
+	/// Returns contents of the file from before the change,
+	/// `None` for directories and files created by it
+	pub fn contents(&self, index: usize) -> Result<Option<Vec<u8>>> {
+		let record = &self.records[index];
+
+		if record.change == Change::Created || record.is_dir {
+			return Ok(None);
+		}
+
+		let contents = fs::read(self.dir.join(index.to_string()))
+			.with_context(|| format!("Failed to read history of {}", record.path.display()))?;
+
+		Ok(Some(contents))
+	}
+}
+
+/// Local history of files modified by two-way sync, stored per project
+pub struct History {
+	root: PathBuf,
+	dir: PathBuf,
+}
+
+impl History {
+	/// Opens history of the project in the given directory, stored in `~/.argon/history`
+	pub fn new(root: &Path) -> Result<Self> {
+		let dir = util::get_argon_dir()?.join("history").join(project_key(root));
+
+		Ok(Self::from_dir(root, &dir))
+	}
+
+	pub fn from_dir(root: &Path, dir: &Path) -> Self {
+		Self {
+			root: root.to_owned(),
+			dir: dir.to_owned(),
+		}
+	}
+
+	/// Saves previous state of all paths that differ from their backups
+	/// as a new entry, returns `None` if nothing has changed
+	pub fn record(&self, backups: HashMap<PathBuf, Backup>, tree: &Tree, vfs: &Vfs) -> Result<Option<Entry>> {
+		let mut backups: Vec<(PathBuf, Backup)> = backups
+			.into_iter()
+			.filter(|(path, backup)| match backup {
+				Backup::Missing => vfs.exists(path),
+				Backup::Directory => !vfs.is_dir(path),
+				Backup::File(contents) => vfs.read(path).ok().as_ref() != Some(contents),
+			})
+			.collect();
+
+		if backups.is_empty() {
+			return Ok(None);
+		}
+
+		backups.sort_by(|a, b| a.0.cmp(&b.0));
+
+		let mut id = chrono::Utc::now().timestamp_millis() as u64;
+
+		while self.dir.join(id.to_string()).exists() {
+			id += 1;
+		}
+
+		let dir = self.dir.join(id.to_string());
+		fs::create_dir_all(&dir)?;
+
+		let mut records = vec![];
+
+		for (index, (path, backup)) in backups.into_iter().enumerate() {
+			let change = match backup {
+				Backup::Missing => Change::Created,
+				_ if vfs.exists(&path) => Change::Modified,
+				_ => Change::Removed,
+			};
+
+			if let Backup::File(contents) = &backup {
+				fs::write(dir.join(index.to_string()), contents)?;
+			}
+
+			records.push(Record {
+				instance: instance_path(&path, tree),
+				path: path.strip_prefix(&self.root).unwrap_or(&path).to_owned(),
+				change,
+				is_dir: backup == Backup::Directory,
+			});
+		}
+
+		let entry = Entry { id, records, dir };
+
+		// Written last, so interrupted entries are ignored
+		fs::write(entry.dir.join("entry.json"), serde_json::to_vec(&entry)?)?;
+
+		Ok(Some(entry))
+	}
+
+	/// Returns all entries, oldest first
+	pub fn entries(&self) -> Result<Vec<Entry>> {
+		let mut entries = vec![];
+
+		if !self.dir.exists() {
+			return Ok(entries);
+		}
+
+		for dir in fs::read_dir(&self.dir)? {
+			let dir = dir?.path();
+
+			let entry = fs::read(dir.join("entry.json"))
+				.ok()
+				.and_then(|contents| serde_json::from_slice::<Entry>(&contents).ok());
+
+			if let Some(mut entry) = entry {
+				entry.dir = dir;
+				entries.push(entry);
+			}
+		}
+
+		entries.sort_by_key(|entry| entry.id);
+
+		Ok(entries)
+	}
+
+	/// Removes the oldest entries so at most `size` are kept
+	pub fn prune(&self, size: usize) -> Result<()> {
+		let entries = self.entries()?;
+
+		if entries.len() > size {
+			for entry in &entries[..entries.len() - size] {
+				fs::remove_dir_all(&entry.dir)?;
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Returns absolute path of the record
+	pub fn path(&self, record: &Record) -> PathBuf {
+		self.root.join(&record.path)
+	}
+
+	/// Brings the path back to its state from before the change,
+	/// returns `false` if it was created by the change and is left as is
+	pub fn restore(&self, entry: &Entry, index: usize, vfs: &Vfs) -> Result<bool> {
+		let record = &entry.records[index];
+		let path = self.path(record);
+
+		if record.change == Change::Created {
+			return Ok(false);
+		}
+
+		if record.is_dir {
+			if vfs.is_file(&path) {
+				vfs.remove(&path)?;
+			}
+
+			if !vfs.exists(&path) {
+				vfs.create_dir(&path)?;
+			}
+		} else if let Some(contents) = entry.contents(index)? {
+			if vfs.is_dir(&path) {
+				vfs.remove(&path)?;
+			}
+
+			vfs.write(&path, &contents)?;
+		}
+
+		Ok(true)
+	}
+}
+
+/// Returns the first record of every path in the given entries,
+/// which holds the oldest known state of that path
+pub fn originals(entries: &[Entry]) -> Vec<(&Entry, usize)> {
+	let mut paths = HashSet::new();
+	let mut originals = vec![];
+
+	for entry in entries {
+		for (index, record) in entry.records.iter().enumerate() {
+			if paths.insert(&record.path) {
+				originals.push((entry, index));
+			}
+		}
+	}
+
+	originals
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLine<'a> {
+	Same(&'a str),
+	Added(&'a str),
+	Removed(&'a str),
+}
+
+/// Compares two texts line by line using their longest common subsequence
+pub fn diff<'a>(old: &'a str, new: &'a str) -> Vec<DiffLine<'a>> {
+	let old: Vec<&str> = old.lines().collect();
+	let new: Vec<&str> = new.lines().collect();
+
+	if old.len() * new.len() > MAX_DIFF_SIZE {
+		return old
+			.into_iter()
+			.map(DiffLine::Removed)
+			.chain(new.into_iter().map(DiffLine::Added))
+			.collect();
+	}
+
+	// Length of the common subsequence of `old[i..]` and `new[j..]`
+	let mut table = vec![vec![0; new.len() + 1]; old.len() + 1];
+
+	for i in (0..old.len()).rev() {
+		for j in (0..new.len()).rev() {
+			table[i][j] = if old[i] == new[j] {
+				table[i + 1][j + 1] + 1
+			} else {
+				table[i + 1][j].max(table[i][j + 1])
+			};
+		}
+	}
+
+	let mut lines = vec![];
+	let (mut i, mut j) = (0, 0);
+
+	while i < old.len() && j < new.len() {
+		if old[i] == new[j] {
+			lines.push(DiffLine::Same(old[i]));
+			i += 1;
+			j += 1;
+		} else if table[i + 1][j] >= table[i][j + 1] {
+			lines.push(DiffLine::Removed(old[i]));
+			i += 1;
+		} else {
+			lines.push(DiffLine::Added(new[j]));
+			j += 1;
+		}
+	}
+
+	lines.extend(old[i..].iter().map(|line| DiffLine::Removed(line)));
+	lines.extend(new[j..].iter().map(|line| DiffLine::Added(line)));
+
+	lines
+}
+
+/// Returns path of the instance synced from the given path or, for paths
+/// that no longer exist, of their closest synced ancestor joined with their names
+fn instance_path(path: &Path, tree: &Tree) -> Option<String> {
+	let mut names = vec![];
+
+	for ancestor in path.ancestors() {
+		if let Some(&id) = tree.get_ids(ancestor).and_then(|ids| ids.first()) {
+			let instance = util::get_instance_path(tree.inner(), id)?;
+
+			let path = [instance.as_str()]
+				.into_iter()
+				.chain(names.into_iter().rev())
+				.filter(|name| !name.is_empty())
+				.collect::<Vec<_>>()
+				.join("/");
+
+			return Some(path);
+		}
+
+		let name = ancestor.get_name().split('.').next().unwrap_or_default();
+
+		if !name.is_empty() && name != "init" {
+			names.push(name);
+		}
+	}
+
+	None
+}
+
+/// Returns 64-bit FNV-1a hash of the project directory, which
+/// unlike the default hasher stays the same across Argon versions
+fn project_key(root: &Path) -> String {
+	const OFFSET: u64 = 0xcbf29ce484222325;
+	const PRIME: u64 = 0x00000100000001b3;
+
+	let hash = root
+		.to_string()
+		.bytes()
+		.fold(OFFSET, |hash, byte| (hash ^ byte as u64).wrapping_mul(PRIME));
+
+	format!("{:016x}", hash)
+}
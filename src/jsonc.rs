@@ -0,0 +1,395 @@
+//! Support for JSONC (JSON with comments and trailing commas),
+//! including updates that keep the original formatting. Other
+//! JSON5 extensions like unquoted keys are not supported
+
+use anyhow::{bail, Result};
+use json_formatter::JsonFormatter;
+use serde::Serialize;
+use serde_json::{Serializer, Value};
+use std::cmp::Reverse;
+
+/// Replaces comments and trailing commas with whitespace,
+/// so the result can be parsed by `serde_json` while
+/// positions of all remaining tokens stay the same
+pub fn strip(source: &str) -> String {
+	let bytes = source.as_bytes();
+	let mut output = bytes.to_vec();
+	let mut last_comma = None;
+	let mut index = 0;
+
+	while index < bytes.len() {
+		match bytes[index] {
+			b'"' => {
+				last_comma = None;
+				index += 1;
+
+				while index < bytes.len() && bytes[index] != b'"' {
+					if bytes[index] == b'\\' {
+						index += 1;
+					}
+
+					index += 1;
+				}
+			}
+			b'/' if matches!(bytes.get(index + 1), Some(b'/') | Some(b'*')) => {
+				let end = if bytes[index + 1] == b'/' {
+					source[index..].find('\n').map_or(bytes.len(), |end| index + end)
+				} else {
+					source[index + 2..]
+						.find("*/")
+						.map_or(bytes.len(), |end| index + end + 4)
+				};
+
+				for byte in &mut output[index..end] {
+					if !matches!(byte, b'\n' | b'\r') {
+						*byte = b' ';
+					}
+				}
+
+				index = end;
+				continue;
+			}
+			b',' => last_comma = Some(index),
+			b'}' | b']' => {
+				if let Some(comma) = last_comma.take() {
+					output[comma] = b' ';
+				}
+			}
+			byte if byte.is_ascii_whitespace() => {}
+			_ => last_comma = None,
+		}
+
+		index += 1;
+	}
+
+	// Only whole comments are replaced, so the output is still valid UTF-8
+	String::from_utf8(output).unwrap()
+}
+
+/// Returns `source` updated to represent `value`, keeping comments,
+/// formatting and order of keys of all values that did not change
+pub fn update<T: Serialize>(source: &str, value: &T) -> Result<String> {
+	let stripped = strip(source);
+
+	let old: Value = serde_json::from_str(&stripped)?;
+	let new = serde_json::to_value(value)?;
+
+	let node = Parser {
+		bytes: stripped.as_bytes(),
+		index: 0,
+	}
+	.parse()?;
+
+	let document = Document {
+		source,
+		indent: detect_indent(source),
+	};
+
+	let mut edits = vec![];
+	document.patch(&node, &old, &new, &mut edits)?;
+
+	edits.sort_by_key(|edit| Reverse(edit.0));
+
+	let mut output = source.to_owned();
+
+	for (start, end, text) in edits {
+		output.replace_range(start..end, &text);
+	}
+
+	Ok(output)
+}
+
+/// Returns byte offset of the value at `path` (object keys and array
+/// indices separated by `/`), or of its closest existing ancestor
+pub fn locate(source: &str, path: &str) -> Result<usize> {
+	let stripped = strip(source);
+
+	let root = Parser {
+		bytes: stripped.as_bytes(),
+		index: 0,
+	}
+	.parse()?;
+
+	let mut node = &root;
+	let mut position = node.span().0;
+
+	for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+		let (start, child) = match node {
+			Node::Object(members, _, _) => match members.iter().find(|member| member.key == segment) {
+				Some(member) => (member.start, &member.value),
+				None => break,
+			},
+			Node::Array(items, _, _) => match segment.parse::<usize>().ok().and_then(|index| items.get(index)) {
+				Some(item) => (item.span().0, item),
+				None => break,
+			},
+			Node::Scalar(_, _) => break,
+		};
+
+		position = start;
+		node = child;
+	}
+
+	Ok(position)
+}
+
+enum Node {
+	Object(Vec<Member>, usize, usize),
+	Array(Vec<Node>, usize, usize),
+	Scalar(usize, usize),
+}
+
+impl Node {
+	fn span(&self) -> (usize, usize) {
+		match self {
+			Node::Object(_, start, end) | Node::Array(_, start, end) | Node::Scalar(start, end) => (*start, *end),
+		}
+	}
+}
+
+struct Member {
+	key: String,
+	start: usize,
+	value: Node,
+}
+
+struct Parser<'a> {
+	bytes: &'a [u8],
+	index: usize,
+}
+
+impl<'a> Parser<'a> {
+	fn parse(&mut self) -> Result<Node> {
+		self.skip_whitespace();
+
+		let start = self.index;
+
+		match self.bytes.get(start) {
+			Some(b'{') => {
+				self.index += 1;
+
+				let mut members = vec![];
+
+				loop {
+					self.skip_whitespace();
+
+					match self.bytes.get(self.index) {
+						Some(b'}') => break,
+						Some(b',') => self.index += 1,
+						Some(b'"') => {
+							let key_start = self.index;
+							let key_end = self.skip_string();
+							let key = serde_json::from_slice(&self.bytes[key_start..key_end])?;
+
+							self.skip_whitespace();
+
+							if self.bytes.get(self.index) != Some(&b':') {
+								bail!("Expected `:` at position {}", self.index);
+							}
+
+							self.index += 1;
+
+							members.push(Member {
+								key,
+								start: key_start,
+								value: self.parse()?,
+							});
+						}
+						_ => bail!("Unexpected token at position {}", self.index),
+					}
+				}
+
+				self.index += 1;
+
+				Ok(Node::Object(members, start, self.index))
+			}
+			Some(b'[') => {
+				self.index += 1;
+
+				let mut items = vec![];
+
+				loop {
+					self.skip_whitespace();
+
+					match self.bytes.get(self.index) {
+						Some(b']') => break,
+						Some(b',') => self.index += 1,
+						Some(_) => items.push(self.parse()?),
+						None => bail!("Unexpected end of input"),
+					}
+				}
+
+				self.index += 1;
+
+				Ok(Node::Array(items, start, self.index))
+			}
+			Some(b'"') => Ok(Node::Scalar(start, self.skip_string())),
+			Some(_) => {
+				while self
+					.bytes
+					.get(self.index)
+					.is_some_and(|byte| !matches!(byte, b',' | b'}' | b']') && !byte.is_ascii_whitespace())
+				{
+					self.index += 1;
+				}
+
+				Ok(Node::Scalar(start, self.index))
+			}
+			None => bail!("Unexpected end of input"),
+		}
+	}
+
+	fn skip_whitespace(&mut self) {
+		while self
+			.bytes
+			.get(self.index)
+			.is_some_and(|byte| byte.is_ascii_whitespace())
+		{
+			self.index += 1;
+		}
+	}
+
+	fn skip_string(&mut self) -> usize {
+		self.index += 1;
+
+		while let Some(byte) = self.bytes.get(self.index) {
+			match byte {
+				b'\\' => self.index += 2,
+				b'"' => break,
+				_ => self.index += 1,
+			}
+		}
+
+		self.index += 1;
+		self.index
+	}
+}
+
+struct Document<'a> {
+	source: &'a str,
+	indent: String,
+}
+
+impl<'a> Document<'a> {
+	fn patch(&self, node: &Node, old: &Value, new: &Value, edits: &mut Vec<(usize, usize, String)>) -> Result<()> {
+		if old == new {
+			return Ok(());
+		}
+
+		match (node, old, new) {
+			(Node::Object(members, start, _), Value::Object(old), Value::Object(new))
+				if members.iter().any(|member| new.contains_key(&member.key)) =>
+			{
+				let last_kept = members
+					.iter()
+					.rposition(|member| new.contains_key(&member.key))
+					.unwrap();
+
+				for (index, member) in members.iter().enumerate() {
+					if let Some(value) = new.get(&member.key) {
+						self.patch(
+							&member.value,
+							old.get(&member.key).unwrap_or(&Value::Null),
+							value,
+							edits,
+						)?;
+					} else if index < last_kept {
+						edits.push((member.start, members[index + 1].start, String::new()));
+					}
+				}
+
+				let first = &members[0];
+
+				let separator = if self.source[*start..first.start].contains('\n') {
+					format!(",\n{}", self.line_indent(first.start))
+				} else {
+					String::from(", ")
+				};
+
+				let mut text = String::new();
+
+				for (key, value) in new {
+					if members.iter().any(|member| &member.key == key) {
+						continue;
+					}
+
+					text.push_str(&separator);
+					text.push_str(&serde_json::to_string(key)?);
+					text.push_str(": ");
+					text.push_str(&self.render(value, &self.line_indent(first.start))?);
+				}
+
+				let end = members[last_kept].value.span().1;
+				let last = members.last().unwrap().value.span().1;
+
+				if !text.is_empty() || end != last {
+					edits.push((end, last, text));
+				}
+			}
+			(Node::Array(items, ..), Value::Array(old), Value::Array(new))
+				if items.len() == old.len() && old.len() == new.len() =>
+			{
+				for (index, item) in items.iter().enumerate() {
+					self.patch(item, &old[index], &new[index], edits)?;
+				}
+			}
+			_ => {
+				let (start, end) = node.span();
+				edits.push((start, end, self.render(new, &self.line_indent(start))?));
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Serializes the value, indenting it to match the rest of the document
+	fn render(&self, value: &Value, indent: &str) -> Result<String> {
+		let mut writer = Vec::new();
+		let mut serializer = Serializer::with_formatter(&mut writer, JsonFormatter::with_array_breaks(false));
+
+		value.serialize(&mut serializer)?;
+
+		let rendered = String::from_utf8(writer)?;
+
+		let lines = rendered.lines().enumerate().map(|(index, line)| {
+			if index == 0 {
+				return line.to_owned();
+			}
+
+			let trimmed = line.trim_start_matches(' ');
+			let depth = (line.len() - trimmed.len()) / 2;
+
+			format!("{}{}{}", indent, self.indent.repeat(depth), trimmed)
+		});
+
+		Ok(lines.collect::<Vec<_>>().join("\n"))
+	}
+
+	/// Returns the leading whitespace of the line containing `position`
+	fn line_indent(&self, position: usize) -> String {
+		let line_start = self.source[..position].rfind('\n').map_or(0, |index| index + 1);
+
+		self.source[line_start..position]
+			.chars()
+			.take_while(|char| char.is_whitespace())
+			.collect()
+	}
+}
+
+fn detect_indent(source: &str) -> String {
+	source
+		.lines()
+		.map(|line| {
+			line.chars()
+				.take_while(|char| *char == ' ' || *char == '\t')
+				.collect::<String>()
+		})
+		.find(|indent| !indent.is_empty())
+		.map_or(String::from("  "), |indent| {
+			if indent.starts_with('\t') {
+				String::from("\t")
+			} else {
+				indent
+			}
+		})
+}
@@ -8,10 +8,13 @@ pub mod config;
 pub mod constants;
 pub mod core;
 pub mod crash_handler;
+pub mod diagnostics;
 pub mod ext;
 pub mod glob;
+pub mod history;
 pub mod installer;
 pub mod integration;
+pub mod jsonc;
 pub mod logger;
 pub mod middleware;
 pub mod program;
@@ -2,10 +2,14 @@ use colored::{Color, Colorize};
 use dialoguer::console::{style, Style, StyledObject};
 use dialoguer::theme::Theme;
 use dialoguer::Confirm;
-use env_logger::{Builder, WriteStyle};
+use env_logger::{Builder, Target, WriteStyle};
 use log::{Level, LevelFilter};
+use serde_json::{json, Value};
 use std::fmt::{Display, Formatter};
-use std::{fmt, io::Write};
+use std::{
+	fmt,
+	io::{self, BufRead, Write},
+};
 
 use crate::util;
 
@@ -27,12 +31,28 @@ macro_rules! argon_info {
 
 pub fn init(verbosity: LevelFilter, log_style: WriteStyle) {
 	let mut builder = Builder::new();
+	let output_json = util::env_output_json();
 
 	builder.format(move |buffer, record| {
 		if record.level() > verbosity && record.target() != "argon_log" {
 			return Ok(());
 		}
 
+		if output_json {
+			let mut event = json!({
+				"type": "log",
+				"level": record.level().as_str().to_lowercase(),
+				"message": record.args().to_string(),
+			});
+
+			if record.target() != "argon_log" {
+				event["module"] = json!(record.module_path());
+				event["line"] = json!(record.line());
+			}
+
+			return writeln!(buffer, "{}", event);
+		}
+
 		let color = match record.level() {
 			Level::Error => Color::Red,
 			Level::Warn => Color::Yellow,
@@ -70,6 +90,11 @@ pub fn init(verbosity: LevelFilter, log_style: WriteStyle) {
 
 	builder.write_style(log_style);
 
+	// Keep all events in a single stream so they can be parsed in order
+	if output_json {
+		builder.target(Target::Stdout);
+	}
+
 	// We want to see only important logs from these crates
 	builder.filter_module("notify_debouncer_full", LevelFilter::Warn);
 	builder.filter_module("notify", LevelFilter::Warn);
@@ -86,7 +111,29 @@ pub fn init(verbosity: LevelFilter, log_style: WriteStyle) {
 	builder.init();
 }
 
+/// Emits a result event when `--output-format json` is used, does nothing otherwise
+pub fn emit(event: &str, data: Value) {
+	if !util::env_output_json() {
+		return;
+	}
+
+	let mut object = json!({ "type": event });
+
+	if let (Some(object), Value::Object(data)) = (object.as_object_mut(), data) {
+		object.extend(data);
+	}
+
+	let mut stdout = io::stdout().lock();
+
+	writeln!(stdout, "{}", object).ok();
+	stdout.flush().ok();
+}
+
 pub fn prompt(prompt: &str, default: bool) -> bool {
+	if util::env_output_json() {
+		return json_prompt(prompt, default);
+	}
+
 	if util::env_yes() {
 		return default;
 	}
@@ -104,6 +151,40 @@ pub fn prompt(prompt: &str, default: bool) -> bool {
 	result.unwrap_or(default)
 }
 
+/// Emits the prompt as an event and reads the answer from a single line of stdin,
+/// which can be `y`, `n`, `true`, `false` or `{"answer": bool}`. Closed stdin
+/// or invalid answer declines the prompt, `--yes` always picks the default
+fn json_prompt(prompt: &str, default: bool) -> bool {
+	emit("prompt", json!({ "message": prompt, "default": default }));
+
+	let answer = if util::env_yes() {
+		default
+	} else {
+		let mut line = String::new();
+
+		match io::stdin().lock().read_line(&mut line) {
+			Ok(read) if read > 0 => parse_answer(&line).unwrap_or(false),
+			_ => false,
+		}
+	};
+
+	emit("answer", json!({ "message": prompt, "answer": answer }));
+
+	answer
+}
+
+fn parse_answer(line: &str) -> Option<bool> {
+	let line = line.trim();
+
+	match line.to_lowercase().as_str() {
+		"y" | "yes" | "true" => Some(true),
+		"n" | "no" | "false" => Some(false),
+		_ => serde_json::from_str::<Value>(line)
+			.ok()
+			.and_then(|value| value.get("answer").and_then(Value::as_bool)),
+	}
+}
+
 pub struct Table {
 	rows: Vec<Vec<String>>,
 	columns: Vec<usize>,
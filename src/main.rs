@@ -1,6 +1,7 @@
 use env_logger::WriteStyle;
 use log::{debug, error, info, warn, LevelFilter};
 use puffin_http::Server;
+use serde_json::json;
 use std::{
 	env,
 	io::{self, IsTerminal},
@@ -29,8 +30,12 @@ fn main() -> ExitCode {
 	let verbosity = LevelFilter::Trace;// cli.verbosity();
 	let log_style = cli.log_style();
 	let use_virtual = cli.use_virtual();
+	let output_json = cli.output_json();
 
-	if log_style == WriteStyle::Auto && io::stdin().is_terminal() {
+	if output_json {
+		env::set_var("RUST_LOG_STYLE", "never");
+		colored::control::set_override(false);
+	} else if log_style == WriteStyle::Auto && io::stdin().is_terminal() {
 		env::set_var("RUST_LOG_STYLE", "always");
 	} else {
 		env::set_var(
@@ -46,6 +51,7 @@ fn main() -> ExitCode {
 	env::set_var("RUST_YES", if yes { "1" } else { "0" });
 	env::set_var("RUST_BACKTRACE", if backtrace { "1" } else { "0" });
 	env::set_var("RUST_USE_VIRTUAL_FS", if use_virtual { "1" } else { "0" });
+	env::set_var("RUST_OUTPUT_JSON", if output_json { "1" } else { "0" });
 
 	logger::init(verbosity, log_style);
 
@@ -75,7 +81,7 @@ fn main() -> ExitCode {
 		}
 	});
 
-	if cfg!(debug_assertions) && cli.profile() {
+	if cfg!(debug_assertions) && cli.profiler() {
 		match Server::new(PROFILER_ADDRESS) {
 			Ok(server) => {
 				let _ = ManuallyDrop::new(server);
@@ -93,10 +99,14 @@ fn main() -> ExitCode {
 	let exit_code = match cli.main() {
 		Ok(()) => {
 			debug!("Successfully executed command!");
+			logger::emit("exit", json!({ "success": true }));
+
 			ExitCode::SUCCESS
 		}
 		Err(err) => {
 			argon_error!("{}", err);
+			logger::emit("exit", json!({ "success": false, "error": err.to_string() }));
+
 			ExitCode::FAILURE
 		}
 	};
@@ -1,6 +1,5 @@
 use anyhow::Result;
 use json_formatter::JsonFormatter;
-use log::error;
 use rbx_dom_weak::types::Tags;
 use serde::{Deserialize, Serialize};
 use serde_json::Serializer;
@@ -11,6 +10,7 @@ use std::{
 
 use crate::{
 	core::meta::Meta,
+	diagnostics::{self, Diagnostic},
 	ext::{PathExt, WriterExt},
 	middleware::helpers,
 	resolution::UnresolvedValue,
@@ -34,6 +34,8 @@ struct Data {
 	keep_unknowns: Option<bool>,
 	#[serde(default)]
 	original_name: Option<String>,
+	#[serde(default)]
+	id: Option<String>,
 }
 
 #[derive(Debug, Default)]
@@ -44,6 +46,8 @@ pub struct DataSnapshot {
 	pub keep_unknowns: Option<bool>,
 	pub original_name: Option<String>,
 	pub mesh_source: Option<String>,
+	pub refs: HashMap<String, String>,
+	pub id: Option<String>,
 }
 
 #[profiling::function]
@@ -57,6 +61,7 @@ pub fn read_data(path: &Path, class: Option<&str>, vfs: &Vfs) -> Result<DataSnap
 	let data: Data = serde_json::from_str(&data)?;
 
 	let mut properties = HashMap::new();
+	let mut refs = HashMap::new();
 
 	let class = if let Some(class) = class.or(data.class_name.as_deref()) {
 		class.to_owned()
@@ -78,12 +83,20 @@ pub fn read_data(path: &Path, class: Option<&str>, vfs: &Vfs) -> Result<DataSnap
 
 	// Resolve properties
 	for (property, value) in data.properties {
+		if let Some(path) = value.as_ref_path(&class, &property) {
+			refs.insert(property, path.to_owned());
+			continue;
+		}
+
 		match value.resolve(&class, &property) {
 			Ok(value) => {
 				properties.insert(property, value);
 			}
 			Err(err) => {
-				error!("Failed to parse property: {} at {}", err, path.display());
+				diagnostics::report(
+					Diagnostic::error(&format!("Failed to parse property: {}", err), path)
+						.with_json_path(&format!("properties/{}", property)),
+				);
 			}
 		}
 	}
@@ -95,7 +108,10 @@ pub fn read_data(path: &Path, class: Option<&str>, vfs: &Vfs) -> Result<DataSnap
 				properties.insert(String::from("Attributes"), value);
 			}
 			Err(err) => {
-				error!("Failed to parse attributes: {} at {}", err, path.display());
+				diagnostics::report(
+					Diagnostic::error(&format!("Failed to parse attributes: {}", err), path)
+						.with_json_path("attributes"),
+				);
 			}
 		}
 	}
@@ -118,6 +134,8 @@ pub fn read_data(path: &Path, class: Option<&str>, vfs: &Vfs) -> Result<DataSnap
 		keep_unknowns: data.keep_unknowns,
 		original_name: data.original_name,
 		mesh_source,
+		refs,
+		id: data.id,
 	})
 }
 
@@ -133,6 +151,8 @@ struct WritableData {
 	pub keep_unknowns: Option<bool>,
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub original_name: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub id: Option<String>,
 }
 
 #[profiling::function]
@@ -174,6 +194,10 @@ pub fn write_data<'a>(
 		data.original_name = Some(original_name.to_owned());
 	}
 
+	if let Some(id) = meta.id.as_ref() {
+		data.id = Some(id.to_owned());
+	}
+
 	if data == WritableData::default() {
 		if vfs.exists(path) {
 			vfs.remove(path)?;
@@ -215,6 +239,7 @@ pub fn write_original_name(path: &Path, meta: &Meta, vfs: &Vfs) -> Result<()> {
 			properties: data.properties.into_iter().collect(),
 			keep_unknowns: data.keep_unknowns,
 			original_name: meta.original_name.clone(),
+			id: data.id,
 		};
 
 		if data == WritableData::default() {
@@ -0,0 +1,482 @@
+use anyhow::{bail, Result};
+use std::{iter::Peekable, str::Chars};
+
+/// Literal Luau value that can be converted back to data formats
+#[derive(Debug, Clone, PartialEq)]
+pub enum LuaValue {
+	Nil,
+	Bool(bool),
+	Integer(i64),
+	Number(f64),
+	String(String),
+	Table(Vec<(LuaValue, LuaValue)>),
+}
+
+impl LuaValue {
+	/// Returns table values if its keys form a `1..n` sequence, empty
+	/// tables are arrays only when `empty_array` is set (like when the
+	/// value they replace was an array) and objects otherwise
+	pub fn as_array(&self, empty_array: bool) -> Option<Vec<&LuaValue>> {
+		if let LuaValue::Table(entries) = self {
+			if entries.is_empty() && !empty_array {
+				return None;
+			}
+
+			let mut array = Vec::with_capacity(entries.len());
+
+			for (index, (key, value)) in entries.iter().enumerate() {
+				if *key != LuaValue::Integer(index as i64 + 1) {
+					return None;
+				}
+
+				array.push(value);
+			}
+
+			Some(array)
+		} else {
+			None
+		}
+	}
+
+	pub fn describe(&self) -> &'static str {
+		match self {
+			LuaValue::Nil => "nil",
+			LuaValue::Bool(_) => "a boolean",
+			LuaValue::Integer(_) | LuaValue::Number(_) => "a number",
+			LuaValue::String(_) => "a string",
+			LuaValue::Table(_) => "a table",
+		}
+	}
+}
+
+/// Parses Luau source in the form of `return <literal>`,
+/// only literal values (no expressions or calls) are supported
+pub fn parse(source: &str) -> Result<LuaValue> {
+	let mut parser = Parser {
+		chars: source.chars().peekable(),
+		line: 1,
+	};
+
+	parser.skip_trivia()?;
+
+	if parser.read_name().as_deref() != Some("return") {
+		parser.error("expected `return` statement")?;
+	}
+
+	let value = parser.parse_value()?;
+
+	parser.skip_trivia()?;
+
+	if parser.chars.peek() == Some(&';') {
+		parser.next();
+		parser.skip_trivia()?;
+	}
+
+	if let Some(char) = parser.chars.peek().copied() {
+		parser.error(&format!("unexpected `{}` after returned value", char))?;
+	}
+
+	Ok(value)
+}
+
+struct Parser<'a> {
+	chars: Peekable<Chars<'a>>,
+	line: usize,
+}
+
+impl<'a> Parser<'a> {
+	fn error<T>(&self, message: &str) -> Result<T> {
+		bail!("{} at line {}", message, self.line)
+	}
+
+	fn next(&mut self) -> Option<char> {
+		let char = self.chars.next();
+
+		if char == Some('\n') {
+			self.line += 1;
+		}
+
+		char
+	}
+
+	fn expect(&mut self, expected: char) -> Result<()> {
+		self.skip_trivia()?;
+
+		match self.next() {
+			Some(char) if char == expected => Ok(()),
+			Some(char) => self.error(&format!("expected `{}` but got `{}`", expected, char)),
+			None => self.error(&format!("expected `{}` but got end of file", expected)),
+		}
+	}
+
+	fn skip_trivia(&mut self) -> Result<()> {
+		loop {
+			match self.chars.peek() {
+				Some(char) if char.is_whitespace() => {
+					self.next();
+				}
+				Some('-') => {
+					let mut lookahead = self.chars.clone();
+					lookahead.next();
+
+					if lookahead.peek() != Some(&'-') {
+						return Ok(());
+					}
+
+					self.next();
+					self.next();
+
+					if self.chars.peek() == Some(&'[') {
+						if let Some(level) = self.long_bracket_level() {
+							self.read_long_string(level)?;
+							continue;
+						}
+					}
+
+					while let Some(char) = self.chars.peek() {
+						if *char == '\n' {
+							break;
+						}
+
+						self.next();
+					}
+				}
+				_ => return Ok(()),
+			}
+		}
+	}
+
+	fn read_name(&mut self) -> Option<String> {
+		let mut name = String::new();
+
+		while let Some(char) = self.chars.peek() {
+			if char.is_alphanumeric() || *char == '_' {
+				name.push(*char);
+				self.next();
+			} else {
+				break;
+			}
+		}
+
+		if name.is_empty() {
+			None
+		} else {
+			Some(name)
+		}
+	}
+
+	fn parse_value(&mut self) -> Result<LuaValue> {
+		self.skip_trivia()?;
+
+		match self.chars.peek().copied() {
+			Some('{') => self.parse_table(),
+			Some('"') | Some('\'') => Ok(LuaValue::String(self.parse_string()?)),
+			Some('[') => {
+				if let Some(level) = self.long_bracket_level() {
+					Ok(LuaValue::String(self.read_long_string(level)?))
+				} else {
+					self.error("unexpected `[`")
+				}
+			}
+			Some('-') => {
+				self.next();
+				self.skip_trivia()?;
+
+				if self
+					.chars
+					.peek()
+					.is_some_and(|char| char.is_ascii_digit() || *char == '.')
+				{
+					return self.parse_number(true);
+				}
+
+				match self.parse_value()? {
+					LuaValue::Integer(integer) => Ok(LuaValue::Integer(-integer)),
+					LuaValue::Number(number) => Ok(LuaValue::Number(-number)),
+					value => self.error(&format!("cannot negate {}", value.describe())),
+				}
+			}
+			Some(char) if char.is_ascii_digit() || char == '.' => self.parse_number(false),
+			Some(char) if char.is_alphabetic() || char == '_' => {
+				let name = self.read_name().unwrap_or_default();
+
+				match name.as_str() {
+					"nil" => Ok(LuaValue::Nil),
+					"true" => Ok(LuaValue::Bool(true)),
+					"false" => Ok(LuaValue::Bool(false)),
+					"math" if self.chars.peek() == Some(&'.') => {
+						self.next();
+
+						match self.read_name().as_deref() {
+							Some("huge") => Ok(LuaValue::Number(f64::INFINITY)),
+							Some("pi") => Ok(LuaValue::Number(std::f64::consts::PI)),
+							_ => self.error("only `math.huge` and `math.pi` constants are supported"),
+						}
+					}
+					_ => self.error(&format!(
+						"`{}` is not a literal value, only tables, strings, numbers, booleans and nil are supported",
+						name
+					)),
+				}
+			}
+			Some(char) => self.error(&format!("unexpected `{}`", char)),
+			None => self.error("unexpected end of file"),
+		}
+	}
+
+	fn parse_table(&mut self) -> Result<LuaValue> {
+		self.expect('{')?;
+
+		let mut entries = vec![];
+		let mut index = 1;
+
+		loop {
+			self.skip_trivia()?;
+
+			match self.chars.peek().copied() {
+				Some('}') => {
+					self.next();
+					break;
+				}
+				Some('[') if self.long_bracket_level().is_none() => {
+					self.next();
+
+					let key = self.parse_value()?;
+
+					self.expect(']')?;
+					self.expect('=')?;
+
+					if key == LuaValue::Nil {
+						self.error("table key cannot be nil")?;
+					}
+
+					let value = self.parse_value()?;
+
+					entries.retain(|(existing, _)| *existing != key);
+					entries.push((key, value));
+				}
+				Some(char) if char.is_alphabetic() || char == '_' => {
+					let mut lookahead = self.chars.clone();
+					let mut is_field = false;
+
+					while let Some(char) = lookahead.next() {
+						if char.is_alphanumeric() || char == '_' {
+							continue;
+						}
+
+						let mut char = char;
+
+						while char.is_whitespace() {
+							char = lookahead.next().unwrap_or_default();
+						}
+
+						is_field = char == '=' && lookahead.peek() != Some(&'=');
+						break;
+					}
+
+					if is_field {
+						let key = LuaValue::String(self.read_name().unwrap_or_default());
+
+						self.expect('=')?;
+
+						let value = self.parse_value()?;
+
+						entries.retain(|(existing, _)| *existing != key);
+						entries.push((key, value));
+					} else {
+						entries.push((LuaValue::Integer(index), self.parse_value()?));
+						index += 1;
+					}
+				}
+				Some(_) => {
+					entries.push((LuaValue::Integer(index), self.parse_value()?));
+					index += 1;
+				}
+				None => self.error("unclosed table")?,
+			}
+
+			self.skip_trivia()?;
+
+			match self.chars.peek().copied() {
+				Some(',') | Some(';') => {
+					self.next();
+				}
+				Some('}') => continue,
+				Some(char) => self.error(&format!("expected `,` or `}}` but got `{}`", char))?,
+				None => self.error("unclosed table")?,
+			}
+		}
+
+		Ok(LuaValue::Table(entries))
+	}
+
+	/// Sign is parsed together with the digits so `-9223372036854775808` stays an integer
+	fn parse_number(&mut self, negative: bool) -> Result<LuaValue> {
+		let mut literal = String::new();
+
+		while let Some(char) = self.chars.peek().copied() {
+			let is_exponent_sign =
+				(char == '+' || char == '-') && literal.ends_with(['e', 'E']) && !literal.starts_with("0x");
+
+			if char.is_ascii_alphanumeric() || char == '.' || char == '_' || is_exponent_sign {
+				if char != '_' {
+					literal.push(char);
+				}
+
+				self.next();
+			} else {
+				break;
+			}
+		}
+
+		let lowercase = literal.to_lowercase();
+		let sign = if negative { "-" } else { "" };
+
+		let value = if let Some(hex) = lowercase.strip_prefix("0x") {
+			i64::from_str_radix(&format!("{}{}", sign, hex), 16)
+				.ok()
+				.map(LuaValue::Integer)
+		} else if let Some(binary) = lowercase.strip_prefix("0b") {
+			i64::from_str_radix(&format!("{}{}", sign, binary), 2)
+				.ok()
+				.map(LuaValue::Integer)
+		} else if let Ok(integer) = format!("{}{}", sign, literal).parse::<i64>() {
+			Some(LuaValue::Integer(integer))
+		} else {
+			format!("{}{}", sign, literal).parse::<f64>().ok().map(LuaValue::Number)
+		};
+
+		match value {
+			Some(value) => Ok(value),
+			None => self.error(&format!("malformed number `{}{}`", sign, literal)),
+		}
+	}
+
+	/// Byte escapes (`\ddd` and `\xXX`) are collected as raw bytes
+	/// and the whole string is decoded as UTF-8 at the end
+	fn parse_string(&mut self) -> Result<String> {
+		let quote = self.next().unwrap_or_default();
+		let mut bytes = Vec::new();
+
+		loop {
+			match self.next() {
+				Some(char) if char == quote => break,
+				Some('\n') | None => self.error("unfinished string")?,
+				Some('\\') => match self.next() {
+					Some('n') => push_char(&mut bytes, '\n'),
+					Some('t') => push_char(&mut bytes, '\t'),
+					Some('r') => push_char(&mut bytes, '\r'),
+					Some('a') => push_char(&mut bytes, '\u{7}'),
+					Some('b') => push_char(&mut bytes, '\u{8}'),
+					Some('f') => push_char(&mut bytes, '\u{c}'),
+					Some('v') => push_char(&mut bytes, '\u{b}'),
+					Some('\n') => push_char(&mut bytes, '\n'),
+					Some('z') => {
+						while self.chars.peek().is_some_and(|char| char.is_whitespace()) {
+							self.next();
+						}
+					}
+					Some('x') => {
+						let hex: String = [self.next(), self.next()].iter().flatten().collect();
+
+						match u8::from_str_radix(&hex, 16) {
+							Ok(byte) => bytes.push(byte),
+							Err(_) => self.error("invalid hexadecimal escape sequence")?,
+						}
+					}
+					Some('u') => {
+						let mut hex = String::new();
+
+						if self.next() != Some('{') {
+							self.error("missing `{` in unicode escape sequence")?;
+						}
+
+						loop {
+							match self.next() {
+								Some('}') => break,
+								Some(char) if char.is_ascii_hexdigit() => hex.push(char),
+								_ => self.error("invalid unicode escape sequence")?,
+							}
+						}
+
+						match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+							Some(char) => push_char(&mut bytes, char),
+							None => self.error("invalid unicode escape sequence")?,
+						}
+					}
+					Some(char) if char.is_ascii_digit() => {
+						let mut decimal = char.to_string();
+
+						while decimal.len() < 3 && self.chars.peek().is_some_and(|char| char.is_ascii_digit()) {
+							decimal.push(self.next().unwrap_or_default());
+						}
+
+						match decimal.parse::<u8>() {
+							Ok(byte) => bytes.push(byte),
+							Err(_) => self.error("invalid decimal escape sequence")?,
+						}
+					}
+					Some(char) => push_char(&mut bytes, char),
+					None => self.error("unfinished string")?,
+				},
+				Some(char) => push_char(&mut bytes, char),
+			}
+		}
+
+		match String::from_utf8(bytes) {
+			Ok(string) => Ok(string),
+			Err(_) => self.error("invalid UTF-8 sequence in string"),
+		}
+	}
+
+	/// Returns level of the long bracket starting at the current position, e.g. `[==[` is 2
+	fn long_bracket_level(&self) -> Option<usize> {
+		let mut lookahead = self.chars.clone();
+
+		if lookahead.next() != Some('[') {
+			return None;
+		}
+
+		let mut level = 0;
+
+		loop {
+			match lookahead.next() {
+				Some('=') => level += 1,
+				Some('[') => return Some(level),
+				_ => return None,
+			}
+		}
+	}
+
+	fn read_long_string(&mut self, level: usize) -> Result<String> {
+		for _ in 0..level + 2 {
+			self.next();
+		}
+
+		// First newline of the long string is skipped
+		if self.chars.peek() == Some(&'\n') {
+			self.next();
+		}
+
+		let closing = format!("]{}]", "=".repeat(level));
+		let mut string = String::new();
+
+		loop {
+			match self.next() {
+				Some(char) => {
+					string.push(char);
+
+					if string.ends_with(&closing) {
+						string.truncate(string.len() - closing.len());
+						return Ok(string);
+					}
+				}
+				None => self.error("unfinished long string")?,
+			}
+		}
+	}
+}
+
+fn push_char(bytes: &mut Vec<u8>, char: char) {
+	bytes.extend_from_slice(char.encode_utf8(&mut [0; 4]).as_bytes());
+}
@@ -1,8 +1,13 @@
+use anyhow::Result;
 use rbx_dom_weak::{types::Ref, WeakDom};
 
 use crate::{core::snapshot::Snapshot, Properties};
 
+pub use self::{lua::LuaValue, ordered::Ordered};
+
+mod lua;
 mod mesh_part;
+mod ordered;
 mod snapshot;
 
 #[inline]
@@ -14,3 +19,8 @@ pub fn save_mesh(properties: &Properties) -> Option<String> {
 pub fn snapshot_from_dom(dom: WeakDom, id: Ref) -> Snapshot {
 	snapshot::snapshot_from_dom(dom, id)
 }
+
+#[inline]
+pub fn parse_lua(source: &str) -> Result<LuaValue> {
+	lua::parse(source)
+}
@@ -0,0 +1,27 @@
+use serde::{ser::SerializeMap, Serialize, Serializer};
+
+/// Data value that keeps the order of its map entries when
+/// serialized, used to write tables in the same order as in Luau
+pub enum Ordered<T> {
+	Value(T),
+	Array(Vec<Ordered<T>>),
+	Map(Vec<(String, Ordered<T>)>),
+}
+
+impl<T: Serialize> Serialize for Ordered<T> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		match self {
+			Ordered::Value(value) => value.serialize(serializer),
+			Ordered::Array(array) => array.serialize(serializer),
+			Ordered::Map(entries) => {
+				let mut map = serializer.serialize_map(Some(entries.len()))?;
+
+				for (key, value) in entries {
+					map.serialize_entry(key, value)?;
+				}
+
+				map.end()
+			}
+		}
+	}
+}
@@ -1,8 +1,12 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
+use json_formatter::JsonFormatter;
 use rbx_dom_weak::types::Variant;
+use serde::Serialize;
+use serde_json::{Number, Serializer, Value};
 use std::{collections::HashMap, path::Path};
 
-use crate::{core::snapshot::Snapshot, vfs::Vfs};
+use super::helpers::{self, LuaValue, Ordered};
+use crate::{core::snapshot::Snapshot, ext::WriterExt, vfs::Vfs, Properties};
 
 #[profiling::function]
 pub fn read_json(path: &Path, vfs: &Vfs) -> Result<Snapshot> {
@@ -21,3 +25,76 @@ pub fn read_json(path: &Path, vfs: &Vfs) -> Result<Snapshot> {
 
 	Ok(Snapshot::new().with_class("ModuleScript").with_properties(properties))
 }
+
+#[profiling::function]
+pub fn write_json(mut properties: Properties, path: &Path, vfs: &Vfs) -> Result<Properties> {
+	if let Some(Variant::String(source)) = properties.remove("Source") {
+		if source.trim().is_empty() {
+			vfs.write(path, &[])?;
+			return Ok(properties);
+		}
+
+		// Existing file tells whether empty tables are arrays or objects
+		let existing = vfs
+			.read(path)
+			.ok()
+			.and_then(|json| serde_json::from_slice::<Value>(&json).ok());
+
+		let json = match helpers::parse_lua(&source)? {
+			value @ LuaValue::Table(_) => lua_to_json(&value, existing.as_ref())?,
+			value => bail!("JSON module has to return a table, got {}", value.describe()),
+		};
+
+		let mut writer = Vec::new();
+		let mut serializer = Serializer::with_formatter(&mut writer, JsonFormatter::new());
+
+		json.serialize(&mut serializer)?;
+		writer.end()?;
+
+		vfs.write(path, &writer)?;
+	}
+
+	Ok(properties)
+}
+
+fn lua_to_json(value: &LuaValue, existing: Option<&Value>) -> Result<Ordered<Value>> {
+	Ok(match value {
+		LuaValue::Nil => Ordered::Value(Value::Null),
+		LuaValue::Bool(bool) => Ordered::Value(Value::Bool(*bool)),
+		LuaValue::Integer(integer) => Ordered::Value(Value::Number(Number::from(*integer))),
+		LuaValue::Number(number) => match Number::from_f64(*number) {
+			Some(number) => Ordered::Value(Value::Number(number)),
+			None => bail!("JSON does not support {} number", number),
+		},
+		LuaValue::String(string) => Ordered::Value(Value::String(string.to_owned())),
+		LuaValue::Table(entries) => {
+			if let Some(array) = value.as_array(existing.is_some_and(Value::is_array)) {
+				Ordered::Array(
+					array
+						.into_iter()
+						.enumerate()
+						.map(|(index, value)| lua_to_json(value, existing.and_then(|existing| existing.get(index))))
+						.collect::<Result<_>>()?,
+				)
+			} else {
+				lua_to_json_object(entries, existing)?
+			}
+		}
+	})
+}
+
+fn lua_to_json_object(entries: &[(LuaValue, LuaValue)], existing: Option<&Value>) -> Result<Ordered<Value>> {
+	let mut object = Vec::with_capacity(entries.len());
+
+	for (key, value) in entries {
+		match key {
+			LuaValue::String(key) => {
+				let existing = existing.and_then(|existing| existing.get(key));
+				object.push((key.to_owned(), lua_to_json(value, existing)?));
+			}
+			key => bail!("JSON object keys have to be strings, got {}", key.describe()),
+		}
+	}
+
+	Ok(Ordered::Map(object))
+}
@@ -1,11 +1,19 @@
 use anyhow::Result;
+use json_formatter::JsonFormatter;
 use log::error;
-use rbx_dom_weak::types::Tags;
-use serde::Deserialize;
-use std::{collections::HashMap, path::Path};
+use rbx_dom_weak::{
+	types::{Ref, Tags, Variant},
+	WeakDom,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Serializer;
+use std::{
+	collections::{BTreeMap, HashMap},
+	path::Path,
+};
 
 use super::helpers;
-use crate::{core::snapshot::Snapshot, resolution::UnresolvedValue, vfs::Vfs};
+use crate::{core::snapshot::Snapshot, ext::WriterExt, resolution::UnresolvedValue, util, vfs::Vfs};
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -43,6 +51,7 @@ pub fn read_json_model(path: &Path, vfs: &Vfs) -> Result<Snapshot> {
 fn walk(model: JsonModel, path: &Path) -> Result<Snapshot> {
 	let mut snapshot = Snapshot::new();
 	let mut properties = HashMap::new();
+	let mut refs = HashMap::new();
 
 	// Apply class
 	let class = model.class_name.unwrap_or(snapshot.class.clone());
@@ -56,6 +65,11 @@ fn walk(model: JsonModel, path: &Path) -> Result<Snapshot> {
 	// Resolve properties
 	if let Some(model_properties) = model.properties {
 		for (property, value) in model_properties {
+			if let Some(path) = value.as_ref_path(&class, &property) {
+				refs.insert(property, path.to_owned());
+				continue;
+			}
+
 			match value.resolve(&class, &property) {
 				Ok(value) => {
 					properties.insert(property, value);
@@ -89,6 +103,7 @@ fn walk(model: JsonModel, path: &Path) -> Result<Snapshot> {
 	}
 
 	snapshot.set_properties(properties);
+	snapshot.meta.set_refs(refs);
 
 	// Append children
 	for child in model.children.unwrap_or_default() {
@@ -97,3 +112,67 @@ fn walk(model: JsonModel, path: &Path) -> Result<Snapshot> {
 
 	Ok(snapshot)
 }
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WritableJsonModel {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	name: Option<String>,
+	class_name: String,
+
+	#[serde(skip_serializing_if = "BTreeMap::is_empty")]
+	properties: BTreeMap<String, UnresolvedValue>,
+
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	children: Vec<WritableJsonModel>,
+}
+
+#[profiling::function]
+pub fn write_json_model(dom: &WeakDom, id: Ref, path: &Path, vfs: &Vfs) -> Result<()> {
+	fn walk(dom: &WeakDom, id: Ref, is_root: bool) -> WritableJsonModel {
+		let instance = dom
+			.get_by_ref(id)
+			.expect("Provided ID does not exist in the current DOM");
+
+		let properties = instance
+			.properties
+			.iter()
+			.filter_map(|(property, variant)| {
+				// Refs are stored as paths of the referenced instances
+				let variant = match variant {
+					Variant::Ref(target) => Variant::String(util::get_instance_path(dom, *target)?),
+					_ => variant.clone(),
+				};
+
+				Some((
+					property.to_owned(),
+					UnresolvedValue::from_variant(variant, &instance.class, property),
+				))
+			})
+			.collect();
+
+		WritableJsonModel {
+			// Name of the root instance is derived from the file name
+			name: if is_root { None } else { Some(instance.name.clone()) },
+			class_name: instance.class.clone(),
+			properties,
+			children: instance
+				.children()
+				.iter()
+				.map(|&child| walk(dom, child, false))
+				.collect(),
+		}
+	}
+
+	let model = walk(dom, id, true);
+
+	let mut writer = Vec::new();
+	let mut serializer = Serializer::with_formatter(&mut writer, JsonFormatter::with_array_breaks(false));
+
+	model.serialize(&mut serializer)?;
+	writer.end()?;
+
+	vfs.write(path, &writer)?;
+
+	Ok(())
+}
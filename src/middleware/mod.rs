@@ -1,7 +1,10 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use colored::Colorize;
 use log::trace;
-use rbx_dom_weak::types::{Enum, Variant};
+use rbx_dom_weak::{
+	types::{Enum, Ref, Variant},
+	WeakDom,
+};
 use serde::{Deserialize, Serialize};
 use std::{
 	fmt::{self, Display, Formatter},
@@ -102,8 +105,12 @@ impl Middleware {
 			}
 			Middleware::StringValue => txt::write_txt(properties, path, vfs),
 			Middleware::LocalizationTable => csv::write_csv(properties, path, vfs),
-			// TODO: Add support for other middleware
-			_ => unimplemented!(),
+			//
+			Middleware::JsonModule => json::write_json(properties, path, vfs),
+			Middleware::TomlModule => toml::write_toml(properties, path, vfs),
+			Middleware::YamlModule => yaml::write_yaml(properties, path, vfs),
+			Middleware::MsgpackModule => msgpack::write_msgpack(properties, path, vfs),
+			_ => bail!("Writing {} is not supported", self),
 		}
 		.with_desc(|| {
 			format!(
@@ -114,6 +121,45 @@ impl Middleware {
 		})
 	}
 
+	pub fn write_model(&self, dom: &WeakDom, id: Ref, path: &Path, vfs: &Vfs) -> Result<()> {
+		match self {
+			Middleware::JsonModel => json_model::write_json_model(dom, id, path, vfs),
+			Middleware::RbxmModel => rbxm::write_rbxm(dom, id, path, vfs),
+			Middleware::RbxmxModel => rbxmx::write_rbxmx(dom, id, path, vfs),
+			_ => bail!("Writing {} as a model is not supported", self),
+		}
+		.with_desc(|| {
+			format!(
+				"Failed to write {} at {}",
+				self.to_string().bold(),
+				path.display().to_string().bold()
+			)
+		})
+	}
+
+	pub fn is_data_module(&self) -> bool {
+		matches!(
+			self,
+			Middleware::JsonModule | Middleware::TomlModule | Middleware::YamlModule | Middleware::MsgpackModule
+		)
+	}
+
+	pub fn is_model(&self) -> bool {
+		matches!(
+			self,
+			Middleware::JsonModel | Middleware::RbxmModel | Middleware::RbxmxModel
+		)
+	}
+
+	/// Returns the middleware that was used to read the file at `path`
+	pub fn from_path(path: &Path, context: &Context) -> Option<Self> {
+		context
+			.sync_rules()
+			.iter()
+			.find_map(|rule| rule.resolve(path).or_else(|| rule.resolve_child(path)))
+			.map(|resolved| resolved.middleware)
+	}
+
 	pub fn from_class(class: &str, properties: Option<&mut Properties>) -> Option<Self> {
 		// TODO: Implement matcher for detecting remaining middleware
 		match class {
@@ -4,7 +4,8 @@ use rmp_serde::Deserializer;
 use rmpv::Value;
 use std::{collections::HashMap, path::Path};
 
-use crate::{core::snapshot::Snapshot, vfs::Vfs};
+use super::helpers::{self, LuaValue};
+use crate::{core::snapshot::Snapshot, vfs::Vfs, Properties};
 
 #[profiling::function]
 pub fn read_msgpack(path: &Path, vfs: &Vfs) -> Result<Snapshot> {
@@ -25,6 +26,31 @@ pub fn read_msgpack(path: &Path, vfs: &Vfs) -> Result<Snapshot> {
 	Ok(Snapshot::new().with_class("ModuleScript").with_properties(properties))
 }
 
+#[profiling::function]
+pub fn write_msgpack(mut properties: Properties, path: &Path, vfs: &Vfs) -> Result<Properties> {
+	if let Some(Variant::String(source)) = properties.remove("Source") {
+		if source.trim().is_empty() {
+			vfs.write(path, &[])?;
+			return Ok(properties);
+		}
+
+		// Existing file tells whether empty tables are arrays or maps
+		let existing = vfs
+			.read(path)
+			.ok()
+			.and_then(|msgpack| rmpv::decode::read_value(&mut msgpack.as_slice()).ok());
+
+		let msgpack = lua_to_msgpack(&helpers::parse_lua(&source)?, existing.as_ref());
+
+		let mut writer = Vec::new();
+		rmpv::encode::write_value(&mut writer, &msgpack)?;
+
+		vfs.write(path, &writer)?;
+	}
+
+	Ok(properties)
+}
+
 fn msgpack_to_lua(value: &Value) -> String {
 	let mut lua = String::new();
 
@@ -75,6 +101,57 @@ fn msgpack_to_lua(value: &Value) -> String {
 	lua
 }
 
+fn lua_to_msgpack(value: &LuaValue, existing: Option<&Value>) -> Value {
+	match value {
+		LuaValue::Nil => Value::Nil,
+		LuaValue::Bool(bool) => Value::Boolean(*bool),
+		LuaValue::Integer(integer) => Value::from(*integer),
+		LuaValue::Number(number) => {
+			// Keep single precision of existing floats if the number
+			// is the same as the one read from them (shortest `f32` form)
+			let single = *number as f32;
+
+			if matches!(existing, Some(Value::F32(_))) && single.to_string().parse::<f64>().ok() == Some(*number) {
+				Value::F32(single)
+			} else {
+				Value::F64(*number)
+			}
+		}
+		LuaValue::String(string) => Value::from(string.as_str()),
+		LuaValue::Table(entries) => {
+			if let Some(array) = value.as_array(existing.is_some_and(Value::is_array)) {
+				let existing = existing.and_then(Value::as_array);
+
+				Value::Array(
+					array
+						.into_iter()
+						.enumerate()
+						.map(|(index, value)| lua_to_msgpack(value, existing.and_then(|existing| existing.get(index))))
+						.collect(),
+				)
+			} else {
+				let existing = existing.and_then(Value::as_map);
+
+				Value::Map(
+					entries
+						.iter()
+						.map(|(key, value)| {
+							let key = lua_to_msgpack(key, None);
+							let existing = existing
+								.and_then(|existing| existing.iter().find(|(existing, _)| *existing == key))
+								.map(|(_, value)| value);
+
+							let value = lua_to_msgpack(value, existing);
+
+							(key, value)
+						})
+						.collect(),
+				)
+			}
+		}
+	}
+}
+
 fn escape_chars(string: &str) -> String {
 	let mut validated = String::new();
 
@@ -1,6 +1,5 @@
 use anyhow::{bail, Result};
 use colored::Colorize;
-use log::error;
 use path_clean::PathClean;
 use rbx_dom_weak::types::Tags;
 use std::{collections::HashMap, path::Path};
@@ -12,6 +11,7 @@ use crate::{
 		meta::{Context, Meta, NodePath, Source},
 		snapshot::Snapshot,
 	},
+	diagnostics::{self, Diagnostic},
 	ext::PathExt,
 	middleware::helpers,
 	project::{Project, ProjectNode, ProjectPath},
@@ -25,12 +25,22 @@ pub fn read_project(path: &Path, vfs: &Vfs) -> Result<Snapshot> {
 
 	vfs.watch(path, false)?;
 
+	for path in &project.inherited {
+		vfs.watch(path, false)?;
+	}
+
+	let inherited = project.inherited.clone();
+
 	let meta = Meta::from_project(&project);
 	let mut snapshot = new_snapshot_node(&project.name, path, project.node, NodePath::new(), &meta.context, vfs)?;
 
 	let mut source = Source::file(path).with_relevant(snapshot.meta.source.relevant().to_owned());
 	source.add_project(path);
 
+	for path in &inherited {
+		source.add_project(path);
+	}
+
 	snapshot.set_meta(meta.with_source(source));
 
 	Ok(snapshot)
@@ -46,7 +56,13 @@ pub fn new_snapshot_node(
 	vfs: &Vfs,
 ) -> Result<Snapshot> {
 	if node.class_name.is_some() && node.path.is_some() {
-		bail!("Failed to load project: $className and $path cannot be set at the same time");
+		if !diagnostics::is_collecting() {
+			bail!("Failed to load project: $className and $path cannot be set at the same time");
+		}
+
+		diagnostics::report(
+			Diagnostic::error("$className and $path cannot be set at the same time", path).with_node_path(&node_path),
+		);
 	}
 
 	let class = if let Some(class_name) = &node.class_name {
@@ -57,20 +73,26 @@ pub fn new_snapshot_node(
 		String::from("Folder")
 	};
 
+	let mut refs = HashMap::new();
+
 	let properties = {
 		let mut properties = HashMap::new();
 
 		for (property, value) in &node.properties {
+			if let Some(path) = value.as_ref_path(&class, property) {
+				refs.insert(property.to_owned(), path.to_owned());
+				continue;
+			}
+
 			match value.clone().resolve(&class, property) {
 				Ok(value) => {
 					properties.insert(property.to_owned(), value);
 				}
 				Err(err) => {
-					error!(
-						"Failed to parse property: {} at {}, JSON path: {}",
-						err,
-						path.display(),
-						node_path
+					diagnostics::report(
+						Diagnostic::error(&format!("Failed to parse property: {}", err), path)
+							.with_json_path(&format!("{}/$properties/{}", node_path, property))
+							.with_node_path(&node_path),
 					);
 				}
 			}
@@ -82,11 +104,10 @@ pub fn new_snapshot_node(
 					properties.insert(String::from("Attributes"), value);
 				}
 				Err(err) => {
-					error!(
-						"Failed to parse attributes: {} at {}, JSON path: {}",
-						err,
-						path.display(),
-						node_path
+					diagnostics::report(
+						Diagnostic::error(&format!("Failed to parse attributes: {}", err), path)
+							.with_json_path(&format!("{}/$attributes", node_path))
+							.with_node_path(&node_path),
 					);
 				}
 			}
@@ -102,7 +123,8 @@ pub fn new_snapshot_node(
 	let mut meta = Meta::new()
 		.with_source(Source::project(name, path, node.clone(), node_path.clone()))
 		.with_context(context)
-		.with_keep_unknowns(node.keep_unknowns.unwrap_or_else(|| util::is_service(&class)));
+		.with_keep_unknowns(node.keep_unknowns.unwrap_or_else(|| util::is_service(&class)))
+		.with_refs(refs);
 
 	if class == "MeshPart" {
 		meta.set_mesh_source(helpers::save_mesh(&properties));
@@ -115,6 +137,7 @@ pub fn new_snapshot_node(
 		.with_meta(meta);
 
 	if let Some(path_node) = node.path {
+		let project_path = path;
 		let path = path.with_file_name(path_node.path()).clean();
 
 		if vfs.exists(&path) {
@@ -142,12 +165,28 @@ pub fn new_snapshot_node(
 
 				snapshot = path_snapshot;
 			}
-		} else if let ProjectPath::Required(_) = path_node {
-			argon_warn!(
-				"Path specified in the project does not exist: {}. Please create this path and restart Argon \
-				to watch for file changes in this path or remove it from the project to suppress this warning",
-				path.to_string().bold()
-			);
+		} else {
+			// Sync the path as soon as it gets created,
+			// it might have been watched before it was removed
+			vfs.unwatch(&path)?;
+			vfs.watch_missing(&path)?;
+			snapshot.meta.source.add_missing(&path);
+
+			if let ProjectPath::Required(_) = path_node {
+				if diagnostics::is_collecting() {
+					diagnostics::report(
+						Diagnostic::error(&format!("Path {} does not exist", path.display()), project_path)
+							.with_json_path(&format!("{}/$path", node_path))
+							.with_node_path(&node_path),
+					);
+				} else {
+					argon_warn!(
+						"Path specified in the project does not exist: {}. It will be synced once created, \
+					or remove it from the project to suppress this warning",
+						path.to_string().bold()
+					);
+				}
+			}
 		}
 	}
 
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use rbx_dom_weak::{types::Ref, WeakDom};
 use std::path::Path;
 
 use super::helpers;
@@ -18,3 +19,13 @@ pub fn read_rbxmx(path: &Path, vfs: &Vfs) -> Result<Snapshot> {
 
 	Ok(snapshot)
 }
+
+#[profiling::function]
+pub fn write_rbxmx(dom: &WeakDom, id: Ref, path: &Path, vfs: &Vfs) -> Result<()> {
+	let mut writer = Vec::new();
+
+	rbx_xml::to_writer_default(&mut writer, dom, &[id])?;
+	vfs.write(path, &writer)?;
+
+	Ok(())
+}
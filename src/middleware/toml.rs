@@ -1,8 +1,10 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use rbx_dom_weak::types::Variant;
 use std::{collections::HashMap, path::Path};
+use toml::{Table, Value};
 
-use crate::{core::snapshot::Snapshot, vfs::Vfs};
+use super::helpers::{self, LuaValue, Ordered};
+use crate::{core::snapshot::Snapshot, vfs::Vfs, Properties};
 
 #[profiling::function]
 pub fn read_toml(path: &Path, vfs: &Vfs) -> Result<Snapshot> {
@@ -16,3 +18,67 @@ pub fn read_toml(path: &Path, vfs: &Vfs) -> Result<Snapshot> {
 
 	Ok(Snapshot::new().with_class("ModuleScript").with_properties(properties))
 }
+
+#[profiling::function]
+pub fn write_toml(mut properties: Properties, path: &Path, vfs: &Vfs) -> Result<Properties> {
+	if let Some(Variant::String(source)) = properties.remove("Source") {
+		if source.trim().is_empty() {
+			vfs.write(path, &[])?;
+			return Ok(properties);
+		}
+
+		// Existing file tells whether empty tables are arrays or tables
+		let existing = vfs
+			.read_to_string(path)
+			.ok()
+			.and_then(|toml| toml.parse::<Table>().ok());
+
+		let toml = match helpers::parse_lua(&source)? {
+			LuaValue::Table(entries) => lua_to_toml_table(&entries, existing.as_ref())?,
+			value => bail!("TOML module has to return a table, got {}", value.describe()),
+		};
+
+		vfs.write(path, toml::to_string(&toml)?.as_bytes())?;
+	}
+
+	Ok(properties)
+}
+
+fn lua_to_toml(value: &LuaValue, existing: Option<&Value>) -> Result<Ordered<Value>> {
+	Ok(match value {
+		LuaValue::Nil => bail!("TOML does not support nil values"),
+		LuaValue::Bool(bool) => Ordered::Value(Value::Boolean(*bool)),
+		LuaValue::Integer(integer) => Ordered::Value(Value::Integer(*integer)),
+		LuaValue::Number(number) => Ordered::Value(Value::Float(*number)),
+		LuaValue::String(string) => Ordered::Value(Value::String(string.to_owned())),
+		LuaValue::Table(entries) => {
+			if let Some(array) = value.as_array(existing.is_some_and(Value::is_array)) {
+				Ordered::Array(
+					array
+						.into_iter()
+						.enumerate()
+						.map(|(index, value)| lua_to_toml(value, existing.and_then(|existing| existing.get(index))))
+						.collect::<Result<_>>()?,
+				)
+			} else {
+				lua_to_toml_table(entries, existing.and_then(Value::as_table))?
+			}
+		}
+	})
+}
+
+fn lua_to_toml_table(entries: &[(LuaValue, LuaValue)], existing: Option<&Table>) -> Result<Ordered<Value>> {
+	let mut table = Vec::with_capacity(entries.len());
+
+	for (key, value) in entries {
+		match key {
+			LuaValue::String(key) => {
+				let existing = existing.and_then(|existing| existing.get(key));
+				table.push((key.to_owned(), lua_to_toml(value, existing)?));
+			}
+			key => bail!("TOML table keys have to be strings, got {}", key.describe()),
+		}
+	}
+
+	Ok(Ordered::Map(table))
+}
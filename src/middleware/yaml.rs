@@ -1,8 +1,10 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use rbx_dom_weak::types::Variant;
+use serde_yaml::{Mapping, Number, Value};
 use std::{collections::HashMap, path::Path};
 
-use crate::{core::snapshot::Snapshot, vfs::Vfs};
+use super::helpers::{self, LuaValue};
+use crate::{core::snapshot::Snapshot, vfs::Vfs, Properties};
 
 #[profiling::function]
 pub fn read_yaml(path: &Path, vfs: &Vfs) -> Result<Snapshot> {
@@ -16,3 +18,68 @@ pub fn read_yaml(path: &Path, vfs: &Vfs) -> Result<Snapshot> {
 
 	Ok(Snapshot::new().with_class("ModuleScript").with_properties(properties))
 }
+
+#[profiling::function]
+pub fn write_yaml(mut properties: Properties, path: &Path, vfs: &Vfs) -> Result<Properties> {
+	if let Some(Variant::String(source)) = properties.remove("Source") {
+		if source.trim().is_empty() {
+			vfs.write(path, &[])?;
+			return Ok(properties);
+		}
+
+		// Existing file tells whether empty tables are sequences or mappings
+		let existing = vfs
+			.read(path)
+			.ok()
+			.and_then(|yaml| serde_yaml::from_slice::<Value>(&yaml).ok());
+
+		let yaml = match helpers::parse_lua(&source)? {
+			value @ LuaValue::Table(_) => lua_to_yaml(&value, existing.as_ref())?,
+			value => bail!("YAML module has to return a table, got {}", value.describe()),
+		};
+
+		vfs.write(path, serde_yaml::to_string(&yaml)?.as_bytes())?;
+	}
+
+	Ok(properties)
+}
+
+fn lua_to_yaml(value: &LuaValue, existing: Option<&Value>) -> Result<Value> {
+	Ok(match value {
+		LuaValue::Nil => Value::Null,
+		LuaValue::Bool(bool) => Value::Bool(*bool),
+		LuaValue::Integer(integer) => Value::Number(Number::from(*integer)),
+		LuaValue::Number(number) => Value::Number(Number::from(*number)),
+		LuaValue::String(string) => Value::String(string.to_owned()),
+		LuaValue::Table(entries) => {
+			if let Some(array) = value.as_array(existing.is_some_and(Value::is_sequence)) {
+				Value::Sequence(
+					array
+						.into_iter()
+						.enumerate()
+						.map(|(index, value)| lua_to_yaml(value, existing.and_then(|existing| existing.get(index))))
+						.collect::<Result<_>>()?,
+				)
+			} else {
+				lua_to_yaml_mapping(entries, existing)?
+			}
+		}
+	})
+}
+
+fn lua_to_yaml_mapping(entries: &[(LuaValue, LuaValue)], existing: Option<&Value>) -> Result<Value> {
+	let mut mapping = Mapping::new();
+
+	for (key, value) in entries {
+		if let LuaValue::Table(_) = key {
+			bail!("YAML mapping keys have to be strings, numbers or booleans, got a table");
+		}
+
+		let key = lua_to_yaml(key, None)?;
+		let existing = existing.and_then(|existing| existing.get(&key));
+
+		mapping.insert(key, lua_to_yaml(value, existing)?);
+	}
+
+	Ok(Value::Mapping(mapping))
+}
@@ -22,6 +22,7 @@ pub enum ProgramName {
 pub struct Program {
 	program: ProgramName,
 	args: Vec<String>,
+	envs: Vec<(String, String)>,
 	current_dir: PathBuf,
 	message: String,
 }
@@ -31,6 +32,7 @@ impl Program {
 		Self {
 			program,
 			args: vec![],
+			envs: vec![],
 			current_dir: env::current_dir().unwrap(),
 			message: String::from("Failed to start child process"),
 		}
@@ -66,6 +68,13 @@ impl Program {
 		self
 	}
 
+	/// Sets environment variable of the child process, unlike
+	/// arguments it's not visible to other users in the process list
+	pub fn env(&mut self, key: &str, value: &str) -> &mut Self {
+		self.envs.push((key.to_owned(), value.to_owned()));
+		self
+	}
+
 	pub fn current_dir(&mut self, dir: &Path) -> &mut Self {
 		dir.clone_into(&mut self.current_dir);
 		self
@@ -109,7 +118,8 @@ impl Program {
 				.env("RUST_VERBOSE", verbosity)
 				.env("RUST_LOG_STYLE", log_style)
 				.env("RUST_BACKTRACE", backtrace)
-				.env("RUST_YES", yes);
+				.env("RUST_YES", yes)
+				.envs(self.envs.clone());
 
 			return command;
 		};
@@ -138,7 +148,10 @@ impl Program {
 		}
 
 		let mut command = Command::new(program);
-		command.current_dir(self.current_dir.clone()).args(self.args.clone());
+		command
+			.current_dir(self.current_dir.clone())
+			.args(self.args.clone())
+			.envs(self.envs.clone());
 
 		if util::env_verbosity() == LevelFilter::Off {
 			command.stdout(Stdio::null());
@@ -1,14 +1,15 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use colored::Colorize;
 use json_formatter::JsonFormatter;
 use rbx_dom_weak::types::Ref;
 use serde::{Deserialize, Serialize};
-use serde_json::Serializer;
+use serde_json::{Serializer, Value};
 use std::{
-	collections::{BTreeMap, HashMap},
-	fs, mem,
+	collections::{btree_map::Entry, BTreeMap, HashMap},
+	env, fs, mem,
 	path::{Path, PathBuf},
 };
+use toml_edit::{DocumentMut, Item, TableLike};
 
 use crate::{
 	config::Config,
@@ -18,6 +19,8 @@ use crate::{
 	},
 	ext::{PathExt, ResultExt, WriterExt},
 	glob::Glob,
+	jsonc,
+	middleware::Middleware,
 	resolution::UnresolvedValue,
 };
 
@@ -35,6 +38,29 @@ impl ProjectPath {
 			ProjectPath::Optional { optional } => optional.as_ref(),
 		}
 	}
+
+	fn rebase(&mut self, dir: &Path) {
+		match self {
+			ProjectPath::Required(path) => *path = dir.join(&path),
+			ProjectPath::Optional { optional } => *optional = dir.join(&optional),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ProjectExtends {
+	Single(PathBuf),
+	Multiple(Vec<PathBuf>),
+}
+
+impl ProjectExtends {
+	pub fn paths(&self) -> Vec<PathBuf> {
+		match self {
+			ProjectExtends::Single(path) => vec![path.to_owned()],
+			ProjectExtends::Multiple(paths) => paths.to_owned(),
+		}
+	}
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
@@ -60,6 +86,66 @@ pub struct ProjectNode {
 		skip_serializing_if = "Option::is_none"
 	)]
 	pub keep_unknowns: Option<bool>,
+
+	/// Base project this node is inherited from, `None` if it's defined locally
+	#[serde(skip)]
+	pub origin: Option<PathBuf>,
+}
+
+impl ProjectNode {
+	/// Fills this node with values of the `base` node,
+	/// values that are already set take precedence
+	fn merge(&mut self, base: ProjectNode) {
+		if self.class_name.is_none() && self.path.is_none() {
+			self.class_name = base.class_name;
+			self.path = base.path;
+		}
+
+		for (name, node) in base.tree {
+			match self.tree.entry(name) {
+				Entry::Occupied(mut entry) => entry.get_mut().merge(node),
+				Entry::Vacant(entry) => {
+					entry.insert(node);
+				}
+			}
+		}
+
+		for (property, value) in base.properties {
+			self.properties.entry(property).or_insert(value);
+		}
+
+		for tag in base.tags {
+			if !self.tags.contains(&tag) {
+				self.tags.push(tag);
+			}
+		}
+
+		self.attributes = self.attributes.take().or(base.attributes);
+		self.keep_unknowns = self.keep_unknowns.or(base.keep_unknowns);
+	}
+
+	/// Marks this node and its descendants that
+	/// have no origin yet as defined in `path`
+	fn set_origin(&mut self, path: &Path) {
+		if self.origin.is_none() {
+			self.origin = Some(path.to_owned());
+		}
+
+		for node in self.tree.values_mut() {
+			node.set_origin(path);
+		}
+	}
+
+	/// Makes `$path`s of this node and its descendants relative to `dir`
+	fn rebase(&mut self, dir: &Path) {
+		if let Some(path) = &mut self.path {
+			path.rebase(dir);
+		}
+
+		for node in self.tree.values_mut() {
+			node.rebase(dir);
+		}
+	}
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -74,14 +160,41 @@ pub struct SyncbackSettings {
 	pub ignore_classes: Vec<String>,
 	#[serde(default, skip_serializing_if = "Vec::is_empty")]
 	pub ignore_properties: Vec<String>,
+
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub models: Vec<SyncbackModel>,
+}
+
+impl SyncbackSettings {
+	fn merge(&mut self, base: SyncbackSettings) {
+		self.ignore_globs.extend(base.ignore_globs);
+		self.ignore_names.extend(base.ignore_names);
+		self.ignore_classes.extend(base.ignore_classes);
+		self.ignore_properties.extend(base.ignore_properties);
+		self.models.extend(base.models);
+	}
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncbackModel {
+	#[serde(rename = "type")]
+	pub middleware: Middleware,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub class: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub pattern: Option<Glob>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Project {
-	#[serde(default = "default_project_name")]
+	#[serde(default, skip_serializing_if = "String::is_empty")]
 	pub name: String,
-	#[serde(rename = "tree")]
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extends: Option<ProjectExtends>,
+	#[serde(rename = "tree", default)]
 	pub node: ProjectNode,
 
 	#[serde(alias = "serveAddress", skip_serializing_if = "Option::is_none")]
@@ -104,16 +217,65 @@ pub struct Project {
 	#[serde(alias = "emitLegacyScripts", skip_serializing_if = "Option::is_none")]
 	pub legacy_scripts: Option<bool>,
 
+	/// Named sets of overrides that can be selected with `--profile`
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	pub profiles: BTreeMap<String, Value>,
+
 	#[serde(skip)]
 	pub path: PathBuf,
 	#[serde(skip)]
 	pub workspace_dir: PathBuf,
+	/// Paths of all projects this project inherits from
+	#[serde(skip)]
+	pub inherited: Vec<PathBuf>,
 }
 
 impl Project {
+	/// Loads the project and merges all projects it `extends`
 	pub fn load(project_path: &Path) -> Result<Self> {
-		let project = fs::read_to_string(project_path)?;
-		let mut project: Project = serde_json::from_str(&project).with_desc(|| {
+		let profile = Config::new().profile.clone();
+
+		Self::load_profile(project_path, if profile.is_empty() { None } else { Some(&profile) })
+	}
+
+	/// Loads the project like [`Project::load`] with the given profile applied
+	pub fn load_profile(project_path: &Path, profile: Option<&str>) -> Result<Self> {
+		let mut project = Self::parse(project_path, profile)?;
+
+		project.inherit(&mut vec![project_path.to_owned()], profile)?;
+
+		if project.name.is_empty() {
+			project.name = default_project_name();
+		}
+
+		Ok(project)
+	}
+
+	/// Loads the project without resolving `extends` and applying profiles,
+	/// should be used when the project is going to be saved
+	pub fn load_local(project_path: &Path) -> Result<Self> {
+		Self::parse(project_path, None)
+	}
+
+	fn parse(project_path: &Path, profile: Option<&str>) -> Result<Self> {
+		let project = read_value(project_path).and_then(|mut value| {
+			if let Some(overrides) = profile.and_then(|profile| value.get("profiles")?.get(profile)).cloned() {
+				merge_value(&mut value, overrides);
+			}
+
+			// Profiles may use variables that are only set when they are selected
+			if let Value::Object(map) = &mut value {
+				for (key, value) in map {
+					if key != "profiles" {
+						interpolate(value)?;
+					}
+				}
+			}
+
+			Ok(serde_json::from_value(value)?)
+		});
+
+		let mut project: Project = project.with_desc(|| {
 			format!(
 				"Failed to parse project at {}",
 				project_path.display().to_string().bold()
@@ -128,14 +290,111 @@ impl Project {
 		Ok(project)
 	}
 
+	fn inherit(&mut self, chain: &mut Vec<PathBuf>, profile: Option<&str>) -> Result<()> {
+		let extends = if let Some(extends) = &self.extends {
+			extends.paths()
+		} else {
+			return Ok(());
+		};
+
+		// Later bases take precedence over earlier ones
+		for path in extends.into_iter().rev() {
+			let path = resolve(self.workspace_dir.join(path))?;
+
+			if chain.contains(&path) {
+				bail!(
+					"Circular project inheritance: {} extends {}",
+					chain.last().unwrap().to_string().bold(),
+					path.to_string().bold()
+				);
+			}
+
+			if !path.exists() {
+				bail!("Base project {} does not exist", path.to_string().bold());
+			}
+
+			let mut base = Self::parse(&path, profile)?;
+
+			chain.push(path.clone());
+			base.inherit(chain, profile)?;
+			chain.pop();
+
+			base.node.rebase(&base.workspace_dir);
+			base.node.set_origin(&path);
+
+			self.inherited.push(path);
+			self.merge(base);
+		}
+
+		Ok(())
+	}
+
+	/// Fills this project with settings and tree of the `base` project,
+	/// sync rules and ignore globs of this project are checked first
+	fn merge(&mut self, base: Project) {
+		if self.name.is_empty() {
+			self.name = base.name;
+		}
+
+		self.node.merge(base.node);
+
+		self.host = self.host.take().or(base.host);
+		self.port = self.port.or(base.port);
+		self.game_id = self.game_id.or(base.game_id);
+		self.legacy_scripts = self.legacy_scripts.or(base.legacy_scripts);
+
+		if self.place_ids.is_empty() {
+			self.place_ids = base.place_ids;
+		}
+
+		self.ignore_globs.extend(base.ignore_globs);
+		self.sync_rules.extend(base.sync_rules);
+
+		for (name, profile) in base.profiles {
+			self.profiles.entry(name).or_insert(profile);
+		}
+
+		self.syncback = match (self.syncback.take(), base.syncback) {
+			(Some(mut syncback), Some(base)) => {
+				syncback.merge(base);
+				Some(syncback)
+			}
+			(syncback, base) => syncback.or(base),
+		};
+
+		self.inherited.extend(base.inherited);
+	}
+
+	/// Saves the project, keeping comments and formatting of the existing file
 	pub fn save(&self, path: &Path) -> Result<()> {
-		let formatter = JsonFormatter::with_array_breaks(false);
+		if path.exists() {
+			let contents = fs::read_to_string(path)?;
+
+			let mut project = serde_json::to_value(self)?;
+			restore_placeholders(&read_value(path)?, &mut project);
+
+			let contents = if is_toml(path) {
+				update_toml(&contents, &project)?
+			} else {
+				jsonc::update(&contents, &project)?
+			};
+
+			fs::write(path, contents)?;
+
+			return Ok(());
+		}
 
 		let mut writer = Vec::new();
-		let mut serializer = Serializer::with_formatter(&mut writer, formatter);
 
-		self.serialize(&mut serializer)?;
-		writer.end()?;
+		if is_toml(path) {
+			writer.extend(toml::to_string_pretty(self)?.as_bytes());
+		} else {
+			let formatter = JsonFormatter::with_array_breaks(false);
+			let mut serializer = Serializer::with_formatter(&mut writer, formatter);
+
+			self.serialize(&mut serializer)?;
+			writer.end()?;
+		}
 
 		fs::write(path, &writer)?;
 
@@ -150,6 +409,21 @@ impl Project {
 		Ok(self)
 	}
 
+	/// Makes sure the selected profile is defined by this project
+	pub fn verify_profile(&self) -> Result<()> {
+		let profile = &Config::new().profile;
+
+		if !profile.is_empty() && !self.profiles.contains_key(profile) {
+			bail!(
+				"Profile {} is not defined in project {}",
+				profile.bold(),
+				self.path.to_string().bold()
+			);
+		}
+
+		Ok(())
+	}
+
 	pub fn is_place(&self) -> bool {
 		if let Some(class) = &self.node.class_name {
 			class == "DataModel"
@@ -218,30 +492,245 @@ impl Project {
 pub fn resolve(path: PathBuf) -> Result<PathBuf> {
 	let path = path.resolve()?;
 
-	if path.is_file() || path.get_name().ends_with(".project.json") {
+	if path.is_file() || is_project(&path) {
 		return Ok(path);
 	}
 
 	if Config::new().smart_paths {
-		let path = path.with_file_name(path.get_name().to_owned() + ".project.json");
+		for ext in PROJECT_EXTENSIONS {
+			let path = path.with_file_name(path.get_name().to_owned() + ext);
 
-		if path.exists() {
+			if path.exists() {
+				return Ok(path);
+			}
+		}
+	}
+
+	for ext in PROJECT_EXTENSIONS {
+		let default_project = path.join(format!("default{}", ext));
+
+		if default_project.exists() {
+			return Ok(default_project);
+		}
+	}
+
+	for ext in PROJECT_EXTENSIONS {
+		let glob = path.clone().join(format!("*{}", ext));
+
+		if let Some(path) = Glob::from_path(&glob)?.first() {
 			return Ok(path);
 		}
 	}
 
-	let default_project = path.join("default.project.json");
+	Ok(path.join("default.project.json"))
+}
+
+/// Supported project file extensions, in order of priority
+const PROJECT_EXTENSIONS: [&str; 2] = [".project.json", ".project.toml"];
+
+pub fn is_project(path: &Path) -> bool {
+	PROJECT_EXTENSIONS.iter().any(|ext| path.get_name().ends_with(ext))
+}
+
+fn is_toml(path: &Path) -> bool {
+	path.get_name().ends_with(".toml")
+}
+
+/// Reads the project file as a generic value, JSON files are parsed as JSONC
+fn read_value(path: &Path) -> Result<Value> {
+	let contents = fs::read_to_string(path)?;
+
+	if is_toml(path) {
+		Ok(toml::from_str(&contents)?)
+	} else {
+		Ok(serde_json::from_str(&jsonc::strip(&contents))?)
+	}
+}
+
+/// Deep merges `overrides` into `value`, only objects are merged
+fn merge_value(value: &mut Value, overrides: Value) {
+	match (value, overrides) {
+		(Value::Object(value), Value::Object(overrides)) => {
+			for (key, override_value) in overrides {
+				match value.get_mut(&key) {
+					Some(value) => merge_value(value, override_value),
+					None => {
+						value.insert(key, override_value);
+					}
+				}
+			}
+		}
+		(value, overrides) => *value = overrides,
+	}
+}
+
+/// Replaces `${VAR}` and `${VAR:-default}` placeholders in all strings,
+/// values that are a single placeholder can resolve to numbers and booleans
+fn interpolate(value: &mut Value) -> Result<()> {
+	match value {
+		Value::String(string) if string.contains("${") => {
+			let interpolated = interpolate_str(string)?;
+
+			let is_whole = string.starts_with("${") && string.find('}') == Some(string.len() - 1);
+
+			*value = match serde_json::from_str::<Value>(&interpolated) {
+				Ok(parsed) if is_whole && (parsed.is_number() || parsed.is_boolean()) => parsed,
+				_ => Value::String(interpolated),
+			};
+		}
+		Value::Array(items) => {
+			for item in items {
+				interpolate(item)?;
+			}
+		}
+		Value::Object(map) => {
+			for value in map.values_mut() {
+				interpolate(value)?;
+			}
+		}
+		_ => {}
+	}
+
+	Ok(())
+}
+
+fn interpolate_str(string: &str) -> Result<String> {
+	let mut output = String::new();
+	let mut rest = string;
+
+	while let Some(start) = rest.find("${") {
+		// `$${` is an escaped placeholder
+		if rest[..start].ends_with('$') {
+			output.push_str(&rest[..start - 1]);
+			output.push_str("${");
+			rest = &rest[start + 2..];
+
+			continue;
+		}
+
+		output.push_str(&rest[..start]);
+
+		let end = if let Some(end) = rest[start..].find('}') {
+			start + end
+		} else {
+			bail!("Unclosed placeholder in {}", string.bold());
+		};
+
+		let placeholder = &rest[start + 2..end];
+
+		let (name, default) = match placeholder.split_once(":-") {
+			Some((name, default)) => (name, Some(default)),
+			None => (placeholder, None),
+		};
+
+		match (env::var(name), default) {
+			(Ok(value), _) => output.push_str(&value),
+			(Err(_), Some(default)) => output.push_str(default),
+			(Err(_), None) => bail!("Environment variable {} is not set and has no default", name.bold()),
+		}
+
+		rest = &rest[end + 1..];
+	}
+
+	output.push_str(rest);
+
+	Ok(output)
+}
+
+/// Puts back placeholders from the `raw` project file in place of values
+/// they resolve to, so saving the project does not overwrite them
+fn restore_placeholders(raw: &Value, value: &mut Value) {
+	match (raw, value) {
+		(Value::Object(raw), Value::Object(value)) => {
+			for (key, value) in value.iter_mut() {
+				if let Some(raw) = raw.get(key) {
+					restore_placeholders(raw, value);
+				}
+			}
+		}
+		(Value::Array(raw), Value::Array(value)) => {
+			for (raw, value) in raw.iter().zip(value.iter_mut()) {
+				restore_placeholders(raw, value);
+			}
+		}
+		(Value::String(string), value) if string.contains("${") => {
+			let mut interpolated = raw.clone();
+
+			if interpolate(&mut interpolated).is_ok() && interpolated == *value {
+				*value = raw.clone();
+			}
+		}
+		_ => {}
+	}
+}
+
+/// Updates TOML document in place so existing comments, formatting and order are kept
+fn update_toml(source: &str, project: &Value) -> Result<String> {
+	let mut document: DocumentMut = source.parse()?;
+	let new = toml_edit::ser::to_document(project)?;
+
+	patch_table(document.as_table_mut(), new.as_table());
 
-	if default_project.exists() {
-		return Ok(default_project);
+	Ok(document.to_string())
+}
+
+fn patch_table(table: &mut dyn TableLike, new: &dyn TableLike) {
+	let removed: Vec<String> = table
+		.iter()
+		.filter(|(key, _)| !new.contains_key(key))
+		.map(|(key, _)| key.to_owned())
+		.collect();
+
+	for key in removed {
+		table.remove(&key);
 	}
 
-	let glob = path.clone().join("*.project.json");
+	for (key, item) in new.iter() {
+		match table.get_mut(key) {
+			Some(old) => patch_item(old, item),
+			None => {
+				table.insert(key, item.clone());
+			}
+		}
+	}
+}
 
-	if let Some(path) = Glob::from_path(&glob)?.first() {
-		Ok(path)
+fn patch_item(old: &mut Item, new: &Item) {
+	if old.is_table_like() && new.is_table_like() {
+		patch_table(old.as_table_like_mut().unwrap(), new.as_table_like().unwrap());
+	} else if let (Some(old), Some(new)) = (old.as_value_mut(), new.as_value()) {
+		patch_value(old, new);
+	} else if let (Some(old), Some(new)) = (old.as_array_of_tables_mut(), new.as_array_of_tables()) {
+		if old.len() == new.len() {
+			for (old, new) in old.iter_mut().zip(new.iter()) {
+				patch_table(old, new);
+			}
+		} else {
+			*old = new.clone();
+		}
 	} else {
-		Ok(default_project)
+		*old = new.clone();
+	}
+}
+
+fn patch_value(old: &mut toml_edit::Value, new: &toml_edit::Value) {
+	match (old, new) {
+		(toml_edit::Value::Array(old), toml_edit::Value::Array(new)) if old.len() == new.len() => {
+			for (old, new) in old.iter_mut().zip(new.iter()) {
+				patch_value(old, new);
+			}
+		}
+		(toml_edit::Value::InlineTable(old), toml_edit::Value::InlineTable(new)) => {
+			patch_table(old, new);
+		}
+		(old, new) => {
+			if old.to_string().trim() != new.to_string().trim() {
+				let decor = old.decor().clone();
+
+				*old = new.clone();
+				*old.decor_mut() = decor;
+			}
+		}
 	}
 }
 
@@ -33,6 +33,15 @@ impl UnresolvedValue {
 		}
 	}
 
+	/// Returns path of the referenced instance if the property is an instance reference,
+	/// it can be resolved only after the whole tree is built
+	pub fn as_ref_path(&self, class: &str, property: &str) -> Option<&str> {
+		match find_descriptor(class, property)?.data_type {
+			DataType::Value(VariantType::Ref) => self.as_str(),
+			_ => None,
+		}
+	}
+
 	pub fn as_str(&self) -> Option<&str> {
 		match self {
 			UnresolvedValue::Ambiguous(AmbiguousValue::String(s)) => Some(s.as_str()),
@@ -208,9 +217,8 @@ impl UnresolvedValue {
 				rect.max.x as f64,
 				rect.max.y as f64,
 			]),
-			// TODO: Implement Ref
-			// Variant::Ref(reference) => AmbiguousValue::
-			//
+			// Refs are replaced with paths of the referenced instances before serialization
+			// so they are emitted as strings, the same way they are read
 			Variant::Region3(region) => AmbiguousValue::Array3Array2([
 				[region.min.x as f64, region.min.y as f64, region.min.z as f64],
 				[region.max.x as f64, region.max.y as f64, region.max.z as f64],
@@ -463,9 +471,7 @@ impl AmbiguousValue {
 					Vector2::new(rect[2] as f32, rect[3] as f32),
 				)
 				.into()),
-				// TODO: Implement Ref
-				// (VariantType::Ref, AmbiguousValue::String(path)) => Ok(),
-				//
+				// Refs are resolved only after the whole tree is built, see `UnresolvedValue::as_ref_path`
 				(VariantType::Region3, AmbiguousValue::Array3Array2(region)) => Ok(Region3::new(
 					Vector3::new(region[0][0] as f32, region[0][1] as f32, region[0][2] as f32),
 					Vector3::new(region[1][0] as f32, region[1][1] as f32, region[1][2] as f32),
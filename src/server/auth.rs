@@ -0,0 +1,53 @@
+use actix_web::{
+	body::{EitherBody, MessageBody},
+	dev::{ServiceRequest, ServiceResponse},
+	http::header::AUTHORIZATION,
+	middleware::Next,
+	web::Data,
+	Error, HttpResponse,
+};
+use constant_time_eq::constant_time_eq;
+use log::trace;
+
+/// Endpoints that modify the project or control the server
+const PROTECTED_PATHS: [&str; 7] = [
+	"/subscribe",
+	"/unsubscribe",
+	"/write",
+	"/confirm",
+	"/exec",
+	"/open",
+	"/stop",
+];
+
+/// Session token required by protected endpoints, `None` if authentication is disabled
+#[derive(Debug, Clone)]
+pub struct Token(pub Option<String>);
+
+pub async fn main(
+	request: ServiceRequest,
+	next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+	let token = request.app_data::<Data<Token>>().and_then(|token| token.0.clone());
+
+	if let Some(token) = token {
+		if PROTECTED_PATHS.contains(&request.path()) {
+			let authorized = request
+				.headers()
+				.get(AUTHORIZATION)
+				.and_then(|value| value.to_str().ok())
+				.and_then(|value| value.strip_prefix("Bearer "))
+				// Constant time comparison, so the token cannot be guessed from response times
+				.is_some_and(|value| constant_time_eq(value.as_bytes(), token.as_bytes()));
+
+			if !authorized {
+				trace!("Rejected unauthorized request: {}", request.path());
+
+				let response = HttpResponse::Unauthorized().body("Invalid or missing session token");
+				return Ok(request.into_response(response).map_into_right_body());
+			}
+		}
+	}
+
+	next.call(request).await.map(|response| response.map_into_left_body())
+}
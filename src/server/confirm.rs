@@ -0,0 +1,24 @@
+use actix_msgpack::MsgPack;
+use actix_web::{post, web::Data, HttpResponse, Responder};
+use log::trace;
+
+use crate::{core::processor::ConfirmRequest, server::Cores};
+
+#[post("/confirm")]
+async fn main(request: MsgPack<ConfirmRequest>, cores: Data<Cores>) -> impl Responder {
+	trace!("Received request: confirm");
+
+	let request = request.0;
+	let core = match cores.get(request.client_id) {
+		Ok(core) => core,
+		Err(err) => return HttpResponse::NotFound().body(err.to_string()),
+	};
+
+	if !core.queue().is_subscribed(request.client_id) {
+		return HttpResponse::Unauthorized().body("Not subscribed");
+	}
+
+	core.processor().confirm(request);
+
+	HttpResponse::Ok().body("Received confirmation successfully")
+}
@@ -1,12 +1,27 @@
 use actix_msgpack::MsgPackResponseBuilder;
-use actix_web::{get, web::Data, HttpResponse, Responder};
+use actix_web::{
+	get,
+	web::{Data, Query},
+	HttpResponse, Responder,
+};
 use log::trace;
-use std::sync::Arc;
 
-use crate::{core::Core, project::ProjectDetails};
+use crate::{
+	project::ProjectDetails,
+	server::{Cores, Target},
+};
 
 #[get("/details")]
-async fn main(core: Data<Arc<Core>>) -> impl Responder {
+async fn main(target: Query<Target>, cores: Data<Cores>) -> impl Responder {
 	trace!("Received request: details");
-	HttpResponse::Ok().msgpack(ProjectDetails::from_project(&core.project(), &core.tree()))
+
+	match cores.find(&target) {
+		Some(index) => {
+			let core = cores.get_index(index);
+			let details = ProjectDetails::from_project(&core.project(), &core.tree());
+
+			HttpResponse::Ok().msgpack(details)
+		}
+		None => HttpResponse::NotFound().body("No project matches the request"),
+	}
 }
@@ -2,9 +2,11 @@ use actix_msgpack::MsgPack;
 use actix_web::{post, web::Data, HttpResponse, Responder};
 use log::{error, trace};
 use serde::Deserialize;
-use std::sync::Arc;
 
-use crate::{core::Core, server, studio};
+use crate::{
+	server::{self, Cores},
+	studio,
+};
 
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -14,20 +16,25 @@ struct Request {
 }
 
 #[post("/exec")]
-async fn main(request: MsgPack<Request>, core: Data<Arc<Core>>) -> impl Responder {
+async fn main(request: MsgPack<Request>, cores: Data<Cores>) -> impl Responder {
 	trace!("Received request: exec");
 
-	let queue = core.queue();
-
-	let pushed = queue.push(
-		server::ExecuteCode {
-			code: request.code.clone(),
-		},
-		None,
-	);
+	let pushed = cores.all().iter().try_for_each(|core| {
+		core.queue().push(
+			server::ExecuteCode {
+				code: request.code.clone(),
+			},
+			None,
+		)
+	});
 
 	if request.focus {
-		if let Some(name) = queue.get_first_non_internal_listener_name() {
+		let name = cores
+			.all()
+			.iter()
+			.find_map(|core| core.queue().get_first_non_internal_listener_name());
+
+		if let Some(name) = name {
 			match studio::focus(Some(name)) {
 				Ok(()) => (),
 				Err(err) => error!("Failed to focus Roblox Studio: {}", err),
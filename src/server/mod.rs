@@ -1,18 +1,27 @@
 use actix_msgpack::MsgPackConfig;
 use actix_web::{
+	middleware::from_fn,
 	web::{self, Data},
 	App, HttpServer, Responder,
 };
+use anyhow::bail;
 use derive_from_one::FromOne;
 use serde::{Deserialize, Serialize};
-use std::{io::Result, net::TcpListener, sync::Arc};
+use std::{
+	collections::HashMap,
+	io::Result,
+	net::TcpListener,
+	sync::{Arc, RwLock},
+};
 
 use crate::{
 	constants::MAX_PAYLOAD_SIZE,
-	core::{changes::Changes, Core},
+	core::{changes::Changes, processor::outcome::InstanceOutcome, Core},
 	project::ProjectDetails,
 };
 
+mod auth;
+mod confirm;
 mod details;
 mod exec;
 mod home;
@@ -20,6 +29,7 @@ mod open;
 mod read;
 mod snapshot;
 mod stop;
+mod stream;
 mod subscribe;
 mod unsubscribe;
 mod write;
@@ -28,17 +38,64 @@ mod write;
 pub enum Message {
 	SyncChanges(SyncChanges),
 	SyncbackChanges(SyncbackChanges),
+	SyncbackResult(SyncbackResult),
+	ConfirmChanges(ConfirmChanges),
 	SyncDetails(SyncDetails),
 	ExecuteCode(ExecuteCode),
 	Disconnect(Disconnect),
 }
 
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SequencedMessage {
+	pub sequence: u64,
+	pub message: Message,
+}
+
+impl Message {
+	pub fn kind(&self) -> &'static str {
+		match self {
+			Message::SyncChanges(_) => "SyncChanges",
+			Message::SyncbackChanges(_) => "SyncbackChanges",
+			Message::SyncbackResult(_) => "SyncbackResult",
+			Message::ConfirmChanges(_) => "ConfirmChanges",
+			Message::SyncDetails(_) => "SyncDetails",
+			Message::ExecuteCode(_) => "ExecuteCode",
+			Message::Disconnect(_) => "Disconnect",
+		}
+	}
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct SyncChanges(pub Changes);
 
+/// Sent once client changes are processed, kept
+/// empty for compatibility with older clients
 #[derive(Debug, Clone, Serialize)]
 pub struct SyncbackChanges();
 
+/// Result of applying changes sent by the client,
+/// failed batches are rolled back as a whole
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncbackResult {
+	pub success: bool,
+	pub error: Option<String>,
+	/// Whether files were restored to their state from before the batch
+	pub rolled_back: bool,
+	/// Outcome of every requested instance and the ones that were not applied as sent
+	pub instances: Vec<InstanceOutcome>,
+}
+
+/// Asks the client to accept or reject its changes, which exceed
+/// `changes_threshold`, the answer is expected at `/confirm` endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfirmChanges {
+	pub additions: usize,
+	pub updates: usize,
+	pub removals: usize,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct SyncDetails(pub ProjectDetails);
 
@@ -58,38 +115,125 @@ pub struct AuthRequest {
 	client_id: u32,
 }
 
+/// Project selected by the client, matched by its name or place ID
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Target {
+	pub project: Option<String>,
+	pub place_id: Option<u64>,
+}
+
+/// Cores served by the server together with clients routed to them
+pub struct Cores {
+	cores: Vec<Arc<Core>>,
+	clients: RwLock<HashMap<u32, usize>>,
+}
+
+impl Cores {
+	pub fn new(cores: Vec<Arc<Core>>) -> Self {
+		Self {
+			cores,
+			clients: RwLock::new(HashMap::new()),
+		}
+	}
+
+	/// Returns index of the core matching the target,
+	/// the first one is used when no target is specified
+	pub fn find(&self, target: &Target) -> Option<usize> {
+		if let Some(name) = &target.project {
+			return self.cores.iter().position(|core| core.name() == *name);
+		}
+
+		if let Some(place_id) = target.place_id {
+			if let Some(index) = self
+				.cores
+				.iter()
+				.position(|core| core.project().place_ids.contains(&place_id))
+			{
+				return Some(index);
+			}
+
+			// Projects without place IDs accept any place
+			return self.cores.iter().position(|core| core.project().place_ids.is_empty());
+		}
+
+		Some(0)
+	}
+
+	pub fn route(&self, client_id: u32, index: usize) {
+		self.clients.write().unwrap().insert(client_id, index);
+	}
+
+	pub fn unroute(&self, client_id: u32) {
+		self.clients.write().unwrap().remove(&client_id);
+	}
+
+	/// Returns core the client is routed to, clients that are not routed
+	/// can only be served when there is a single project to choose from
+	pub fn get(&self, client_id: u32) -> anyhow::Result<Arc<Core>> {
+		let index = self.clients.read().unwrap().get(&client_id).copied();
+
+		match index {
+			Some(index) => Ok(self.cores[index].clone()),
+			None if self.cores.len() == 1 => Ok(self.cores[0].clone()),
+			None => bail!("Client {} is not subscribed to any project", client_id),
+		}
+	}
+
+	pub fn get_index(&self, index: usize) -> Arc<Core> {
+		self.cores[index].clone()
+	}
+
+	pub fn all(&self) -> &[Arc<Core>] {
+		&self.cores
+	}
+}
+
 pub struct Server {
-	core: Arc<Core>,
+	cores: Arc<Cores>,
 	host: String,
 	port: u16,
+	token: Option<String>,
 }
 
 impl Server {
-	pub fn new(core: Arc<Core>, host: &str, port: u16) -> Self {
+	pub fn new(cores: Vec<Arc<Core>>, host: &str, port: u16) -> Self {
 		Self {
-			core,
+			cores: Arc::new(Cores::new(cores)),
 			host: host.to_owned(),
 			port,
+			token: None,
 		}
 	}
 
+	/// Require the given token for requests that modify the project or control the server
+	pub fn with_token(mut self, token: Option<String>) -> Self {
+		self.token = token;
+		self
+	}
+
 	#[actix_web::main]
 	pub async fn start(&self) -> Result<()> {
-		let core = self.core.clone();
+		let cores = self.cores.clone();
+		let token = auth::Token(self.token.clone());
 
 		HttpServer::new(move || {
 			let mut msgpack_config = MsgPackConfig::default();
 			msgpack_config.limit(MAX_PAYLOAD_SIZE);
 
 			App::new()
-				.app_data(Data::new(core.clone()))
+				.wrap(from_fn(auth::main))
+				.app_data(Data::from(cores.clone()))
+				.app_data(Data::new(token.clone()))
 				.app_data(msgpack_config)
 				.service(details::main)
 				.service(subscribe::main)
 				.service(unsubscribe::main)
 				.service(snapshot::main)
 				.service(read::main)
+				.service(stream::main)
 				.service(write::main)
+				.service(confirm::main)
 				.service(exec::main)
 				.service(open::main)
 				.service(stop::main)
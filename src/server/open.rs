@@ -3,21 +3,27 @@ use actix_web::{post, web::Data, HttpResponse, Responder};
 use log::trace;
 use rbx_dom_weak::types::Ref;
 use serde::Deserialize;
-use std::sync::Arc;
 
-use crate::core::Core;
+use crate::server::Cores;
 
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 struct Request {
 	instance: Ref,
 	_line: u32,
+	#[serde(default)]
+	client_id: u32,
 }
 
 #[post("/open")]
-async fn main(request: MsgPack<Request>, core: Data<Arc<Core>>) -> impl Responder {
+async fn main(request: MsgPack<Request>, cores: Data<Cores>) -> impl Responder {
 	trace!("Received request: open");
 
+	let core = match cores.get(request.client_id) {
+		Ok(core) => core,
+		Err(err) => return HttpResponse::NotFound().body(err.to_string()),
+	};
+
 	match core.open(request.instance) {
 		Ok(_) => HttpResponse::Ok().body("Opened file successfully"),
 		Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
@@ -1,21 +1,42 @@
 use actix_msgpack::{MsgPack, MsgPackResponseBuilder};
 use actix_web::{post, web::Data, HttpResponse, Responder};
 use log::trace;
-use std::sync::Arc;
+use serde::Deserialize;
 
-use crate::{core::Core, server::AuthRequest};
+use crate::{constants::QUEUE_TIMEOUT, server::Cores};
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Request {
+	client_id: u32,
+	/// Whether to include message sequence number in the response
+	#[serde(default)]
+	sequenced: bool,
+}
 
 #[post("/read")]
-async fn main(request: MsgPack<AuthRequest>, core: Data<Arc<Core>>) -> impl Responder {
+async fn main(request: MsgPack<Request>, cores: Data<Cores>) -> impl Responder {
 	trace!("Received request: read");
 
 	let id = request.client_id;
+	let core = match cores.get(id) {
+		Ok(core) => core,
+		Err(err) => return HttpResponse::NotFound().body(err.to_string()),
+	};
+
 	let queue = core.queue();
 
 	if !queue.is_subscribed(id) {
 		return HttpResponse::Unauthorized().body("Not subscribed");
 	}
 
+	if request.sequenced {
+		return match queue.get_sequenced(id, QUEUE_TIMEOUT) {
+			Ok(message) => HttpResponse::Ok().msgpack(message),
+			Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+		};
+	}
+
 	match queue.get_timeout(id) {
 		Ok(message) => HttpResponse::Ok().msgpack(message),
 		Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
@@ -0,0 +1,93 @@
+use actix_web::{
+	get,
+	web::{Bytes, Data, Query},
+	HttpRequest, HttpResponse, Responder,
+};
+use futures_util::{stream, StreamExt};
+use log::{error, trace};
+
+use crate::{
+	constants::STREAM_KEEP_ALIVE,
+	server::{AuthRequest, Cores, Message, SequencedMessage},
+};
+
+/// Streams queue messages as server-sent events, alternative to long-polling `/read`.
+/// Messages are pulled from the queue only when the client is ready to receive them,
+/// reconnecting clients receive messages sent after `Last-Event-ID` again
+#[get("/stream")]
+async fn main(request: Query<AuthRequest>, http_request: HttpRequest, cores: Data<Cores>) -> impl Responder {
+	trace!("Received request: stream");
+
+	let id = request.client_id;
+	let core = match cores.get(id) {
+		Ok(core) => core,
+		Err(err) => return HttpResponse::NotFound().body(err.to_string()),
+	};
+
+	let queue = core.queue();
+
+	if !queue.is_subscribed(id) {
+		return HttpResponse::Unauthorized().body("Not subscribed");
+	}
+
+	// Ask the client to reconnect after one second if the connection drops
+	let mut initial = vec![Bytes::from_static(b"retry: 1000\n\n")];
+
+	let last_event_id = http_request
+		.headers()
+		.get("Last-Event-ID")
+		.and_then(|value| value.to_str().ok())
+		.and_then(|value| value.parse::<u64>().ok());
+
+	if let Some(sequence) = last_event_id {
+		match queue.redeliver(id, sequence) {
+			Some(messages) => initial.extend(messages.iter().filter_map(format_event)),
+			None => initial.push(Bytes::from(format!(
+				"id: {}\nevent: ResnapshotRequired\ndata: {{}}\n\n",
+				queue.sequence()
+			))),
+		}
+	}
+
+	let events = stream::unfold(Some(queue), move |queue| async move {
+		let queue = queue?;
+
+		match queue.get_sequenced_async(id, STREAM_KEEP_ALIVE).await {
+			Ok(Some(message)) => {
+				let event = format_event(&message).unwrap_or_default();
+
+				// Client is expected to close the connection after being disconnected
+				let queue = if let Message::Disconnect(_) = message.message {
+					None
+				} else {
+					Some(queue)
+				};
+
+				Some((Ok::<_, actix_web::Error>(event), queue))
+			}
+			Ok(None) => Some((Ok(Bytes::from_static(b": keep-alive\n\n")), Some(queue))),
+			// Client unsubscribed
+			Err(_) => None,
+		}
+	});
+
+	HttpResponse::Ok()
+		.content_type("text/event-stream")
+		.insert_header(("Cache-Control", "no-cache"))
+		.streaming(stream::iter(initial.into_iter().map(Ok)).chain(events))
+}
+
+fn format_event(message: &SequencedMessage) -> Option<Bytes> {
+	match serde_json::to_string(&message.message) {
+		Ok(data) => Some(Bytes::from(format!(
+			"id: {}\nevent: {}\ndata: {}\n\n",
+			message.sequence,
+			message.message.kind(),
+			data
+		))),
+		Err(err) => {
+			error!("Failed to serialize message: {}", err);
+			None
+		}
+	}
+}
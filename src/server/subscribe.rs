@@ -1,25 +1,60 @@
-use actix_msgpack::MsgPack;
+use actix_msgpack::{MsgPack, MsgPackResponseBuilder};
 use actix_web::{post, web::Data, HttpResponse, Responder};
 use log::trace;
-use serde::Deserialize;
-use std::sync::Arc;
+use serde::{Deserialize, Serialize};
 
-use crate::core::Core;
+use crate::server::{Cores, Target};
 
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 struct Request {
 	client_id: u32,
 	name: String,
+	/// Sequence number of the last message received before reconnecting
+	last_sequence: Option<u64>,
+	/// Project to subscribe to when serving multiple projects
+	#[serde(flatten)]
+	target: Target,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Response {
+	/// Whether missed messages were queued, otherwise full resnapshot is required
+	resumed: bool,
+	sequence: u64,
 }
 
 #[post("/subscribe")]
-async fn main(request: MsgPack<Request>, core: Data<Arc<Core>>) -> impl Responder {
+async fn main(request: MsgPack<Request>, cores: Data<Cores>) -> impl Responder {
 	trace!("Received request: subscribe");
 
-	let subscribed = core.queue().subscribe(request.client_id, &request.name);
+	let index = if let Some(index) = cores.find(&request.target) {
+		index
+	} else {
+		return HttpResponse::NotFound().body("No project matches the request");
+	};
+
+	let queue = cores.get_index(index).queue();
+
+	if let Some(sequence) = request.last_sequence {
+		return match queue.resume(request.client_id, &request.name, sequence) {
+			Ok(resumed) => {
+				cores.route(request.client_id, index);
+
+				HttpResponse::Ok().msgpack(Response {
+					resumed,
+					sequence: queue.sequence(),
+				})
+			}
+			Err(_) => HttpResponse::BadRequest().body("Already subscribed"),
+		};
+	}
+
+	let subscribed = queue.subscribe(request.client_id, &request.name);
 
 	if subscribed.is_ok() {
+		cores.route(request.client_id, index);
 		HttpResponse::Ok().body("Subscribed successfully")
 	} else {
 		HttpResponse::BadRequest().body("Already subscribed")
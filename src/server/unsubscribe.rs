@@ -1,17 +1,19 @@
 use actix_msgpack::MsgPack;
 use actix_web::{post, web::Data, HttpResponse, Responder};
 use log::trace;
-use std::sync::Arc;
 
-use crate::{core::Core, server::AuthRequest};
+use crate::server::{AuthRequest, Cores};
 
 #[post("/unsubscribe")]
-async fn main(request: MsgPack<AuthRequest>, core: Data<Arc<Core>>) -> impl Responder {
+async fn main(request: MsgPack<AuthRequest>, cores: Data<Cores>) -> impl Responder {
 	trace!("Received request: unsubscribe");
 
-	let unsubscribed = core.queue().unsubscribe(request.client_id);
+	let unsubscribed = cores
+		.get(request.client_id)
+		.and_then(|core| core.queue().unsubscribe(request.client_id));
 
 	if unsubscribed.is_ok() {
+		cores.unroute(request.client_id);
 		HttpResponse::Ok().body("Unsubscribed successfully")
 	} else {
 		HttpResponse::BadRequest().body("Not subscribed")
@@ -1,15 +1,18 @@
 use actix_msgpack::MsgPack;
 use actix_web::{post, web::Data, HttpResponse, Responder};
 use log::trace;
-use std::sync::Arc;
 
-use crate::core::{processor::WriteRequest, Core};
+use crate::{core::processor::WriteRequest, server::Cores};
 
 #[post("/write")]
-async fn main(request: MsgPack<WriteRequest>, core: Data<Arc<Core>>) -> impl Responder {
+async fn main(request: MsgPack<WriteRequest>, cores: Data<Cores>) -> impl Responder {
 	trace!("Received request: write");
 
 	let request = request.0;
+	let core = match cores.get(request.client_id) {
+		Ok(core) => core,
+		Err(err) => return HttpResponse::NotFound().body(err.to_string()),
+	};
 
 	if !core.queue().is_subscribed(request.client_id) {
 		return HttpResponse::Unauthorized().body("Not subscribed");
@@ -17,5 +20,6 @@ async fn main(request: MsgPack<WriteRequest>, core: Data<Arc<Core>>) -> impl Res
 
 	core.processor().write(request);
 
-	HttpResponse::Ok().body("Written changes successfully")
+	// Per-instance results are pushed later as `SyncbackResult` message
+	HttpResponse::Ok().body("Queued changes successfully")
 }
@@ -1,15 +1,18 @@
 use anyhow::{Context, Result};
 use log::{debug, trace, warn};
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fs, process, thread};
+use serde_json::json;
+use std::{collections::HashMap, fs, path::Path, process, thread};
 
-use crate::util;
+use crate::{logger, util};
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 pub struct Session {
 	pub pid: u32,
 	pub host: Option<String>,
 	pub port: Option<u16>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub token: Option<String>,
 }
 
 impl Session {
@@ -22,6 +25,10 @@ impl Session {
 
 		None
 	}
+
+	pub fn get_auth_header(&self) -> Option<String> {
+		self.token.as_ref().map(|token| format!("Bearer {}", token))
+	}
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -45,7 +52,7 @@ fn get_sessions() -> Result<Sessions> {
 		active_sessions: HashMap::new(),
 	};
 
-	fs::write(path, toml::to_string(&sessions)?)?;
+	write_sessions(&path, &sessions)?;
 
 	Ok(sessions)
 }
@@ -53,17 +60,61 @@ fn get_sessions() -> Result<Sessions> {
 fn set_sessions(sessions: &Sessions) -> Result<()> {
 	let path = util::get_argon_dir()?.join("sessions.toml");
 
-	fs::write(path, toml::to_string(sessions)?)?;
+	write_sessions(&path, sessions)
+}
+
+/// Writes session data readable only by the owner, as it contains session tokens
+fn write_sessions(path: &Path, sessions: &Sessions) -> Result<()> {
+	let contents = toml::to_string(sessions)?;
+
+	#[cfg(not(target_os = "windows"))]
+	{
+		use std::{
+			io::Write,
+			os::unix::fs::{OpenOptionsExt, PermissionsExt},
+		};
+
+		let mut file = fs::OpenOptions::new()
+			.write(true)
+			.create(true)
+			.truncate(true)
+			.mode(0o600)
+			.open(path)?;
+
+		// Mode is only applied when the file is created
+		file.set_permissions(fs::Permissions::from_mode(0o600))?;
+		file.write_all(contents.as_bytes())?;
+	}
+
+	#[cfg(target_os = "windows")]
+	fs::write(path, contents)?;
 
 	Ok(())
 }
 
-pub fn add(id: Option<String>, host: Option<String>, port: Option<u16>, pid: u32, run_async: bool) -> Result<()> {
+pub fn add(
+	id: Option<String>,
+	host: Option<String>,
+	port: Option<u16>,
+	token: Option<String>,
+	pid: u32,
+	run_async: bool,
+) -> Result<()> {
 	let mut sessions = get_sessions()?;
 
-	let session = Session { host, port, pid };
+	let session = Session { host, port, token, pid };
 	let id = id.unwrap_or(generate_id(&sessions));
 
+	logger::emit(
+		"session",
+		json!({
+			"id": id,
+			"host": session.host,
+			"port": session.port,
+			"pid": session.pid,
+		}),
+	);
+
 	sessions.last_session.clone_from(&id);
 	sessions.active_sessions.insert(id, session.clone());
 
@@ -1,12 +1,21 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use colored::Colorize;
 use directories::UserDirs;
 use env_logger::WriteStyle;
 use log::LevelFilter;
-use rbx_dom_weak::types::Variant;
+use rbx_dom_weak::{
+	types::{Ref, Variant},
+	WeakDom,
+};
 use rbx_reflection::ClassTag;
 use roblox_install::RobloxStudio;
-use std::{env, path::PathBuf, process::Command};
+use std::{
+	collections::hash_map::DefaultHasher,
+	env,
+	hash::{Hash, Hasher},
+	path::PathBuf,
+	process::Command,
+};
 
 use crate::Properties;
 
@@ -52,6 +61,78 @@ pub fn is_script(class: &str) -> bool {
 	class == "Script" || class == "LocalScript" || class == "ModuleScript"
 }
 
+/// Returns path of the instance relative to the root of the `dom`, e.g. `Workspace/Map/Spawn`,
+/// `/` and `\` in instance names are escaped with `\`
+pub fn get_instance_path(dom: &WeakDom, id: Ref) -> Option<String> {
+	let mut names = vec![];
+	let mut instance = dom.get_by_ref(id)?;
+
+	while instance.referent() != dom.root_ref() {
+		names.push(instance.name.replace('\\', "\\\\").replace('/', "\\/"));
+		instance = dom.get_by_ref(instance.parent())?;
+	}
+
+	names.reverse();
+
+	Some(names.join("/"))
+}
+
+/// Finds instance by its `path`, relative to the root of the `dom`
+/// or to the `from` instance if the path starts with `.` or `..`,
+/// fails if the path is ambiguous because of same-named siblings
+pub fn find_instance(dom: &WeakDom, from: Ref, path: &str) -> Result<Ref> {
+	let mut segments = vec![String::new()];
+	let mut chars = path.chars();
+
+	while let Some(char) = chars.next() {
+		match char {
+			'\\' => segments.last_mut().unwrap().extend(chars.next()),
+			'/' => segments.push(String::new()),
+			char => segments.last_mut().unwrap().push(char),
+		}
+	}
+
+	segments.retain(|segment| !segment.is_empty());
+
+	let mut id = if matches!(segments.first().map(String::as_str), Some(".") | Some("..")) {
+		from
+	} else {
+		dom.root_ref()
+	};
+
+	for segment in &segments {
+		let Some(instance) = dom.get_by_ref(id) else {
+			bail!("instance {} does not exist", path);
+		};
+
+		id = match segment.as_str() {
+			"." => id,
+			".." => instance.parent(),
+			name => {
+				let mut matching = instance
+					.children()
+					.iter()
+					.filter(|child| dom.get_by_ref(**child).is_some_and(|child| child.name == name));
+
+				match (matching.next(), matching.next()) {
+					(Some(child), None) => *child,
+					(Some(_), Some(_)) => bail!(
+						"path {} is ambiguous, there are multiple instances named {}",
+						path,
+						name
+					),
+					(None, _) => bail!("instance {} does not exist", path),
+				}
+			}
+		};
+	}
+
+	match dom.get_by_ref(id) {
+		Some(instance) => Ok(instance.referent()),
+		None => bail!("instance {} does not exist", path),
+	}
+}
+
 /// Kills the process with the given `pid`
 pub fn kill_process(pid: u32) {
 	#[cfg(not(target_os = "windows"))]
@@ -98,6 +179,13 @@ pub fn process_exists(pid: u32) -> bool {
 	}
 }
 
+/// Returns hash of the file contents, only valid within the current process
+pub fn hash(contents: &[u8]) -> u64 {
+	let mut hasher = DefaultHasher::new();
+	contents.hash(&mut hasher);
+	hasher.finish()
+}
+
 /// Returns progress bar styling
 pub fn get_progress_style() -> (String, String) {
 	let mut template = match env_log_style() {
@@ -147,6 +235,18 @@ pub fn env_yes() -> bool {
 	yes == "1"
 }
 
+/// Returns the `RUST_USE_VIRTUAL_FS` environment variable
+pub fn env_use_virtual() -> bool {
+	let use_virtual = env::var("RUST_USE_VIRTUAL_FS").unwrap_or("0".to_owned());
+	use_virtual == "1"
+}
+
+/// Returns the `RUST_OUTPUT_JSON` environment variable
+pub fn env_output_json() -> bool {
+	let output_json = env::var("RUST_OUTPUT_JSON").unwrap_or("0".to_owned());
+	output_json == "1"
+}
+
 /// Return line of code count from snapshot's properties
 pub fn count_loc_from_properties(properties: &Properties) -> usize {
 	let mut loc = 0;
@@ -1,6 +1,9 @@
 use crossbeam_channel::Receiver;
 use log::trace;
-use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use notify::{
+	event::{ModifyKind, RenameMode},
+	EventKind, RecommendedWatcher, RecursiveMode, Watcher,
+};
 use notify_debouncer_full::{new_debouncer, DebouncedEvent, Debouncer, FileIdMap};
 use std::{
 	io::{self, Result},
@@ -13,17 +16,13 @@ use std::{
 #[cfg(target_os = "macos")]
 use notify::event::DataChange;
 
-#[cfg(not(target_os = "windows"))]
-use notify::event::ModifyKind;
-
 #[cfg(target_os = "linux")]
 use {
-	notify::event::{AccessKind, AccessMode, RenameMode},
+	notify::event::{AccessKind, AccessMode},
 	std::path::PathBuf,
 };
 
 use super::VfsEvent;
-use crate::constants::SYNCBACK_DEBOUNCE_TIME;
 
 #[cfg(target_os = "linux")]
 const DEBOUNCE_TIME: Duration = Duration::from_micros(500);
@@ -34,6 +33,16 @@ macro_rules! event_path {
 	};
 }
 
+/// Renames paired by the debouncer using file IDs carry both paths
+macro_rules! rename_event {
+	($event:expr) => {
+		VfsEvent::Rename {
+			from: $event.paths[0].to_owned(),
+			to: $event.paths[1].to_owned(),
+		}
+	};
+}
+
 #[cfg(target_os = "linux")]
 struct DebounceContext {
 	time: Instant,
@@ -42,7 +51,7 @@ struct DebounceContext {
 
 pub struct VfsDebouncer {
 	inner: Debouncer<RecommendedWatcher, FileIdMap>,
-	pause_state: Arc<RwLock<(bool, Instant)>>,
+	is_paused: Arc<RwLock<bool>>,
 	receiver: Receiver<VfsEvent>,
 }
 
@@ -51,10 +60,10 @@ impl VfsDebouncer {
 		let (inner_sender, inner_receiver) = mpsc::channel();
 		let (sender, receiver) = crossbeam_channel::unbounded();
 
-		let debouncer = new_debouncer(Duration::from_millis(100), None, inner_sender, false).unwrap();
+		let debouncer = new_debouncer(Duration::from_millis(100), None, inner_sender, true).unwrap();
 
-		let pause_state = Arc::new(RwLock::new((false, Instant::now())));
-		let local_pause_state = pause_state.clone();
+		let is_paused = Arc::new(RwLock::new(false));
+		let local_is_paused = is_paused.clone();
 
 		Builder::new()
 			.name("debouncer".to_owned())
@@ -66,9 +75,9 @@ impl VfsDebouncer {
 				};
 
 				for events in inner_receiver {
-					let (is_paused, timestamp) = *local_pause_state.read().unwrap();
-
-					if is_paused || timestamp.elapsed() < SYNCBACK_DEBOUNCE_TIME {
+					// Echoes of our own writes are recognized by
+					// the processor using file content hashes
+					if *local_is_paused.read().unwrap() {
 						continue;
 					}
 
@@ -91,7 +100,7 @@ impl VfsDebouncer {
 
 		Self {
 			inner: debouncer,
-			pause_state,
+			is_paused,
 			receiver,
 		}
 	}
@@ -117,11 +126,11 @@ impl VfsDebouncer {
 	}
 
 	pub fn pause(&mut self) {
-		*self.pause_state.write().unwrap() = (true, Instant::now());
+		*self.is_paused.write().unwrap() = true;
 	}
 
 	pub fn resume(&mut self) {
-		*self.pause_state.write().unwrap() = (false, Instant::now());
+		*self.is_paused.write().unwrap() = false;
 	}
 
 	pub fn receiver(&self) -> Receiver<VfsEvent> {
@@ -151,6 +160,7 @@ fn debounce(event: &DebouncedEvent) -> Option<VfsEvent> {
 			}
 		}
 		EventKind::Modify(kind) => match kind {
+			ModifyKind::Name(RenameMode::Both) => Some(rename_event!(event)),
 			ModifyKind::Name(_) => {
 				let path = event_path!(event);
 
@@ -187,8 +197,10 @@ fn debounce(event: &DebouncedEvent, context: &mut DebounceContext) -> Option<Vfs
 		EventKind::Modify(ModifyKind::Name(mode)) => match mode {
 			RenameMode::From => Some(VfsEvent::Delete(event_path!(event))),
 			RenameMode::To => Some(VfsEvent::Create(event_path!(event))),
+			RenameMode::Both => Some(rename_event!(event)),
 			_ => None,
 		},
+		EventKind::Remove(_) => Some(VfsEvent::Delete(event_path!(event))),
 		EventKind::Access(kind) => {
 			if kind == AccessKind::Close(AccessMode::Write) {
 				let duration = event.time.duration_since(context.time);
@@ -212,6 +224,7 @@ fn debounce(event: &DebouncedEvent) -> Option<VfsEvent> {
 	match event.kind {
 		EventKind::Create(_) => Some(VfsEvent::Create(event_path!(event))),
 		EventKind::Remove(_) => Some(VfsEvent::Delete(event_path!(event))),
+		EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => Some(rename_event!(event)),
 		EventKind::Modify(_) => Some(VfsEvent::Write(event_path!(event))),
 		_ => None,
 	}
@@ -0,0 +1,116 @@
+use std::{
+	collections::HashMap,
+	io::Result,
+	path::{Path, PathBuf},
+};
+
+use super::VfsBackend;
+
+/// Original state of the modified path
+#[derive(Debug, Clone, PartialEq)]
+pub enum Backup {
+	Missing,
+	Directory,
+	File(Vec<u8>),
+}
+
+/// Records original state of every path before it's modified,
+/// so a batch of changes can be undone if one of them fails
+#[derive(Debug, Default)]
+pub struct Journal {
+	backups: HashMap<PathBuf, Backup>,
+	written: HashMap<PathBuf, u64>,
+}
+
+impl Journal {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Records the path with all its descendants and
+	/// ancestors that do not exist yet, unless already recorded
+	pub fn record(&mut self, path: &Path, backend: &dyn VfsBackend) {
+		for ancestor in path.ancestors().skip(1) {
+			if ancestor.as_os_str().is_empty() || backend.exists(ancestor) {
+				break;
+			}
+
+			self.backups.entry(ancestor.to_owned()).or_insert(Backup::Missing);
+		}
+
+		self.record_tree(path, backend);
+	}
+
+	fn record_tree(&mut self, path: &Path, backend: &dyn VfsBackend) {
+		if self.backups.contains_key(path) {
+			return;
+		}
+
+		if backend.is_dir(path) {
+			self.backups.insert(path.to_owned(), Backup::Directory);
+
+			for child in backend.read_dir(path).unwrap_or_default() {
+				self.record_tree(&child, backend);
+			}
+		} else if let Ok(contents) = backend.read(path) {
+			self.backups.insert(path.to_owned(), Backup::File(contents));
+		} else {
+			self.backups.insert(path.to_owned(), Backup::Missing);
+		}
+	}
+
+	/// Records content hash of the written file
+	pub fn written(&mut self, path: &Path, hash: u64) {
+		self.written.insert(path.to_owned(), hash);
+	}
+
+	/// Returns content hashes of all files written during the transaction
+	pub fn take_written(&mut self) -> HashMap<PathBuf, u64> {
+		std::mem::take(&mut self.written)
+	}
+
+	/// Returns original state of all recorded paths
+	pub fn into_backups(self) -> HashMap<PathBuf, Backup> {
+		self.backups
+	}
+
+	/// Restores all recorded paths to their original state
+	pub fn restore(self, backend: &mut dyn VfsBackend) -> Result<()> {
+		let mut paths: Vec<&PathBuf> = self.backups.keys().collect();
+		paths.sort();
+
+		// Remove created paths first, deepest ones first
+		for path in paths.iter().rev() {
+			if let Backup::Missing = self.backups[*path] {
+				if backend.exists(path) {
+					backend.remove(path)?;
+				}
+			}
+		}
+
+		// Then restore directories and files, parents first
+		for path in paths {
+			match &self.backups[path] {
+				Backup::Missing => {}
+				Backup::Directory => {
+					if backend.is_file(path) {
+						backend.remove(path)?;
+					}
+
+					if !backend.is_dir(path) {
+						backend.create_dir(path)?;
+					}
+				}
+				Backup::File(contents) => {
+					if backend.is_dir(path) {
+						backend.remove(path)?;
+					}
+
+					backend.write(path, contents)?;
+				}
+			}
+		}
+
+		Ok(())
+	}
+}
@@ -61,6 +61,7 @@ impl VfsBackend for MemBackend {
 	}
 
 	fn write(&mut self, path: &Path, contents: &[u8]) -> Result<()> {
+		let is_new = !self.inner.contains_key(path);
 		let entry = self.inner.entry(path.to_owned()).or_insert(VfsEntry::File(vec![]));
 
 		match entry {
@@ -68,6 +69,12 @@ impl VfsBackend for MemBackend {
 			VfsEntry::Directory(_) => return not_file(path),
 		}
 
+		if is_new {
+			if let Some(VfsEntry::Directory(children)) = path.parent().and_then(|parent| self.inner.get_mut(parent)) {
+				children.push(path.to_owned());
+			}
+		}
+
 		Ok(())
 	}
 
@@ -97,18 +104,38 @@ impl VfsBackend for MemBackend {
 	}
 
 	fn rename(&mut self, from: &Path, to: &Path) -> Result<()> {
-		let entry = self.inner.remove(from);
-
-		match entry {
-			Some(entry) => {
-				self.inner.insert(to.to_owned(), entry);
+		if !self.inner.contains_key(from) {
+			return not_found(from);
+		}
 
-				if let Some(VfsEntry::Directory(children)) = self.inner.get_mut(from.parent().unwrap()) {
-					children.retain(|p| p != from);
-					children.push(to.to_owned());
+		let moved: Vec<PathBuf> = self
+			.inner
+			.keys()
+			.filter(|path| path.starts_with(from))
+			.cloned()
+			.collect();
+		let move_path = |path: &Path| match path.strip_prefix(from) {
+			Ok(suffix) if !suffix.as_os_str().is_empty() => to.join(suffix),
+			_ => to.to_owned(),
+		};
+
+		for path in moved {
+			let entry = match self.inner.remove(&path).unwrap() {
+				VfsEntry::Directory(children) => {
+					VfsEntry::Directory(children.iter().map(|child| move_path(child)).collect())
 				}
-			}
-			None => return not_found(from),
+				file => file,
+			};
+
+			self.inner.insert(move_path(&path), entry);
+		}
+
+		if let Some(VfsEntry::Directory(children)) = from.parent().and_then(|parent| self.inner.get_mut(parent)) {
+			children.retain(|path| path != from);
+		}
+
+		if let Some(VfsEntry::Directory(children)) = to.parent().and_then(|parent| self.inner.get_mut(parent)) {
+			children.push(to.to_owned());
 		}
 
 		Ok(())
@@ -125,6 +152,10 @@ impl VfsBackend for MemBackend {
 			_ => {}
 		}
 
+		if let Some(VfsEntry::Directory(children)) = path.parent().and_then(|parent| self.inner.get_mut(parent)) {
+			children.retain(|child| child != path);
+		}
+
 		Ok(())
 	}
 
@@ -1,15 +1,22 @@
-use crossbeam_channel::Receiver;
+use crossbeam_channel::{Receiver, Sender};
 use std::{
 	io::Result,
 	path::{Path, PathBuf},
-	sync::Mutex,
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		Arc, Mutex, MutexGuard,
+	},
+	thread::Builder,
+	time::Duration,
 };
 
-use self::{mem_backend::MemBackend, std_backend::StdBackend};
-use crate::lock;
+use self::{journal::Journal, mem_backend::MemBackend, poll_backend::PollBackend, std_backend::StdBackend};
+use crate::{argon_warn, config::Config, lock, util};
 
 pub mod debouncer;
+pub mod journal;
 pub mod mem_backend;
+pub mod poll_backend;
 pub mod std_backend;
 
 #[derive(Debug, Clone)]
@@ -17,6 +24,7 @@ pub enum VfsEvent {
 	Create(PathBuf),
 	Delete(PathBuf),
 	Write(PathBuf),
+	Rename { from: PathBuf, to: PathBuf },
 }
 
 pub trait VfsBackend: Send {
@@ -42,27 +50,132 @@ pub trait VfsBackend: Send {
 }
 
 impl VfsEvent {
+	/// Returns path of the event, or the new path in case of rename
 	pub fn path(&self) -> &Path {
 		match self {
 			VfsEvent::Create(path) | VfsEvent::Delete(path) | VfsEvent::Write(path) => path.as_ref(),
+			VfsEvent::Rename { to, .. } => to.as_ref(),
 		}
 	}
 }
 
+/// Receiver of VFS events that can be paused
+/// without affecting other listeners of the same VFS
+pub struct Listener {
+	receiver: Receiver<VfsEvent>,
+	paused: Arc<AtomicBool>,
+}
+
+impl Listener {
+	pub fn receiver(&self) -> &Receiver<VfsEvent> {
+		&self.receiver
+	}
+
+	/// Drops all events emitted until [`Listener::resume`] is called
+	pub fn pause(&self) {
+		self.paused.store(true, Ordering::SeqCst);
+	}
+
+	pub fn resume(&self) {
+		self.paused.store(false, Ordering::SeqCst);
+	}
+}
+
+struct ListenerSender {
+	sender: Sender<VfsEvent>,
+	paused: Arc<AtomicBool>,
+}
+
+/// Batch of changes that can be rolled back, only one
+/// transaction can be active at a time across all cores
+pub struct Transaction<'a> {
+	vfs: &'a Vfs,
+	_guard: MutexGuard<'a, ()>,
+}
+
+impl Transaction<'_> {
+	/// Keeps all changes made during the transaction and
+	/// returns the journal with original state of every path
+	pub fn commit(self) -> Journal {
+		lock!(self.vfs.journal).take().unwrap_or_default()
+	}
+
+	/// Restores all paths modified during the transaction
+	pub fn rollback(self) -> Result<()> {
+		let journal = lock!(self.vfs.journal).take();
+
+		match journal {
+			Some(journal) => journal.restore(lock!(self.vfs.inner).as_mut()),
+			None => Ok(()),
+		}
+	}
+}
+
+impl Drop for Transaction<'_> {
+	fn drop(&mut self) {
+		lock!(self.vfs.journal).take();
+	}
+}
+
 pub struct Vfs {
 	inner: Mutex<Box<dyn VfsBackend>>,
+	listeners: Arc<Mutex<Vec<ListenerSender>>>,
+	journal: Mutex<Option<Journal>>,
+	transaction: Mutex<()>,
 }
 
 impl Vfs {
 	pub fn new(watch: bool) -> Self {
-		Self {
-			inner: Mutex::new(Box::new(StdBackend::new(watch))),
+		if !watch {
+			return Self::from_backend(Box::new(StdBackend::new(false)));
+		}
+
+		let config = Config::new();
+
+		match config.watcher.to_lowercase().as_str() {
+			"poll" => Self::new_poll(Duration::from_millis(config.poll_interval), config.poll_hash),
+			watcher => {
+				if watcher != "native" {
+					argon_warn!("Unknown file watcher: {}, using native instead", watcher);
+				}
+
+				Self::from_backend(Box::new(StdBackend::new(true)))
+			}
 		}
 	}
 
+	pub fn new_poll(interval: Duration, hash: bool) -> Self {
+		Self::from_backend(Box::new(PollBackend::new(interval, hash)))
+	}
+
 	pub fn new_virtual() -> Self {
+		Self::from_backend(Box::new(MemBackend::new()))
+	}
+
+	fn from_backend(backend: Box<dyn VfsBackend>) -> Self {
+		let receiver = backend.receiver();
+		let listeners: Arc<Mutex<Vec<ListenerSender>>> = Arc::new(Mutex::new(Vec::new()));
+
+		// Forward every event to all listeners, so
+		// multiple cores can share the same watcher
+		let senders = listeners.clone();
+
+		Builder::new()
+			.name("vfs".to_owned())
+			.spawn(move || {
+				for event in receiver {
+					lock!(senders).retain(|listener| {
+						listener.paused.load(Ordering::SeqCst) || listener.sender.send(event.clone()).is_ok()
+					});
+				}
+			})
+			.unwrap();
+
 		Self {
-			inner: Mutex::new(Box::new(MemBackend::new())),
+			inner: Mutex::new(backend),
+			listeners,
+			journal: Mutex::new(None),
+			transaction: Mutex::new(()),
 		}
 	}
 
@@ -79,19 +192,38 @@ impl Vfs {
 	}
 
 	pub fn write(&self, path: &Path, contents: &[u8]) -> Result<()> {
-		lock!(self.inner).write(path, contents)
+		let mut inner = lock!(self.inner);
+
+		self.record(path, inner.as_ref());
+		inner.write(path, contents)?;
+
+		if let Some(journal) = lock!(self.journal).as_mut() {
+			journal.written(path, util::hash(contents));
+		}
+
+		Ok(())
 	}
 
 	pub fn create_dir(&self, path: &Path) -> Result<()> {
-		lock!(self.inner).create_dir(path)
+		let mut inner = lock!(self.inner);
+
+		self.record(path, inner.as_ref());
+		inner.create_dir(path)
 	}
 
 	pub fn rename(&self, from: &Path, to: &Path) -> Result<()> {
-		lock!(self.inner).rename(from, to)
+		let mut inner = lock!(self.inner);
+
+		self.record(from, inner.as_ref());
+		self.record(to, inner.as_ref());
+		inner.rename(from, to)
 	}
 
 	pub fn remove(&self, path: &Path) -> Result<()> {
-		lock!(self.inner).remove(path)
+		let mut inner = lock!(self.inner);
+
+		self.record(path, inner.as_ref());
+		inner.remove(path)
 	}
 
 	pub fn exists(&self, path: &Path) -> bool {
@@ -114,6 +246,43 @@ impl Vfs {
 		lock!(self.inner).unwatch(path)
 	}
 
+	/// Watches the nearest existing ancestor of the path that does
+	/// not exist yet, so its creation emits [`VfsEvent::Create`]
+	pub fn watch_missing(&self, path: &Path) -> Result<()> {
+		let mut inner = lock!(self.inner);
+
+		match path.ancestors().skip(1).find(|ancestor| inner.is_dir(ancestor)) {
+			Some(ancestor) => inner.watch(ancestor, false),
+			None => Ok(()),
+		}
+	}
+
+	/// Starts recording original state of all paths modified
+	/// from now on, so the changes can be rolled back. Waits
+	/// for the transaction of any other core to finish first
+	pub fn begin(&self) -> Transaction<'_> {
+		let guard = lock!(self.transaction);
+
+		*lock!(self.journal) = Some(Journal::new());
+
+		Transaction {
+			vfs: self,
+			_guard: guard,
+		}
+	}
+
+	/// Records original state of the path that is about
+	/// to be modified without going through the VFS
+	pub fn track(&self, path: &Path) {
+		self.record(path, lock!(self.inner).as_ref());
+	}
+
+	fn record(&self, path: &Path, backend: &dyn VfsBackend) {
+		if let Some(journal) = lock!(self.journal).as_mut() {
+			journal.record(path, backend);
+		}
+	}
+
 	pub fn pause(&self) {
 		lock!(self.inner).pause()
 	}
@@ -122,7 +291,16 @@ impl Vfs {
 		lock!(self.inner).resume()
 	}
 
-	pub fn receiver(&self) -> Receiver<VfsEvent> {
-		lock!(self.inner).receiver()
+	/// Returns new listener that gets all events emitted after this call
+	pub fn listener(&self) -> Listener {
+		let (sender, receiver) = crossbeam_channel::unbounded();
+		let paused = Arc::new(AtomicBool::new(false));
+
+		lock!(self.listeners).push(ListenerSender {
+			sender,
+			paused: paused.clone(),
+		});
+
+		Listener { receiver, paused }
 	}
 }
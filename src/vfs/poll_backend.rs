@@ -0,0 +1,281 @@
+use crossbeam_channel::{Receiver, Sender};
+use log::{trace, warn};
+use std::{
+	collections::BTreeMap,
+	fs,
+	io::Result,
+	path::{Path, PathBuf},
+	sync::{Arc, Mutex},
+	thread::{self, Builder},
+	time::{Duration, SystemTime},
+};
+
+use super::{std_backend::StdBackend, VfsBackend, VfsEvent};
+use crate::{lock, util};
+
+/// State of a single path found during the scan
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+	is_dir: bool,
+	modified: Option<SystemTime>,
+	size: u64,
+	hash: Option<u64>,
+}
+
+#[derive(Default)]
+struct State {
+	/// Watched paths and whether they are watched recursively
+	roots: Vec<(PathBuf, bool)>,
+	/// Entries found during the last scan
+	entries: BTreeMap<PathBuf, Entry>,
+	/// Whether events are ignored
+	is_paused: bool,
+}
+
+/// Backend that detects changes by periodically scanning watched paths,
+/// for file systems where native events are not available (network
+/// drives, container bind mounts, WSL paths to Windows drives)
+pub struct PollBackend {
+	inner: StdBackend,
+	state: Arc<Mutex<State>>,
+	sender: Sender<VfsEvent>,
+	receiver: Receiver<VfsEvent>,
+	hash: bool,
+}
+
+impl PollBackend {
+	pub fn new(interval: Duration, hash: bool) -> Self {
+		let backend = Self::manual(hash);
+
+		let state = backend.state.clone();
+		let sender = backend.sender.clone();
+
+		Builder::new()
+			.name("poller".to_owned())
+			.spawn(move || loop {
+				thread::sleep(interval);
+				poll(&state, &sender, hash);
+			})
+			.unwrap();
+
+		backend
+	}
+
+	/// Creates backend that scans watched paths only when `poll` is called
+	pub fn manual(hash: bool) -> Self {
+		let (sender, receiver) = crossbeam_channel::unbounded();
+
+		Self {
+			inner: StdBackend::new(false),
+			state: Arc::new(Mutex::new(State::default())),
+			sender,
+			receiver,
+			hash,
+		}
+	}
+
+	/// Scans watched paths once and emits events for all changes since the previous scan
+	pub fn poll(&self) {
+		poll(&self.state, &self.sender, self.hash);
+	}
+}
+
+impl VfsBackend for PollBackend {
+	fn read(&self, path: &Path) -> Result<Vec<u8>> {
+		self.inner.read(path)
+	}
+
+	fn read_to_string(&self, path: &Path) -> Result<String> {
+		self.inner.read_to_string(path)
+	}
+
+	fn read_dir(&self, path: &Path) -> Result<Vec<PathBuf>> {
+		self.inner.read_dir(path)
+	}
+
+	fn write(&mut self, path: &Path, contents: &[u8]) -> Result<()> {
+		self.inner.write(path, contents)
+	}
+
+	fn create_dir(&mut self, path: &Path) -> Result<()> {
+		self.inner.create_dir(path)
+	}
+
+	fn rename(&mut self, from: &Path, to: &Path) -> Result<()> {
+		self.inner.rename(from, to)
+	}
+
+	fn remove(&mut self, path: &Path) -> Result<()> {
+		self.unwatch(path)?;
+		self.inner.remove(path)
+	}
+
+	fn exists(&self, path: &Path) -> bool {
+		self.inner.exists(path)
+	}
+
+	fn is_dir(&self, path: &Path) -> bool {
+		self.inner.is_dir(path)
+	}
+
+	fn is_file(&self, path: &Path) -> bool {
+		self.inner.is_file(path)
+	}
+
+	fn watch(&mut self, path: &Path, recursive: bool) -> Result<()> {
+		let mut state = lock!(self.state);
+
+		if state
+			.roots
+			.iter()
+			.any(|(p, r)| (*r && path.starts_with(p)) || (p == path && !recursive))
+		{
+			return Ok(());
+		}
+
+		state.roots.retain(|(p, _)| p != path);
+		state.roots.push((path.to_owned(), recursive));
+
+		// Paths that already exist should not be reported as created
+		let mut entries = BTreeMap::new();
+		scan(path, recursive, self.hash, &mut entries);
+
+		state.entries.extend(entries);
+
+		Ok(())
+	}
+
+	fn unwatch(&mut self, path: &Path) -> Result<()> {
+		let mut state = lock!(self.state);
+
+		state.roots.retain(|(p, _)| !p.starts_with(path));
+		state.entries.retain(|p, _| !p.starts_with(path));
+
+		Ok(())
+	}
+
+	fn pause(&mut self) {
+		lock!(self.state).is_paused = true;
+	}
+
+	fn resume(&mut self) {
+		lock!(self.state).is_paused = false;
+	}
+
+	fn receiver(&self) -> Receiver<VfsEvent> {
+		self.receiver.clone()
+	}
+}
+
+/// Scans all watched paths and emits events for every difference
+/// from the previous scan, unless the backend is paused
+fn poll(state: &Mutex<State>, sender: &Sender<VfsEvent>, hash: bool) {
+	let roots = lock!(state).roots.clone();
+
+	let mut entries = BTreeMap::new();
+
+	for (root, recursive) in &roots {
+		scan(root, *recursive, hash, &mut entries);
+	}
+
+	let mut state = lock!(state);
+
+	// Roots could have changed during the scan
+	if state.roots != roots {
+		return;
+	}
+
+	let old_entries = std::mem::replace(&mut state.entries, entries);
+
+	if state.is_paused {
+		return;
+	}
+
+	let mut events = vec![];
+
+	for (path, entry) in &state.entries {
+		match old_entries.get(path) {
+			// Creation of the parent covers its children
+			None if !is_new_parent(path, &state.entries, &old_entries) => {
+				events.push(VfsEvent::Create(path.to_owned()))
+			}
+			Some(old) if old.is_dir != entry.is_dir => events.push(VfsEvent::Create(path.to_owned())),
+			Some(old) if !entry.is_dir && is_modified(old, entry) => events.push(VfsEvent::Write(path.to_owned())),
+			_ => {}
+		}
+	}
+
+	for path in old_entries.keys() {
+		if !state.entries.contains_key(path) && !is_new_parent(path, &old_entries, &state.entries) {
+			events.push(VfsEvent::Delete(path.to_owned()));
+		}
+	}
+
+	for event in events {
+		trace!("Polled event: {:?}", event);
+		sender.send(event).ok();
+	}
+}
+
+/// Records the path and its children, descending into
+/// subdirectories only when watched recursively
+fn scan(path: &Path, recursive: bool, hash: bool, entries: &mut BTreeMap<PathBuf, Entry>) {
+	if !scan_entry(path, hash, entries) {
+		return;
+	}
+
+	match fs::read_dir(path) {
+		Ok(children) => {
+			for child in children.flatten() {
+				let child = child.path();
+
+				if recursive {
+					scan(&child, true, hash, entries);
+				} else {
+					scan_entry(&child, hash, entries);
+				}
+			}
+		}
+		Err(err) => warn!("Failed to scan directory {}: {}", path.display(), err),
+	}
+}
+
+/// Records a single path, returns whether it is a directory
+fn scan_entry(path: &Path, hash: bool, entries: &mut BTreeMap<PathBuf, Entry>) -> bool {
+	let metadata = match fs::metadata(path) {
+		Ok(metadata) => metadata,
+		Err(_) => return false,
+	};
+
+	let content_hash = if hash && metadata.is_file() {
+		fs::read(path).ok().map(|contents| util::hash(&contents))
+	} else {
+		None
+	};
+
+	entries.insert(
+		path.to_owned(),
+		Entry {
+			is_dir: metadata.is_dir(),
+			modified: metadata.modified().ok(),
+			size: metadata.len(),
+			hash: content_hash,
+		},
+	);
+
+	metadata.is_dir()
+}
+
+fn is_modified(old: &Entry, new: &Entry) -> bool {
+	match (old.hash, new.hash) {
+		(Some(old), Some(new)) => old != new,
+		_ => old.modified != new.modified || old.size != new.size,
+	}
+}
+
+/// Whether the parent of the path was also added (or removed) since
+/// the previous scan, so there is no need to report the path itself
+fn is_new_parent(path: &Path, entries: &BTreeMap<PathBuf, Entry>, old_entries: &BTreeMap<PathBuf, Entry>) -> bool {
+	path.parent()
+		.is_some_and(|parent| entries.contains_key(parent) && !old_entries.contains_key(parent))
+}
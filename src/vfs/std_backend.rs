@@ -11,7 +11,7 @@ use crate::config::Config;
 pub struct StdBackend {
 	watching: bool,
 	debouncer: VfsDebouncer,
-	watched_paths: Vec<PathBuf>,
+	watched_paths: Vec<(PathBuf, bool)>,
 }
 
 impl StdBackend {
@@ -82,12 +82,23 @@ impl VfsBackend for StdBackend {
 	fn watch(&mut self, path: &Path, recursive: bool) -> Result<()> {
 		let path = path.to_owned();
 
-		if !self.watching || self.watched_paths.iter().any(|p| path.starts_with(p)) {
+		if !self.watching
+			|| self
+				.watched_paths
+				.iter()
+				.any(|(p, r)| (*r && path.starts_with(p)) || (*p == path && !recursive))
+		{
 			return Ok(());
 		}
 
+		// Directory watched only for its direct children now has to be watched recursively
+		if let Some(index) = self.watched_paths.iter().position(|(p, _)| *p == path) {
+			self.debouncer.unwatch(&path).ok();
+			self.watched_paths.remove(index);
+		}
+
 		self.debouncer.watch(&path, recursive)?;
-		self.watched_paths.push(path);
+		self.watched_paths.push((path, recursive));
 
 		Ok(())
 	}
@@ -99,7 +110,7 @@ impl VfsBackend for StdBackend {
 
 		let path = path.to_owned();
 
-		self.watched_paths.retain(|p| {
+		self.watched_paths.retain(|(p, _)| {
 			let unwatch = p.starts_with(&path);
 
 			if unwatch {
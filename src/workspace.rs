@@ -13,7 +13,7 @@ use crate::{
 	config::Config,
 	ext::PathExt,
 	program::{Program, ProgramName},
-	util,
+	project, util,
 };
 
 #[derive(Debug)]
@@ -157,7 +157,7 @@ pub fn init_ts(workspace: WorkspaceConfig) -> Result<Option<PathBuf>> {
 		_ => "init",
 	};
 
-	if project.get_name().ends_with(".project.json") {
+	if project::is_project(&project) {
 		project = workspace
 			.project
 			.parent()
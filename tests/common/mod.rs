@@ -0,0 +1,26 @@
+use argon::{core::Core, project::Project, vfs::Vfs};
+use std::{fs, path::Path, sync::Arc};
+use tempfile::TempDir;
+
+/// Returns unique directory that is removed when dropped
+pub fn temp_dir() -> TempDir {
+	tempfile::Builder::new().prefix("argon-").tempdir().unwrap()
+}
+
+/// Creates project with a single `Main` script in the given directory
+/// and returns its core with `Studio` client subscribed as `1`
+pub fn new_core(dir: &Path, vfs: &Arc<Vfs>) -> Core {
+	fs::create_dir_all(dir.join("src")).unwrap();
+	fs::write(dir.join("src/Main.luau"), "return 1").unwrap();
+	fs::write(
+		dir.join("default.project.json"),
+		r#"{"name": "Test", "tree": {"$path": "src"}}"#,
+	)
+	.unwrap();
+
+	let project = Project::load(&dir.join("default.project.json")).unwrap();
+	let core = Core::with_vfs(project, vfs.clone()).unwrap();
+
+	core.queue().subscribe(1, "Studio").unwrap();
+	core
+}
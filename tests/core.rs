@@ -0,0 +1,1409 @@
+mod common;
+
+mod diff {
+	use argon::core::{diff, meta::SyncbackFilter, snapshot::Snapshot, tree::Tree};
+	use rbx_dom_weak::{
+		types::{Variant, Vector3},
+		InstanceBuilder, WeakDom,
+	};
+	use std::collections::HashMap;
+
+	#[test]
+	fn filter_properties() {
+		let properties = HashMap::from([
+			(String::from("Size"), Variant::Vector3(Vector3::new(4.0, 1.2, 2.0))),
+			(String::from("Anchored"), Variant::Bool(true)),
+			(String::from("UniqueId"), Variant::String(String::from("unique"))),
+			(String::from("Unknown"), Variant::Bool(true)),
+		]);
+
+		assert_eq!(
+			diff::filter_properties("Part", &properties),
+			HashMap::from([(String::from("Anchored"), Variant::Bool(true))])
+		);
+	}
+
+	#[test]
+	fn changes() {
+		let tree = Tree::new(Snapshot::new().with_name("Root").with_children(vec![
+				Snapshot::new().with_name("Handle").with_class("Part"),
+				Snapshot::new()
+					.with_name("Main")
+					.with_class("Script")
+					.with_properties(HashMap::from([(
+						String::from("Source"),
+						Variant::String(String::from("print('Hello')")),
+					)])),
+			]));
+
+		let mut dom = WeakDom::new(InstanceBuilder::new("Folder").with_name("Root"));
+		let root = dom.root_ref();
+
+		dom.insert(
+			root,
+			InstanceBuilder::new("Part")
+				.with_name("Handle")
+				.with_property("Anchored", true),
+		);
+		dom.insert(
+			root,
+			InstanceBuilder::new("Folder")
+				.with_name("Assets")
+				.with_child(InstanceBuilder::new("StringValue").with_name("Value")),
+		);
+
+		let changes = diff::diff(&tree, tree.root_ref(), &dom, root);
+
+		assert_eq!(changes.additions.len(), 1);
+		assert_eq!(changes.additions[0].name, "Assets");
+		assert_eq!(changes.additions[0].children.len(), 1);
+		assert!(changes.additions[0].children[0].id.is_some());
+
+		assert_eq!(changes.updates.len(), 1);
+		assert_eq!(
+			changes.updates[0].properties,
+			Some(HashMap::from([(String::from("Anchored"), Variant::Bool(true))]))
+		);
+
+		assert_eq!(changes.removals.len(), 1);
+		assert_eq!(tree.get_instance(changes.removals[0]).unwrap().name, "Main");
+
+		let report = diff::report(&changes, &tree, &SyncbackFilter::default());
+
+		assert_eq!(report.added[0].path, "Main");
+		assert_eq!(report.removed[0].path, "Assets");
+		assert_eq!(report.modified[0].path, "Handle");
+		assert_eq!(report.modified[0].properties[0].name, "Anchored");
+		assert_eq!(report.modified[0].properties[0].old, Some(serde_json::json!(true)));
+		assert_eq!(report.modified[0].properties[0].new, None);
+
+		let filter = SyncbackFilter {
+			ignore_classes: vec![String::from("Script")],
+			ignore_properties: vec![String::from("Anchored")],
+			..SyncbackFilter::default()
+		};
+
+		let report = diff::report(&changes, &tree, &filter);
+
+		assert!(report.added.is_empty());
+		assert!(report.modified.is_empty());
+		assert_eq!(report.removed.len(), 1);
+	}
+}
+
+mod refs {
+	use argon::{
+		core::{diff, meta::Meta, snapshot::Snapshot, tree::Tree},
+		diagnostics,
+		project::Project,
+		util,
+	};
+	use rbx_dom_weak::{
+		types::{Ref, Variant},
+		InstanceBuilder, WeakDom,
+	};
+	use std::collections::HashMap;
+
+	fn model(primary_part: &str) -> Snapshot {
+		Snapshot::new()
+			.with_name("Car")
+			.with_class("Model")
+			.with_meta(Meta::new().with_refs(HashMap::from([(String::from("PrimaryPart"), primary_part.to_owned())])))
+			.with_children(vec![Snapshot::new().with_name("Body").with_class("Part")])
+	}
+
+	#[test]
+	fn paths() {
+		let mut dom = WeakDom::new(InstanceBuilder::new("DataModel"));
+		let workspace = dom.insert(dom.root_ref(), InstanceBuilder::new("Workspace"));
+		let model = dom.insert(workspace, InstanceBuilder::new("Model").with_name("Car"));
+		let body = dom.insert(model, InstanceBuilder::new("Part").with_name("Body"));
+
+		assert_eq!(
+			util::get_instance_path(&dom, body),
+			Some(String::from("Workspace/Car/Body"))
+		);
+		assert_eq!(util::get_instance_path(&dom, dom.root_ref()), Some(String::new()));
+
+		assert_eq!(util::find_instance(&dom, body, "Workspace/Car/Body").ok(), Some(body));
+		assert_eq!(util::find_instance(&dom, model, "./Body").ok(), Some(body));
+		assert_eq!(util::find_instance(&dom, body, "../..").ok(), Some(workspace));
+		assert!(util::find_instance(&dom, model, "Workspace/Truck").is_err());
+
+		// Slashes in names are escaped
+		let wheel = dom.insert(model, InstanceBuilder::new("Part").with_name("Wheel/Front"));
+		let path = util::get_instance_path(&dom, wheel).unwrap();
+
+		assert_eq!(path, "Workspace/Car/Wheel\\/Front");
+		assert_eq!(util::find_instance(&dom, body, &path).ok(), Some(wheel));
+
+		// Same-named siblings make the path ambiguous
+		dom.insert(model, InstanceBuilder::new("Part").with_name("Body"));
+
+		let err = util::find_instance(&dom, model, "./Body").unwrap_err();
+		assert!(err.to_string().contains("ambiguous"));
+	}
+
+	#[test]
+	fn resolve() {
+		let tree = Tree::new(Snapshot::new().with_name("Root").with_children(vec![model("Car/Body")]));
+
+		let car = tree.root().children()[0];
+		let body = tree.get_instance(car).unwrap().children()[0];
+
+		assert_eq!(
+			tree.get_instance(car).unwrap().properties.get("PrimaryPart"),
+			Some(&Variant::Ref(body))
+		);
+
+		let tree = Tree::new(
+			Snapshot::new()
+				.with_name("Root")
+				.with_children(vec![model("Car/Wheel")]),
+		);
+		let car = tree.root().children()[0];
+
+		assert_eq!(tree.get_instance(car).unwrap().properties.get("PrimaryPart"), None);
+
+		// Ambiguous references are left unresolved and reported
+		let mut car = model("Car/Body");
+		car.children.push(Snapshot::new().with_name("Body").with_class("Part"));
+
+		let tree = Tree::new(Snapshot::new().with_name("Root").with_children(vec![car]));
+		let car = tree.root().children()[0];
+		let project: Project = serde_json::from_str(r#"{ "tree": {} }"#).unwrap();
+
+		assert_eq!(tree.get_instance(car).unwrap().properties.get("PrimaryPart"), None);
+		assert!(diagnostics::check_tree(&tree, &project)
+			.iter()
+			.any(|diagnostic| diagnostic.message.contains("Cannot resolve PrimaryPart property")));
+	}
+
+	#[test]
+	fn diff() {
+		let tree = Tree::new(Snapshot::new().with_name("Root").with_children(vec![model("Car/Body")]));
+		let car = tree.root().children()[0];
+
+		let mut dom = WeakDom::new(InstanceBuilder::new("Folder").with_name("Root"));
+		let model = dom.insert(dom.root_ref(), InstanceBuilder::new("Model").with_name("Car"));
+		let body = dom.insert(model, InstanceBuilder::new("Part").with_name("Body"));
+
+		dom.get_by_ref_mut(model)
+			.unwrap()
+			.properties
+			.insert(String::from("PrimaryPart"), Variant::Ref(body));
+
+		assert!(diff::diff(&tree, tree.root_ref(), &dom, dom.root_ref()).is_empty());
+
+		let wheel = dom.insert(model, InstanceBuilder::new("Part").with_name("Wheel"));
+
+		dom.get_by_ref_mut(model)
+			.unwrap()
+			.properties
+			.insert(String::from("PrimaryPart"), Variant::Ref(wheel));
+
+		let changes = diff::diff(&tree, tree.root_ref(), &dom, dom.root_ref());
+
+		assert_eq!(changes.additions.len(), 1);
+		assert_eq!(changes.updates.len(), 1);
+		assert_eq!(changes.updates[0].id, car);
+		assert_eq!(
+			changes.updates[0].properties.as_ref().unwrap().get("PrimaryPart"),
+			Some(&Variant::Ref(changes.additions[0].id))
+		);
+	}
+
+	#[test]
+	fn relink() {
+		let link = Snapshot::new()
+			.with_name("Link")
+			.with_class("ObjectValue")
+			.with_meta(Meta::new().with_refs(HashMap::from([(String::from("Value"), String::from("Car/Body"))])));
+
+		let mut tree = Tree::new(
+			Snapshot::new()
+				.with_name("Root")
+				.with_children(vec![model("./Body"), link]),
+		);
+
+		let car = tree.root().children()[0];
+		let link = tree.root().children()[1];
+		let body = tree.get_instance(car).unwrap().children()[0];
+
+		// Both relative and absolute refs no longer resolve
+		tree.get_instance_mut(body).unwrap().name = String::from("Hull");
+
+		assert_eq!(tree.stale_referrers(&[body], false).len(), 2);
+		assert_eq!(tree.relink_referrers(&[body]).len(), 2);
+		assert_eq!(
+			tree.get_instance(link).unwrap().properties.get("Value"),
+			Some(&Variant::Ref(Ref::none()))
+		);
+
+		// Unresolved refs are resolved once the target is created
+		let body = tree.insert_instance(Snapshot::new().with_name("Body").with_class("Part"), car);
+
+		assert!(tree.stale_referrers(&[body], false).is_empty());
+		assert_eq!(tree.relink_referrers(&[body]).len(), 2);
+		assert_eq!(
+			tree.get_instance(link).unwrap().properties.get("Value"),
+			Some(&Variant::Ref(body))
+		);
+
+		tree.remove_instance(body);
+
+		assert_eq!(tree.relink_referrers(&[]).len(), 2);
+		assert_eq!(
+			tree.get_instance(link).unwrap().properties.get("Value"),
+			Some(&Variant::Ref(Ref::none()))
+		);
+	}
+}
+
+mod stable_ids {
+	use argon::core::{
+		meta::{Meta, Source},
+		snapshot::Snapshot,
+		tree::Tree,
+	};
+	use std::path::Path;
+
+	fn tree(sibling: &str) -> Tree {
+		let file = |name: &str| {
+			Snapshot::new()
+				.with_name(name)
+				.with_class("ModuleScript")
+				.with_meta(Meta::new().with_source(Source::file(&Path::new("src").join(name))))
+		};
+
+		let mut custom = Snapshot::new().with_name("Custom").with_class("Folder");
+		custom.meta.id = Some(String::from("custom"));
+
+		let snapshot = Snapshot::new().with_name("Root").with_children(vec![
+			file(sibling),
+			file("Main").with_children(vec![Snapshot::new().with_name("Config").with_class("Configuration")]),
+			custom,
+		]);
+
+		Tree::with_stable_ids(snapshot, true)
+	}
+
+	#[test]
+	fn persist() {
+		let (first, second) = (tree("Utils"), tree("Helpers"));
+
+		let ids = |tree: &Tree| {
+			let children = tree.root().children();
+			let main = tree.get_instance(children[1]).unwrap();
+
+			(
+				tree.root_ref(),
+				children[1],
+				main.children()[0],
+				children[2],
+				children[0],
+			)
+		};
+
+		let (first, second) = (ids(&first), ids(&second));
+
+		assert_eq!(first.0, second.0);
+		assert_eq!(first.1, second.1);
+		assert_eq!(first.2, second.2);
+		assert_eq!(first.3, second.3);
+		assert_ne!(first.4, second.4);
+	}
+}
+
+mod queue {
+	use argon::{
+		constants::QUEUE_HISTORY_SIZE,
+		core::queue::Queue,
+		server::{ExecuteCode, Message},
+	};
+	use std::time::Duration;
+
+	fn code(code: &str) -> ExecuteCode {
+		ExecuteCode { code: code.to_owned() }
+	}
+
+	fn next(queue: &Queue, id: u32) -> Option<String> {
+		match queue.try_get(id, Duration::ZERO).unwrap() {
+			Some(Message::ExecuteCode(message)) => Some(message.code),
+			_ => None,
+		}
+	}
+
+	#[test]
+	fn resume() {
+		let queue = Queue::new();
+
+		queue.subscribe(1, "Studio").unwrap();
+		queue.push(code("first"), None).unwrap();
+
+		assert_eq!(next(&queue, 1), Some(String::from("first")));
+
+		let sequence = queue.sequence();
+		queue.unsubscribe(1).unwrap();
+
+		queue.push(code("second"), None).unwrap();
+		queue.push(code("third"), None).unwrap();
+
+		assert!(queue.resume(1, "Studio", sequence).unwrap());
+		assert_eq!(next(&queue, 1), Some(String::from("second")));
+		assert_eq!(next(&queue, 1), Some(String::from("third")));
+		assert_eq!(next(&queue, 1), None);
+
+		assert_eq!(queue.redeliver(1, sequence).unwrap().len(), 2);
+		assert!(queue.resume(1, "Studio", sequence).is_err());
+	}
+
+	#[test]
+	fn resnapshot() {
+		let queue = Queue::new();
+		let sequence = queue.sequence();
+
+		for index in 0..=QUEUE_HISTORY_SIZE {
+			queue.push(code(&index.to_string()), None).unwrap();
+		}
+
+		assert!(!queue.resume(1, "Studio", sequence).unwrap());
+		assert_eq!(next(&queue, 1), None);
+
+		queue.unsubscribe(1).unwrap();
+
+		assert!(!queue.resume(1, "Studio", queue.sequence() + 1).unwrap());
+	}
+}
+
+mod stream {
+	use argon::{
+		core::Core,
+		server::{self, ExecuteCode, Server},
+		vfs::Vfs,
+	};
+	use std::{
+		io::{BufRead, BufReader},
+		path::Path,
+		sync::Arc,
+		thread,
+		time::Duration,
+	};
+
+	use super::common;
+
+	fn start(dir: &Path) -> (Arc<Core>, String) {
+		let core = Arc::new(common::new_core(dir, &Arc::new(Vfs::new(false))));
+
+		let port = server::get_free_port("localhost", 8100);
+		let server = Server::new(vec![core.clone()], "localhost", port);
+
+		thread::spawn(move || server.start().unwrap());
+
+		(core, format!("http://localhost:{}/stream?clientId=1", port))
+	}
+
+	/// Connects to the stream and reads the given number of events as their IDs and kinds
+	fn read(url: &str, last_event_id: Option<u64>, count: usize) -> Vec<(u64, String)> {
+		let client = reqwest::blocking::Client::new();
+
+		let response = loop {
+			let mut request = client.get(url);
+
+			if let Some(id) = last_event_id {
+				request = request.header("Last-Event-ID", id.to_string());
+			}
+
+			// Server might not be listening yet
+			match request.send() {
+				Ok(response) => break response,
+				Err(_) => thread::sleep(Duration::from_millis(10)),
+			}
+		};
+
+		let mut events = vec![];
+		let mut id = 0;
+
+		for line in BufReader::new(response).lines() {
+			let line = line.unwrap();
+
+			if let Some(value) = line.strip_prefix("id: ") {
+				id = value.parse().unwrap();
+			} else if let Some(kind) = line.strip_prefix("event: ") {
+				events.push((id, kind.to_owned()));
+
+				if events.len() == count {
+					break;
+				}
+			}
+		}
+
+		events
+	}
+
+	#[test]
+	fn redeliver() {
+		let temp = common::temp_dir();
+		let (core, url) = start(temp.path());
+
+		for code in ["first", "second"] {
+			let message = ExecuteCode { code: code.to_owned() };
+			core.queue().push(message, None).unwrap();
+		}
+
+		let events = read(&url, None, 2);
+
+		assert_eq!(events.len(), 2);
+		assert!(events.iter().all(|(_, kind)| kind == "ExecuteCode"));
+
+		// Messages delivered after the last received one are sent again
+		let redelivered = read(&url, Some(events[0].0), 1);
+
+		assert_eq!(redelivered, vec![events[1].clone()]);
+
+		// Messages that are no longer in the history cannot be redelivered
+		let events = read(&url, Some(1), 1);
+
+		assert_eq!(
+			events,
+			vec![(core.queue().sequence(), String::from("ResnapshotRequired"))]
+		);
+	}
+}
+
+mod diagnostics {
+	use argon::{
+		core::{snapshot::Snapshot, tree::Tree},
+		diagnostics::{self, Diagnostic},
+		project::Project,
+	};
+	use rbx_dom_weak::types::Variant;
+	use std::{collections::HashMap, fs};
+
+	use super::common;
+
+	#[test]
+	fn check_tree() {
+		let tree = Tree::new(Snapshot::new().with_name("Root").with_children(vec![
+			Snapshot::new().with_name("Part").with_class("Part").with_properties(HashMap::from([(
+				String::from("Bogus"),
+				Variant::Bool(true),
+			)])),
+			Snapshot::new().with_name("Part").with_class("Part"),
+			Snapshot::new().with_name("Widget").with_class("Frobnicator"),
+		]));
+
+		let project: Project =
+			serde_json::from_str(r#"{ "tree": {}, "syncRules": [{ "type": "ModuleScript", "pattern": "*.never" }] }"#)
+				.unwrap();
+
+		let messages: Vec<(bool, String)> = diagnostics::check_tree(&tree, &project)
+			.into_iter()
+			.map(|diagnostic| (diagnostic.is_error(), diagnostic.message))
+			.collect();
+
+		assert_eq!(
+			messages,
+			vec![
+				(
+					false,
+					String::from("Multiple children named Part cannot be synced back")
+				),
+				(false, String::from("Unknown property Bogus of class Part")),
+				(true, String::from("Unknown class Frobnicator")),
+				(false, String::from("Sync rule never matches any file")),
+			]
+		);
+	}
+
+	#[test]
+	fn line() {
+		let temp = common::temp_dir();
+		let json = temp.path().join("default.project.json");
+		let toml = temp.path().join("default.project.toml");
+
+		// Keys mentioned in comments or other values are not matched
+		fs::write(
+			&json,
+			"{\n\t// syncRules are below\n\t\"name\": \"syncRules\",\n\t\"syncRules\": [\n\t\t{},\n\t\t{},\n\t],\n}",
+		)
+		.unwrap();
+		fs::write(
+			&toml,
+			"# syncRules are below\nname = \"syncRules\"\n\n[[syncRules]]\n\n[[syncRules]]\ntype = \"Folder\"\n",
+		)
+		.unwrap();
+
+		let line = |path, json_path| Diagnostic::warning("", path).with_json_path(json_path).line();
+
+		assert_eq!(line(&json, "syncRules"), Some(4));
+		assert_eq!(line(&json, "syncRules/1"), Some(6));
+		assert_eq!(line(&toml, "syncRules/1"), Some(6));
+		assert_eq!(line(&toml, "syncRules/1/type"), Some(7));
+
+		// Closest existing ancestor is used for missing keys
+		assert_eq!(line(&json, "syncRules/5"), Some(4));
+	}
+}
+
+mod matching {
+	use argon::{
+		core::{meta::Context, processor::read, tree::Tree},
+		middleware::new_snapshot,
+		vfs::Vfs,
+	};
+	use std::path::Path;
+
+	#[test]
+	fn rename() {
+		let vfs = Vfs::new_virtual();
+
+		vfs.create_dir(Path::new("src")).unwrap();
+		vfs.write(Path::new("src/Old.luau"), b"return 1").unwrap();
+		vfs.write(Path::new("src/Other.luau"), b"return 2").unwrap();
+
+		let snapshot = new_snapshot(Path::new("src"), &Context::default(), &vfs)
+			.unwrap()
+			.unwrap();
+		let mut tree = Tree::new(snapshot);
+
+		let script = tree.get_ids(Path::new("src/Old.luau")).unwrap()[0];
+
+		vfs.rename(Path::new("src/Old.luau"), Path::new("src/New.luau"))
+			.unwrap();
+
+		let changes = read::process_changes(tree.root_ref(), &mut tree, &vfs);
+
+		assert!(changes.additions.is_empty());
+		assert!(changes.removals.is_empty());
+		assert_eq!(changes.updates.len(), 1);
+		assert_eq!(changes.updates[0].id, script);
+		assert_eq!(changes.updates[0].name.as_deref(), Some("New"));
+
+		assert_eq!(tree.get_instance(script).unwrap().name, "New");
+		assert_eq!(tree.get_ids(Path::new("src/New.luau")), Some(&vec![script]));
+		assert!(tree.get_ids(Path::new("src/Old.luau")).is_none());
+	}
+
+	#[test]
+	fn duplicates() {
+		let model = |first: u32, second: u32| {
+			format!(
+				r#"{{
+					"className": "Model",
+					"children": [
+						{{ "name": "Part", "className": "Part", "properties": {{ "Size": [{0}, {0}, {0}] }} }},
+						{{ "name": "Part", "className": "Part", "properties": {{ "Size": [{1}, {1}, {1}] }} }}
+					]
+				}}"#,
+				first, second
+			)
+		};
+
+		let vfs = Vfs::new_virtual();
+		let path = Path::new("Car.model.json");
+
+		vfs.write(path, model(1, 2).as_bytes()).unwrap();
+
+		let snapshot = new_snapshot(path, &Context::default(), &vfs).unwrap().unwrap();
+		let mut tree = Tree::new(snapshot);
+
+		let children = tree.root().children().to_owned();
+
+		vfs.write(path, model(2, 1).as_bytes()).unwrap();
+
+		let changes = read::process_changes(tree.root_ref(), &mut tree, &vfs);
+
+		assert!(changes.is_empty());
+		assert_eq!(tree.root().children(), children);
+	}
+}
+
+mod rename {
+	use argon::{
+		core::{meta::Context, processor::read, tree::Tree},
+		middleware::new_snapshot,
+		vfs::Vfs,
+	};
+	use std::path::Path;
+
+	fn project(vfs: &Vfs) -> Tree {
+		vfs.create_dir(Path::new("src/Shared")).unwrap();
+		vfs.create_dir(Path::new("src/Client")).unwrap();
+		vfs.write(Path::new("src/Shared/Util.luau"), b"return 1").unwrap();
+		vfs.write(Path::new("src/Shared/Data.luau"), b"return 2").unwrap();
+
+		let snapshot = new_snapshot(Path::new("src"), &Context::default(), vfs)
+			.unwrap()
+			.unwrap();
+
+		Tree::new(snapshot)
+	}
+
+	#[test]
+	fn file() {
+		let vfs = Vfs::new_virtual();
+		let mut tree = project(&vfs);
+
+		let (from, to) = (Path::new("src/Shared/Util.luau"), Path::new("src/Shared/Helpers.luau"));
+		let script = tree.get_ids(from).unwrap()[0];
+
+		vfs.rename(from, to).unwrap();
+
+		let changes = read::process_rename(from, to, &mut tree, &vfs).unwrap();
+
+		assert!(changes.additions.is_empty());
+		assert!(changes.removals.is_empty());
+		assert_eq!(changes.updates.len(), 1);
+		assert_eq!(changes.updates[0].name.as_deref(), Some("Helpers"));
+		assert_eq!(changes.updates[0].parent, None);
+
+		assert_eq!(tree.get_ids(to), Some(&vec![script]));
+		assert!(tree.get_ids(from).is_none());
+	}
+
+	#[test]
+	fn folder() {
+		let vfs = Vfs::new_virtual();
+		let mut tree = project(&vfs);
+
+		let (from, to) = (Path::new("src/Shared"), Path::new("src/Client/Shared"));
+		let folder = tree.get_ids(from).unwrap()[0];
+		let client = tree.get_ids(Path::new("src/Client")).unwrap()[0];
+		let script = tree.get_ids(Path::new("src/Shared/Util.luau")).unwrap()[0];
+
+		vfs.rename(from, to).unwrap();
+
+		let changes = read::process_rename(from, to, &mut tree, &vfs).unwrap();
+
+		assert!(changes.additions.is_empty());
+		assert!(changes.removals.is_empty());
+		assert_eq!(changes.updates.len(), 1);
+		assert_eq!(changes.updates[0].id, folder);
+		assert_eq!(changes.updates[0].parent, Some(client));
+		assert_eq!(changes.updates[0].name, None);
+
+		assert_eq!(tree.get_instance(folder).unwrap().parent(), client);
+		assert_eq!(
+			tree.get_ids(Path::new("src/Client/Shared/Util.luau")),
+			Some(&vec![script])
+		);
+		assert!(tree.get_ids(Path::new("src/Shared/Util.luau")).is_none());
+	}
+
+	#[test]
+	fn unsynced() {
+		let vfs = Vfs::new_virtual();
+		let mut tree = project(&vfs);
+
+		let (from, to) = (Path::new("src/Shared/Util.luau.tmp"), Path::new("src/Shared/Util.luau"));
+
+		assert!(read::process_rename(from, to, &mut tree, &vfs).is_none());
+	}
+}
+
+mod hashes {
+	use argon::{
+		core::{meta::Context, tree::Tree},
+		middleware::new_snapshot,
+		util,
+		vfs::Vfs,
+	};
+	use std::path::Path;
+
+	#[test]
+	fn unchanged() {
+		let vfs = Vfs::new_virtual();
+		let path = Path::new("src/Main.luau");
+
+		vfs.create_dir(Path::new("src")).unwrap();
+		vfs.write(path, b"return 1").unwrap();
+
+		let snapshot = new_snapshot(Path::new("src"), &Context::default(), &vfs)
+			.unwrap()
+			.unwrap();
+		let mut tree = Tree::new(snapshot);
+
+		assert!(tree.update_hash(path, util::hash(b"return 1")));
+		assert!(!tree.update_hash(path, util::hash(b"return 1")));
+		assert!(tree.update_hash(path, util::hash(b"return 2")));
+
+		// Paths that are not synced are never skipped
+		assert!(tree.update_hash(Path::new("src/.DS_Store"), 0));
+		assert!(tree.update_hash(Path::new("src/.DS_Store"), 0));
+
+		let id = tree.get_ids(path).unwrap()[0];
+		tree.remove_instance(id);
+
+		assert!(tree.update_hash(path, util::hash(b"return 2")));
+	}
+
+	#[test]
+	fn seeded() {
+		let vfs = Vfs::new_virtual();
+		let path = Path::new("src/Main.luau");
+
+		vfs.create_dir(Path::new("src")).unwrap();
+		vfs.write(path, b"return 1").unwrap();
+
+		let snapshot = new_snapshot(Path::new("src"), &Context::default(), &vfs)
+			.unwrap()
+			.unwrap();
+		let mut tree = Tree::new(snapshot);
+		tree.seed_hashes(&vfs);
+
+		// First save after startup is recognized as unchanged
+		assert!(!tree.update_hash(path, util::hash(b"return 1")));
+		assert!(tree.update_hash(path, util::hash(b"return 2")));
+	}
+
+	#[test]
+	fn written() {
+		let vfs = Vfs::new_virtual();
+		let path = Path::new("Main.luau");
+
+		let transaction = vfs.begin();
+		vfs.write(path, b"return 1").unwrap();
+
+		let written = transaction.commit().take_written();
+
+		assert_eq!(written.get(path), Some(&util::hash(b"return 1")));
+
+		// Writes outside of the transaction are not recorded
+		vfs.write(path, b"return 2").unwrap();
+
+		assert!(vfs.begin().commit().take_written().is_empty());
+	}
+}
+
+mod journal {
+	use argon::vfs::Vfs;
+	use std::{path::Path, sync::Arc, thread};
+
+	#[test]
+	fn rollback() {
+		let vfs = Vfs::new_virtual();
+
+		vfs.create_dir(Path::new("src/Shared")).unwrap();
+		vfs.write(Path::new("src/Main.luau"), b"return 1").unwrap();
+		vfs.write(Path::new("src/Shared/Util.luau"), b"return 2").unwrap();
+
+		let transaction = vfs.begin();
+
+		vfs.write(Path::new("src/Main.luau"), b"return 3").unwrap();
+		vfs.create_dir(Path::new("src/New/Nested")).unwrap();
+		vfs.write(Path::new("src/New/Nested/Added.luau"), b"").unwrap();
+		vfs.rename(Path::new("src/Shared"), Path::new("src/Renamed")).unwrap();
+		vfs.remove(Path::new("src/Renamed/Util.luau")).unwrap();
+
+		transaction.rollback().unwrap();
+
+		let mut children = vfs.read_dir(Path::new("src")).unwrap();
+		children.sort();
+
+		assert_eq!(children, vec![Path::new("src/Main.luau"), Path::new("src/Shared")]);
+		assert_eq!(vfs.read_to_string(Path::new("src/Main.luau")).unwrap(), "return 1");
+		assert_eq!(
+			vfs.read_to_string(Path::new("src/Shared/Util.luau")).unwrap(),
+			"return 2"
+		);
+		assert!(!vfs.exists(Path::new("src/New")));
+		assert!(!vfs.exists(Path::new("src/Renamed")));
+	}
+
+	#[test]
+	fn commit() {
+		let vfs = Vfs::new_virtual();
+
+		let transaction = vfs.begin();
+		vfs.write(Path::new("Main.luau"), b"return 1").unwrap();
+		transaction.commit();
+
+		vfs.begin().rollback().unwrap();
+
+		assert!(vfs.exists(Path::new("Main.luau")));
+	}
+
+	#[test]
+	fn concurrent() {
+		let vfs = Arc::new(Vfs::new_virtual());
+		let transaction = vfs.begin();
+
+		vfs.write(Path::new("First.luau"), b"return 1").unwrap();
+
+		// Second transaction waits until the first one is finished
+		let thread = {
+			let vfs = vfs.clone();
+
+			thread::spawn(move || {
+				let transaction = vfs.begin();
+				vfs.write(Path::new("Second.luau"), b"return 2").unwrap();
+				transaction.commit().take_written()
+			})
+		};
+
+		transaction.rollback().unwrap();
+
+		let written = thread.join().unwrap();
+
+		assert!(!vfs.exists(Path::new("First.luau")));
+		assert!(vfs.exists(Path::new("Second.luau")));
+		assert_eq!(written.keys().collect::<Vec<_>>(), vec![Path::new("Second.luau")]);
+	}
+}
+
+mod cores {
+	use argon::{
+		core::{changes::Changes, processor::WriteRequest, snapshot::Snapshot, snapshot::UpdatedSnapshot, Core},
+		server::{Cores, Message},
+		vfs::Vfs,
+	};
+	use rbx_dom_weak::types::{Ref, Variant};
+	use std::{collections::HashMap, fs, sync::Arc, time::Duration};
+
+	use super::common::{self, new_core};
+
+	fn add_script(changes: &mut Changes, core: &Core, name: &str) {
+		let snapshot = Snapshot::new()
+			.with_id(Ref::new())
+			.with_name(name)
+			.with_class("ModuleScript")
+			.with_properties(HashMap::from([(
+				String::from("Source"),
+				Variant::String(String::from("return 2")),
+			)]));
+
+		changes.add(snapshot, core.tree().root_ref());
+	}
+
+	fn wait_for_syncback(core: &Core) -> bool {
+		loop {
+			match core.queue().try_get(1, Duration::from_secs(10)).unwrap() {
+				Some(Message::SyncbackResult(message)) => return message.success,
+				Some(_) => continue,
+				None => panic!("Syncback result was not received"),
+			}
+		}
+	}
+
+	#[test]
+	fn shared_vfs() {
+		let temp = common::temp_dir();
+		let dir = temp.path();
+		let vfs = Arc::new(Vfs::new(false));
+
+		let failing = new_core(&dir.join("a"), &vfs);
+		let succeeding = new_core(&dir.join("b"), &vfs);
+
+		// Main.luau can no longer be written, so the whole batch fails
+		let main = failing.tree().get_ids(&dir.join("a/src/Main.luau")).unwrap()[0];
+		fs::remove_file(dir.join("a/src/Main.luau")).unwrap();
+		fs::create_dir(dir.join("a/src/Main.luau")).unwrap();
+
+		let mut changes = Changes::new();
+		add_script(&mut changes, &failing, "Added");
+
+		let mut snapshot = UpdatedSnapshot::new(main);
+		snapshot.properties = Some(HashMap::from([(
+			String::from("Source"),
+			Variant::String(String::from("return 3")),
+		)]));
+		changes.update(snapshot);
+
+		failing.processor().write(WriteRequest { changes, client_id: 1 });
+
+		let mut changes = Changes::new();
+		add_script(&mut changes, &succeeding, "Added");
+
+		succeeding.processor().write(WriteRequest { changes, client_id: 1 });
+
+		assert!(!wait_for_syncback(&failing));
+		assert!(wait_for_syncback(&succeeding));
+
+		// Rollback of one core does not touch files of the other one
+		let read_project = |name: &str| fs::read_to_string(dir.join(name).join("default.project.json")).unwrap();
+
+		assert!(!read_project("a").contains("Added"));
+		assert!(read_project("b").contains("Added"));
+	}
+
+	#[test]
+	fn routing() {
+		let temp = common::temp_dir();
+		let dir = temp.path();
+		let vfs = Arc::new(Vfs::new(false));
+
+		let first = Arc::new(new_core(&dir.join("a"), &vfs));
+		let second = Arc::new(new_core(&dir.join("b"), &vfs));
+
+		// Single project serves all clients
+		let cores = Cores::new(vec![first.clone()]);
+		assert!(cores.get(1).is_ok());
+
+		// Clients that are not routed are rejected instead of falling back to the first project
+		let cores = Cores::new(vec![first, second.clone()]);
+		assert!(cores.get(1).is_err());
+
+		cores.route(1, 1);
+		assert!(Arc::ptr_eq(&cores.get(1).unwrap(), &second));
+	}
+
+	#[test]
+	fn renamed_refs() {
+		let temp = common::temp_dir();
+		let dir = temp.path();
+		let vfs = Arc::new(Vfs::new(false));
+
+		fs::create_dir_all(dir.join("src/Link")).unwrap();
+		fs::write(
+			dir.join("src/Link/init.meta.json"),
+			r#"{"className": "ObjectValue", "properties": {"Value": "Main"}}"#,
+		)
+		.unwrap();
+
+		let core = new_core(dir, &vfs);
+		let main = core.tree().get_ids(&dir.join("src/Main.luau")).unwrap()[0];
+
+		let mut snapshot = UpdatedSnapshot::new(main);
+		snapshot.name = Some(String::from("Entry"));
+
+		let mut changes = Changes::new();
+		changes.update(snapshot);
+
+		core.processor().write(WriteRequest { changes, client_id: 1 });
+
+		assert!(wait_for_syncback(&core));
+
+		// Referencing file is written again with the new path of its target
+		let data = fs::read_to_string(dir.join("src/Link/init.meta.json")).unwrap();
+
+		assert!(data.contains("Entry"));
+		assert!(core.tree().stale_referrers(&[main], true).is_empty());
+	}
+}
+
+mod outcomes {
+	use argon::{
+		core::{
+			changes::Changes,
+			meta::Context,
+			processor::{
+				outcome::{Outcome, Outcomes},
+				write,
+			},
+			snapshot::UpdatedSnapshot,
+			tree::Tree,
+		},
+		middleware::new_snapshot,
+		vfs::Vfs,
+	};
+	use rbx_dom_weak::types::Ref;
+	use std::{fs, path::Path};
+
+	use super::common;
+
+	#[test]
+	fn reported() {
+		let vfs = Vfs::new_virtual();
+
+		vfs.create_dir(Path::new("src")).unwrap();
+		vfs.write(Path::new("src/Main.luau"), b"return 1").unwrap();
+		vfs.write(Path::new("src/Other.luau"), b"return 2").unwrap();
+
+		let snapshot = new_snapshot(Path::new("src"), &Context::default(), &vfs)
+			.unwrap()
+			.unwrap();
+		let mut tree = Tree::new(snapshot);
+
+		let id = tree.get_ids(Path::new("src/Main.luau")).unwrap()[0];
+		let missing = Ref::new();
+
+		let mut snapshot = UpdatedSnapshot::new(id);
+		snapshot.name = Some(String::from("Other"));
+
+		let mut changes = Changes::new();
+		changes.update(snapshot);
+		changes.remove(missing);
+
+		let mut outcomes = Outcomes::new();
+		write::apply_changes(changes, &mut tree, &vfs, &mut outcomes).unwrap();
+
+		let applied = Ref::new();
+		let outcomes = outcomes.complete(&[id, missing, applied], Outcome::Applied);
+
+		assert_eq!(outcomes.len(), 3);
+		assert!(matches!(&outcomes[0].outcome, Outcome::Renamed { name } if name.starts_with("Other_")));
+		assert_eq!(outcomes[0].id, id);
+		assert!(matches!(outcomes[1].outcome, Outcome::Skipped { .. }));
+		assert_eq!(outcomes[1].id, missing);
+		assert_eq!(outcomes[2].outcome, Outcome::Applied);
+	}
+
+	#[test]
+	fn inherited() {
+		let temp = common::temp_dir();
+		let dir = temp.path();
+		let base = r#"{"tree": {"$className": "DataModel", "Workspace": {"Base": {"$className": "Folder"}}}}"#;
+		let place = r#"{"extends": "base.project.json", "tree": {"Workspace": {"Local": {"$className": "Folder"}}}}"#;
+
+		fs::write(dir.join("base.project.json"), base).unwrap();
+		fs::write(dir.join("place.project.json"), place).unwrap();
+
+		let vfs = Vfs::new(false);
+		let snapshot = new_snapshot(&dir.join("place.project.json"), &Context::default(), &vfs)
+			.unwrap()
+			.unwrap();
+		let mut tree = Tree::new(snapshot);
+
+		let workspace = tree.inner().root().children()[0];
+		let find = |tree: &Tree, name: &str| {
+			tree.inner()
+				.get_by_ref(workspace)
+				.unwrap()
+				.children()
+				.iter()
+				.copied()
+				.find(|id| tree.get_instance(*id).unwrap().name == name)
+				.unwrap()
+		};
+
+		let inherited = find(&tree, "Base");
+		let local = find(&tree, "Local");
+
+		let mut changes = Changes::new();
+		changes.remove(inherited);
+		changes.remove(local);
+
+		let mut outcomes = Outcomes::new();
+		write::apply_changes(changes, &mut tree, &vfs, &mut outcomes).unwrap();
+
+		let outcomes = outcomes.complete(&[inherited, local], Outcome::Applied);
+
+		assert!(matches!(&outcomes[0].outcome, Outcome::Skipped { reason } if reason.contains("base project")));
+		assert_eq!(outcomes[1].outcome, Outcome::Applied);
+
+		assert!(tree.exists(inherited));
+		assert!(!tree.exists(local));
+		assert_eq!(fs::read_to_string(dir.join("base.project.json")).unwrap(), base);
+		assert!(!fs::read_to_string(dir.join("place.project.json"))
+			.unwrap()
+			.contains("Local"));
+	}
+}
+
+mod history {
+	use argon::{
+		core::{meta::Context, tree::Tree},
+		history::{self, Change, DiffLine, History},
+		middleware::new_snapshot,
+		vfs::Vfs,
+	};
+	use std::path::Path;
+
+	use super::common;
+
+	#[test]
+	fn record() {
+		let temp = common::temp_dir();
+		let vfs = Vfs::new_virtual();
+
+		vfs.create_dir(Path::new("src")).unwrap();
+		vfs.write(Path::new("src/Main.luau"), b"return 1").unwrap();
+		vfs.write(Path::new("src/Old.luau"), b"return 2").unwrap();
+		vfs.write(Path::new("src/Same.luau"), b"return 3").unwrap();
+
+		let snapshot = new_snapshot(Path::new("src"), &Context::default(), &vfs)
+			.unwrap()
+			.unwrap();
+		let tree = Tree::new(snapshot);
+
+		let transaction = vfs.begin();
+		vfs.write(Path::new("src/Main.luau"), b"return 4").unwrap();
+		vfs.write(Path::new("src/Same.luau"), b"return 3").unwrap();
+		vfs.write(Path::new("src/New.luau"), b"").unwrap();
+		vfs.remove(Path::new("src/Old.luau")).unwrap();
+
+		let history = History::from_dir(Path::new(""), temp.path());
+		let backups = transaction.commit().into_backups();
+		let entry = history.record(backups, &tree, &vfs).unwrap().unwrap();
+
+		let changes = entry
+			.records
+			.iter()
+			.map(|record| (record.path.to_str().unwrap(), record.change))
+			.collect::<Vec<_>>();
+
+		// Unchanged files are not recorded
+		assert_eq!(
+			changes,
+			vec![
+				("src/Main.luau", Change::Modified),
+				("src/New.luau", Change::Created),
+				("src/Old.luau", Change::Removed),
+			]
+		);
+		assert!(entry.records[2].matches("Old"));
+
+		for index in 0..entry.records.len() {
+			history.restore(&entry, index, &vfs).unwrap();
+		}
+
+		assert_eq!(vfs.read_to_string(Path::new("src/Main.luau")).unwrap(), "return 1");
+		assert_eq!(vfs.read_to_string(Path::new("src/Old.luau")).unwrap(), "return 2");
+		assert!(vfs.exists(Path::new("src/New.luau")));
+
+		assert_eq!(history.entries().unwrap().len(), 1);
+		history.prune(0).unwrap();
+		assert!(history.entries().unwrap().is_empty());
+	}
+
+	#[test]
+	fn diff() {
+		assert_eq!(
+			history::diff("a\nb\nc", "a\nc\nd"),
+			vec![
+				DiffLine::Same("a"),
+				DiffLine::Removed("b"),
+				DiffLine::Same("c"),
+				DiffLine::Added("d"),
+			]
+		);
+	}
+}
+
+mod missing {
+	use argon::{
+		core::{
+			meta::{Context, NodePath},
+			processor::read,
+			tree::Tree,
+		},
+		middleware::project::new_snapshot_node,
+		project::{ProjectNode, ProjectPath},
+		vfs::Vfs,
+	};
+	use std::path::{Path, PathBuf};
+
+	#[test]
+	fn created_later() {
+		let vfs = Vfs::new_virtual();
+		let project = Path::new("game/default.project.json");
+
+		vfs.create_dir(Path::new("game")).unwrap();
+
+		let node = ProjectNode {
+			path: Some(ProjectPath::Optional {
+				optional: PathBuf::from("src"),
+			}),
+			..ProjectNode::default()
+		};
+
+		let snapshot = new_snapshot_node("Shared", project, node, NodePath::new(), &Context::default(), &vfs).unwrap();
+		let mut tree = Tree::new(snapshot);
+
+		assert!(tree.root().children().is_empty());
+		assert_eq!(tree.get_project_ids(Path::new("game/src")), vec![tree.root_ref()]);
+
+		vfs.create_dir(Path::new("game/src")).unwrap();
+		vfs.write(Path::new("game/src/main.luau"), b"return 1").unwrap();
+
+		let changes = read::process_changes(tree.root_ref(), &mut tree, &vfs);
+
+		assert_eq!(changes.additions.len(), 1);
+		assert_eq!(changes.additions[0].name, "main");
+		assert!(tree.get_ids(Path::new("game/src/main.luau")).is_some());
+	}
+}
+
+mod build {
+	use argon::{
+		core::{
+			changes::Changes,
+			meta::{Meta, Source},
+			snapshot::{Snapshot, UpdatedSnapshot},
+			Core,
+		},
+		server::SyncChanges,
+		vfs::Vfs,
+	};
+	use rbx_dom_weak::types::Ref;
+	use std::{
+		path::{Path, PathBuf},
+		sync::Arc,
+		thread,
+		time::{Duration, Instant},
+	};
+
+	use super::common;
+
+	fn new_core(dir: &Path) -> Core {
+		let core = common::new_core(dir, &Arc::new(Vfs::new(false)));
+		core.queue().subscribe_internal().unwrap();
+
+		core
+	}
+
+	fn added(path: &Path) -> Changes {
+		let mut changes = Changes::new();
+		let snapshot = Snapshot::new()
+			.with_id(Ref::new())
+			.with_name("Output")
+			.with_meta(Meta::new().with_source(Source::file(path)));
+
+		changes.add(snapshot, Ref::new());
+		changes
+	}
+
+	#[test]
+	fn debounce() {
+		let temp = common::temp_dir();
+		let dir = temp.path();
+		let core = new_core(dir);
+		let queue = core.queue();
+
+		for _ in 0..3 {
+			queue.push(SyncChanges(added(&dir.join("a"))), None).unwrap();
+		}
+
+		// Changes pushed during the debounce window are merged as well
+		let pusher = {
+			let queue = queue.clone();
+			let path = dir.join("b");
+
+			thread::spawn(move || {
+				thread::sleep(Duration::from_millis(20));
+				queue.push(SyncChanges(added(&path)), None).unwrap();
+			})
+		};
+
+		let start = Instant::now();
+		let changes = core.wait_for_changes(Duration::from_millis(200)).unwrap();
+
+		pusher.join().unwrap();
+
+		assert_eq!(changes.additions.len(), 4);
+		assert!(start.elapsed() >= Duration::from_millis(200));
+	}
+
+	#[test]
+	fn filter() {
+		let temp = common::temp_dir();
+		let dir = temp.path();
+		let core = new_core(dir);
+		let main = core.tree().get_ids(&dir.join("src/Main.luau")).unwrap()[0];
+		let output: Vec<PathBuf> = vec![dir.join("src/Test.rbxm")];
+
+		assert!(!core.affects_build(&Changes::new(), &output));
+
+		// Build output written inside the synced directory
+		assert!(!core.affects_build(&added(&output[0]), &output));
+		assert!(core.affects_build(&added(&dir.join("src/Other.luau")), &output));
+
+		// Instance added and removed in the same batch
+		let mut changes = added(&dir.join("src/Other.luau"));
+		changes.remove(changes.additions[0].id);
+
+		assert!(!core.affects_build(&changes, &output));
+
+		// Meta is used by sourcemap only
+		let mut snapshot = UpdatedSnapshot::new(main);
+		snapshot.meta = Some(Meta::new());
+
+		let mut changes = Changes::new();
+		changes.update(snapshot);
+
+		assert!(!core.affects_build(&changes, &output));
+
+		let mut snapshot = UpdatedSnapshot::new(main);
+		snapshot.parent = Some(core.tree().root_ref());
+		changes.update(snapshot);
+
+		assert!(core.affects_build(&changes, &output));
+
+		// Updates of instances that no longer exist
+		let mut changes = Changes::new();
+		let mut snapshot = UpdatedSnapshot::new(Ref::new());
+		snapshot.name = Some(String::from("Removed"));
+		changes.update(snapshot);
+
+		assert!(!core.affects_build(&changes, &output));
+	}
+}
+
+mod virtual_fs {
+	use argon::{core::Core, util};
+	use std::{env, path::Path};
+
+	#[test]
+	fn env() {
+		// Always set by the CLI, even when `--use-virtual` is not passed
+		env::set_var("RUST_USE_VIRTUAL_FS", "0");
+
+		assert!(!util::env_use_virtual());
+
+		let vfs = Core::new_vfs(false);
+
+		assert!(vfs.is_file(&Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml")));
+	}
+}
+
+mod poll {
+	use argon::vfs::{poll_backend::PollBackend, VfsBackend, VfsEvent};
+	use std::{fs, path::PathBuf};
+
+	use super::common;
+
+	fn scan(backend: &PollBackend) -> Vec<(&'static str, PathBuf)> {
+		backend.poll();
+		backend
+			.receiver()
+			.try_iter()
+			.map(|event| match event {
+				VfsEvent::Create(path) => ("create", path),
+				VfsEvent::Write(path) => ("write", path),
+				VfsEvent::Delete(path) => ("delete", path),
+				event => panic!("Unexpected event: {:?}", event),
+			})
+			.collect()
+	}
+
+	#[test]
+	fn events() {
+		let temp = common::temp_dir();
+		let dir = temp.path();
+		let file = dir.join("main.luau");
+
+		fs::write(dir.join("existing.luau"), "return 0").unwrap();
+
+		let mut backend = PollBackend::manual(true);
+		backend.watch(dir, true).unwrap();
+
+		assert!(scan(&backend).is_empty());
+
+		fs::write(&file, "return 1").unwrap();
+		assert_eq!(scan(&backend), vec![("create", file.clone())]);
+
+		fs::write(&file, "return 2").unwrap();
+		assert_eq!(scan(&backend), vec![("write", file.clone())]);
+
+		// Contents are compared, so saving without changes is not reported
+		fs::write(&file, "return 2").unwrap();
+		assert!(scan(&backend).is_empty());
+
+		// Children of created or deleted directory are not reported
+		fs::create_dir(dir.join("nested")).unwrap();
+		fs::write(dir.join("nested/child.luau"), "").unwrap();
+		assert_eq!(scan(&backend), vec![("create", dir.join("nested"))]);
+
+		fs::remove_dir_all(dir.join("nested")).unwrap();
+		assert_eq!(scan(&backend), vec![("delete", dir.join("nested"))]);
+
+		// Changes made while paused are dropped
+		backend.pause();
+		fs::remove_file(&file).unwrap();
+		assert!(scan(&backend).is_empty());
+
+		backend.resume();
+		assert!(scan(&backend).is_empty());
+	}
+}
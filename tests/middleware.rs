@@ -0,0 +1,324 @@
+mod syncback {
+	use argon::{
+		core::snapshot::Snapshot,
+		middleware::{json, json_model, msgpack, rbxm, rbxmx, toml, yaml, Middleware},
+		vfs::Vfs,
+		Properties,
+	};
+	use rbx_dom_weak::{
+		types::{Ref, Variant, Vector3},
+		InstanceBuilder, WeakDom,
+	};
+	use std::{collections::HashMap, path::Path};
+
+	fn source(source: &str) -> Properties {
+		HashMap::from([(String::from("Source"), Variant::String(source.to_owned()))])
+	}
+
+	fn read_source(properties: &Properties) -> String {
+		match properties.get("Source") {
+			Some(Variant::String(source)) => source.to_owned(),
+			_ => panic!("Snapshot has no Source property"),
+		}
+	}
+
+	fn tool() -> (WeakDom, Ref) {
+		let mut dom = WeakDom::new(InstanceBuilder::new("DataModel"));
+
+		let tool = dom.insert(dom.root_ref(), InstanceBuilder::new("Tool").with_name("Sword"));
+		let handle = dom.insert(
+			tool,
+			InstanceBuilder::new("Part")
+				.with_name("Handle")
+				.with_property("Size", Vector3::new(1.0, 4.0, 1.0)),
+		);
+
+		dom.insert(
+			handle,
+			InstanceBuilder::new("Script")
+				.with_name("Damage")
+				.with_property("Source", "print('hit')"),
+		);
+
+		(dom, tool)
+	}
+
+	fn assert_tool(snapshot: &Snapshot) {
+		assert_eq!(snapshot.class, "Tool");
+		assert_eq!(snapshot.children.len(), 1);
+
+		let handle = &snapshot.children[0];
+
+		assert_eq!(handle.name, "Handle");
+		assert_eq!(handle.class, "Part");
+		assert_eq!(
+			handle.properties.get("Size"),
+			Some(&Variant::Vector3(Vector3::new(1.0, 4.0, 1.0)))
+		);
+
+		let script = &handle.children[0];
+
+		assert_eq!(script.name, "Damage");
+		assert_eq!(script.class, "Script");
+		assert_eq!(
+			script.properties.get("Source"),
+			Some(&Variant::String(String::from("print('hit')")))
+		);
+	}
+
+	#[test]
+	fn json_model() {
+		let vfs = Vfs::new_virtual();
+		let path = Path::new("Sword.model.json");
+		let (dom, tool) = tool();
+
+		json_model::write_json_model(&dom, tool, path, &vfs).unwrap();
+
+		assert_tool(&json_model::read_json_model(path, &vfs).unwrap());
+	}
+
+	#[test]
+	fn rbxm() {
+		let vfs = Vfs::new_virtual();
+		let path = Path::new("Sword.rbxm");
+		let (dom, tool) = tool();
+
+		rbxm::write_rbxm(&dom, tool, path, &vfs).unwrap();
+
+		assert_tool(&rbxm::read_rbxm(path, &vfs).unwrap());
+	}
+
+	#[test]
+	fn rbxmx() {
+		let vfs = Vfs::new_virtual();
+		let path = Path::new("Sword.rbxmx");
+		let (dom, tool) = tool();
+
+		rbxmx::write_rbxmx(&dom, tool, path, &vfs).unwrap();
+
+		assert_tool(&rbxmx::read_rbxmx(path, &vfs).unwrap());
+	}
+
+	#[test]
+	fn json() {
+		let vfs = Vfs::new_virtual();
+		let path = Path::new("config.json");
+
+		vfs.write(
+			path,
+			br#"{"name": "Sword", "damage": 12.5, "tags": ["melee", "rare"], "owner": null}"#,
+		)
+		.unwrap();
+
+		let snapshot = json::read_json(path, &vfs).unwrap();
+		json::write_json(snapshot.properties.clone(), path, &vfs).unwrap();
+
+		let json: serde_json::Value = serde_json::from_slice(&vfs.read(path).unwrap()).unwrap();
+
+		assert_eq!(
+			json,
+			serde_json::json!({"name": "Sword", "damage": 12.5, "tags": ["melee", "rare"], "owner": null})
+		);
+		assert_eq!(
+			read_source(&json::read_json(path, &vfs).unwrap().properties),
+			read_source(&snapshot.properties)
+		);
+	}
+
+	#[test]
+	fn json_key_order() {
+		let vfs = Vfs::new_virtual();
+		let path = Path::new("config.json");
+
+		json::write_json(source("return { zeta = 1, alpha = 2, [\"mid\"] = 3 }"), path, &vfs).unwrap();
+
+		let json = vfs.read_to_string(path).unwrap();
+
+		assert!(json.find("zeta") < json.find("alpha"));
+		assert!(json.find("alpha") < json.find("mid"));
+	}
+
+	#[test]
+	fn json_invalid() {
+		let vfs = Vfs::new_virtual();
+		let path = Path::new("config.json");
+
+		assert!(json::write_json(source("return { [1] = true, foo = false }"), path, &vfs).is_err());
+		assert!(json::write_json(source("return { value = math.huge }"), path, &vfs).is_err());
+		assert!(json::write_json(source("return { value = getValue() }"), path, &vfs).is_err());
+		assert!(json::write_json(source("return 5"), path, &vfs).is_err());
+		assert!(!vfs.exists(path));
+	}
+
+	#[test]
+	fn json_literals() {
+		let vfs = Vfs::new_virtual();
+		let path = Path::new("config.json");
+
+		json::write_json(
+			source(
+				r#"return { euro = "\226\130\172", hex = "\xE2\x82\xAC", min = -9223372036854775808, neg = - 0x10 }"#,
+			),
+			path,
+			&vfs,
+		)
+		.unwrap();
+
+		let json: serde_json::Value = serde_json::from_slice(&vfs.read(path).unwrap()).unwrap();
+
+		assert_eq!(
+			json,
+			serde_json::json!({"euro": "€", "hex": "€", "min": i64::MIN, "neg": -16})
+		);
+		assert!(json::write_json(source(r#"return { invalid = "\255" }"#), path, &vfs).is_err());
+	}
+
+	#[test]
+	fn json_shapes() {
+		let vfs = Vfs::new_virtual();
+		let path = Path::new("config.json");
+
+		vfs.write(path, br#"{"empty": {}, "list": [], "nested": [{}]}"#)
+			.unwrap();
+
+		let snapshot = json::read_json(path, &vfs).unwrap();
+		json::write_json(snapshot.properties, path, &vfs).unwrap();
+
+		let json: serde_json::Value = serde_json::from_slice(&vfs.read(path).unwrap()).unwrap();
+		assert_eq!(json, serde_json::json!({"empty": {}, "list": [], "nested": [{}]}));
+
+		// Empty tables without existing value are objects
+		json::write_json(source("return { added = {} }"), path, &vfs).unwrap();
+
+		let json: serde_json::Value = serde_json::from_slice(&vfs.read(path).unwrap()).unwrap();
+		assert_eq!(json, serde_json::json!({"added": {}}));
+
+		json::write_json(source("return { 1, { name = \"Sword\" } }"), path, &vfs).unwrap();
+
+		let json: serde_json::Value = serde_json::from_slice(&vfs.read(path).unwrap()).unwrap();
+		assert_eq!(json, serde_json::json!([1, {"name": "Sword"}]));
+	}
+
+	#[test]
+	fn toml() {
+		let vfs = Vfs::new_virtual();
+		let path = Path::new("balance.toml");
+
+		vfs.write(path, b"speed = 16\nratio = 0.25\n\n[shop]\nitems = [\"a\", \"b\"]\n")
+			.unwrap();
+
+		let snapshot = toml::read_toml(path, &vfs).unwrap();
+		toml::write_toml(snapshot.properties.clone(), path, &vfs).unwrap();
+
+		assert_eq!(
+			read_source(&toml::read_toml(path, &vfs).unwrap().properties),
+			read_source(&snapshot.properties)
+		);
+
+		toml::write_toml(source("return { zeta = 1, alpha = { 2 }, mid = 3 }"), path, &vfs).unwrap();
+
+		let toml = vfs.read_to_string(path).unwrap();
+
+		assert!(toml.find("zeta") < toml.find("alpha"));
+		assert!(toml.find("alpha") < toml.find("mid"));
+
+		assert!(toml::write_toml(source("return { list = { 1, nil, 3 } }"), path, &vfs).is_err());
+		assert!(toml::write_toml(source("return { value = nil }"), path, &vfs).is_err());
+		assert!(toml::write_toml(source("return { [true] = 1 }"), path, &vfs).is_err());
+	}
+
+	#[test]
+	fn yaml() {
+		let vfs = Vfs::new_virtual();
+		let path = Path::new("settings.yaml");
+
+		vfs.write(path, b"title: Game\nlevels:\n  - 1\n  - 2\n10: ten\nenabled: true\n")
+			.unwrap();
+
+		let snapshot = yaml::read_yaml(path, &vfs).unwrap();
+		yaml::write_yaml(snapshot.properties.clone(), path, &vfs).unwrap();
+
+		assert_eq!(
+			read_source(&yaml::read_yaml(path, &vfs).unwrap().properties),
+			read_source(&snapshot.properties)
+		);
+	}
+
+	#[test]
+	fn yaml_shapes() {
+		let vfs = Vfs::new_virtual();
+		let path = Path::new("settings.yaml");
+
+		vfs.write(
+			path,
+			b"empty: {}
+list: []
+",
+		)
+		.unwrap();
+
+		let snapshot = yaml::read_yaml(path, &vfs).unwrap();
+		yaml::write_yaml(snapshot.properties, path, &vfs).unwrap();
+
+		let yaml: serde_yaml::Value = serde_yaml::from_slice(&vfs.read(path).unwrap()).unwrap();
+		assert_eq!(
+			yaml,
+			serde_yaml::from_str::<serde_yaml::Value>("empty: {}\nlist: []").unwrap()
+		);
+
+		yaml::write_yaml(source("return { \"a\", \"b\" }"), path, &vfs).unwrap();
+
+		let yaml: serde_yaml::Value = serde_yaml::from_slice(&vfs.read(path).unwrap()).unwrap();
+		assert_eq!(yaml, serde_yaml::from_str::<serde_yaml::Value>("[a, b]").unwrap());
+	}
+
+	#[test]
+	fn msgpack() {
+		let vfs = Vfs::new_virtual();
+		let path = Path::new("data.msgpack");
+
+		msgpack::write_msgpack(
+			source("-- Generated\nreturn {\n\t[\"hp\"] = 100,\n\t[\"name\"] = 'Boss\\n',\n\t[\"drops\"] = { 1, 2.5, true },\n}"),
+			path,
+			&vfs,
+		)
+		.unwrap();
+
+		let snapshot = msgpack::read_msgpack(path, &vfs).unwrap();
+
+		assert_eq!(
+			read_source(&snapshot.properties),
+			"return {[\"hp\"] = 100,[\"name\"] = \"Boss\\n\",[\"drops\"] = {1,2.5,true,},}"
+		);
+
+		// Existing single precision floats stay single precision
+		let mut writer = Vec::new();
+		rmpv::encode::write_value(
+			&mut writer,
+			&rmpv::Value::Array(vec![rmpv::Value::F32(0.1), rmpv::Value::F64(0.1)]),
+		)
+		.unwrap();
+		vfs.write(path, &writer).unwrap();
+
+		let snapshot = msgpack::read_msgpack(path, &vfs).unwrap();
+		msgpack::write_msgpack(snapshot.properties, path, &vfs).unwrap();
+
+		assert_eq!(
+			rmpv::decode::read_value(&mut vfs.read(path).unwrap().as_slice()).unwrap(),
+			rmpv::Value::Array(vec![rmpv::Value::F32(0.1), rmpv::Value::F64(0.1)])
+		);
+	}
+
+	#[test]
+	fn unsupported() {
+		let vfs = Vfs::new_virtual();
+		let (dom, tool) = tool();
+
+		assert!(Middleware::Project
+			.write(source("return 1"), Path::new("default.project.json"), &vfs)
+			.is_err());
+		assert!(Middleware::JsonModule
+			.write_model(&dom, tool, Path::new("Sword.json"), &vfs)
+			.is_err());
+	}
+}
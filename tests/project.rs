@@ -0,0 +1,251 @@
+mod extends {
+	use argon::project::Project;
+	use std::{fs, path::PathBuf};
+
+	fn workspace(name: &str, files: &[(&str, &str)]) -> PathBuf {
+		let dir = std::env::temp_dir().join(format!("argon-{}-{}", name, std::process::id()));
+
+		for (path, contents) in files {
+			let path = dir.join(path);
+
+			fs::create_dir_all(path.parent().unwrap()).unwrap();
+			fs::write(path, contents).unwrap();
+		}
+
+		dir
+	}
+
+	#[test]
+	fn merge() {
+		let dir = workspace(
+			"extends",
+			&[
+				(
+					"base/base.project.json",
+					r#"{
+						"name": "base",
+						"port": 8000,
+						"placeIds": [1],
+						"globIgnorePaths": ["**/base"],
+						"tree": {
+							"$className": "DataModel",
+							"ReplicatedStorage": {
+								"Shared": { "$path": "shared" },
+								"$properties": { "Name": "Base" }
+							}
+						}
+					}"#,
+				),
+				(
+					"place.project.json",
+					r#"{
+						"extends": "base/base.project.json",
+						"placeIds": [2],
+						"globIgnorePaths": ["**/place"],
+						"tree": {
+							"ReplicatedStorage": {
+								"Client": { "$path": "client" }
+							}
+						}
+					}"#,
+				),
+			],
+		);
+
+		let project = Project::load(&dir.join("place.project.json")).unwrap();
+
+		assert_eq!(project.name, "base");
+		assert_eq!(project.port, Some(8000));
+		assert_eq!(project.place_ids, vec![2]);
+		assert_eq!(project.inherited, vec![dir.join("base/base.project.json")]);
+		assert!(project.is_place());
+
+		assert_eq!(project.ignore_globs.len(), 2);
+		assert!(project.ignore_globs[0].matches("**/place"));
+
+		let storage = &project.node.tree["ReplicatedStorage"];
+
+		assert_eq!(storage.properties.len(), 1);
+		assert_eq!(
+			storage.tree["Shared"].path.as_ref().unwrap().path(),
+			dir.join("base/shared")
+		);
+		assert_eq!(
+			storage.tree["Client"].path.as_ref().unwrap().path(),
+			PathBuf::from("client")
+		);
+
+		let local = Project::load_local(&dir.join("place.project.json")).unwrap();
+
+		assert!(local.inherited.is_empty());
+		assert!(local.node.class_name.is_none());
+
+		fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn circular() {
+		let dir = workspace(
+			"circular",
+			&[
+				("a.project.json", r#"{ "extends": "b.project.json", "tree": {} }"#),
+				("b.project.json", r#"{ "extends": "a.project.json", "tree": {} }"#),
+			],
+		);
+
+		assert!(Project::load(&dir.join("a.project.json")).is_err());
+
+		fs::remove_dir_all(dir).unwrap();
+	}
+}
+
+mod format {
+	use argon::{
+		jsonc,
+		project::{Project, ProjectNode},
+	};
+	use std::fs;
+
+	#[test]
+	fn jsonc() {
+		let source = r#"{
+	// Name of the place
+	"name": "place",
+	"tree": {
+		"$className": "DataModel", /* root */
+		"Workspace": {
+			"$properties": { "Gravity": 100 },
+		},
+		"Lighting": {},
+	},
+}
+"#;
+
+		let stripped = jsonc::strip(source);
+
+		assert_eq!(stripped.len(), source.len());
+		assert!(serde_json::from_str::<serde_json::Value>(&stripped).is_ok());
+
+		let mut project: Project = serde_json::from_str(&stripped).unwrap();
+
+		project.node.tree.remove("Lighting");
+		project
+			.node
+			.tree
+			.insert(String::from("ReplicatedStorage"), ProjectNode::default());
+
+		let updated = jsonc::update(source, &project).unwrap();
+
+		assert!(updated.contains("// Name of the place"));
+		assert!(updated.contains("/* root */"));
+		assert!(updated.contains("\"$properties\": { \"Gravity\": 100 },"));
+		assert!(updated.contains("\t\t\"ReplicatedStorage\": {}"));
+		assert!(!updated.contains("Lighting"));
+		assert!(updated.find("\"name\"") < updated.find("\"tree\""));
+
+		let updated: Project = serde_json::from_str(&jsonc::strip(&updated)).unwrap();
+
+		assert_eq!(updated.node, project.node);
+
+		// Only comments and trailing commas are allowed, not the rest of JSON5
+		let json5 = jsonc::strip("{ name: 'place' }");
+
+		assert!(serde_json::from_str::<serde_json::Value>(&json5).is_err());
+	}
+
+	#[test]
+	fn toml() {
+		let dir = std::env::temp_dir().join(format!("argon-toml-{}", std::process::id()));
+		let path = dir.join("default.project.toml");
+
+		fs::create_dir_all(&dir).unwrap();
+		fs::write(
+			&path,
+			"# Main place\nname = \"place\"\n\n[tree]\n\"$className\" = \"DataModel\"\n\n[tree.Workspace] # world\n\"$path\" = \"src\"\n",
+		)
+		.unwrap();
+
+		let mut project = Project::load(&path).unwrap();
+
+		assert_eq!(project.name, "place");
+		assert!(project.is_place());
+
+		project
+			.node
+			.tree
+			.insert(String::from("Lighting"), ProjectNode::default());
+		project.save(&path).unwrap();
+
+		let contents = fs::read_to_string(&path).unwrap();
+
+		assert!(contents.starts_with("# Main place\n"));
+		assert!(contents.contains("[tree.Workspace] # world"));
+		assert_eq!(Project::load(&path).unwrap().node, project.node);
+
+		fs::remove_dir_all(dir).unwrap();
+	}
+}
+
+mod profiles {
+	use argon::project::Project;
+	use std::{env, fs};
+
+	#[test]
+	fn interpolate() {
+		let dir = std::env::temp_dir().join(format!("argon-profiles-{}", std::process::id()));
+		let path = dir.join("default.project.json");
+
+		env::set_var("ARGON_TEST_PLACE_ID", "42");
+
+		fs::create_dir_all(&dir).unwrap();
+		fs::write(
+			&path,
+			r#"{
+				"name": "${ARGON_TEST_NAME:-game}",
+				"gameId": "${ARGON_TEST_GAME_ID:-1}",
+				"placeIds": ["${ARGON_TEST_PLACE_ID}"],
+				"tree": {
+					"$className": "DataModel",
+					"Workspace": { "$properties": { "Name": "Price: $${PRICE}" } }
+				},
+				"profiles": {
+					"prod": {
+						"gameId": 100,
+						"tree": { "Workspace": { "$attributes": { "Debug": "${ARGON_TEST_UNSET}" } } }
+					}
+				}
+			}"#,
+		)
+		.unwrap();
+
+		let project = Project::load_profile(&path, None).unwrap();
+
+		assert_eq!(project.name, "game");
+		assert_eq!(project.game_id, Some(1));
+		assert_eq!(project.place_ids, vec![42]);
+		assert_eq!(
+			serde_json::to_value(&project.node.tree["Workspace"].properties["Name"]).unwrap(),
+			"Price: ${PRICE}"
+		);
+
+		assert!(Project::load_profile(&path, Some("prod")).is_err());
+
+		env::set_var("ARGON_TEST_UNSET", "true");
+
+		let project = Project::load_profile(&path, Some("prod")).unwrap();
+
+		assert_eq!(project.game_id, Some(100));
+		assert!(project.node.tree["Workspace"].attributes.is_some());
+
+		let mut project = Project::load_local(&path).unwrap();
+		project.place_ids.push(7);
+		project.save(&path).unwrap();
+
+		let contents = fs::read_to_string(&path).unwrap();
+
+		assert!(contents.contains(r#""gameId": "${ARGON_TEST_GAME_ID:-1}""#));
+		assert!(contents.contains(r#""${ARGON_TEST_PLACE_ID}""#));
+
+		fs::remove_dir_all(dir).unwrap();
+	}
+}